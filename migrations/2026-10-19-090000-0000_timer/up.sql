@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- There is at most one timer, so its id can only be 1.
+CREATE TABLE timer (
+    timer_id        INTEGER PRIMARY KEY             NOT NULL DEFAULT 1 CHECK (timer_id = 1),
+    timer_start     DATETIME                        NOT NULL,
+    timer_desc      VARCHAR(255)                    NOT NULL
+);
+
+-- Tickets for the running timer, or for the next one to start when no timer is running.
+CREATE TABLE timer_ticket (
+    proj_key        VARCHAR(10) REFERENCES project  NOT NULL,
+    tick_num        INTEGER                         NOT NULL,
+    PRIMARY KEY (proj_key, tick_num)
+);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Details of a ticket. Time still references tickets by key alone, so they don't need a row here.
+CREATE TABLE ticket (
+    proj_key        VARCHAR(10) REFERENCES project  NOT NULL,
+    tick_num        INTEGER                         NOT NULL,
+    tick_title      VARCHAR(255)                    NOT NULL,
+    tick_url        VARCHAR(255),
+    tick_status     VARCHAR(12)                     NOT NULL DEFAULT 'open',
+    tick_estimate   DECIMAL(4,1),
+    PRIMARY KEY (proj_key, tick_num)
+);
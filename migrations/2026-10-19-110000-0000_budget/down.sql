@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX budget_project;
+DROP INDEX budget_ticket;
+
+DROP TABLE budget;
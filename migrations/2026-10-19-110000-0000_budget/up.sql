@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- A capped budget for a whole project, or for one ticket when tick_num is set. Money budgets are
+-- spent at an hourly rate, since time isn't priced until it's invoiced.
+CREATE TABLE budget (
+    bud_id          INTEGER PRIMARY KEY             NOT NULL,
+    proj_key        VARCHAR(10) REFERENCES project  NOT NULL,
+    tick_num        INTEGER,
+    bud_hours       DECIMAL(6,1),
+    bud_money       DECIMAL(8,2),
+    bud_rate        DECIMAL(6,2),
+    CHECK (bud_hours IS NOT NULL OR bud_money IS NOT NULL),
+    CHECK (bud_money IS NULL OR bud_rate IS NOT NULL)
+);
+
+-- A project has at most one budget of its own, and each of its tickets at most one.
+CREATE UNIQUE INDEX budget_ticket ON budget (proj_key, tick_num) WHERE tick_num IS NOT NULL;
+CREATE UNIQUE INDEX budget_project ON budget (proj_key) WHERE tick_num IS NULL;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Free-form tags such as #meeting, which are created the first time they're used.
+CREATE TABLE tag (
+    tag_name        VARCHAR(25) PRIMARY KEY         NOT NULL
+);
+
+CREATE TABLE time_tag (
+    tag_name        VARCHAR(25) REFERENCES tag      NOT NULL,
+    time_id         INTEGER REFERENCES time         NOT NULL,
+    PRIMARY KEY (tag_name, time_id)
+);
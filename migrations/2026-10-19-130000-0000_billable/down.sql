@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE time DROP COLUMN time_billable;
+
+ALTER TABLE project DROP COLUMN proj_billable;
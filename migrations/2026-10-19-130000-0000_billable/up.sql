@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- Non-billable time is tracked but never invoiced. Entries default to the setting of their
+-- projects when they're logged.
+ALTER TABLE project ADD COLUMN proj_billable BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE time ADD COLUMN time_billable BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- Longer, free-form context for an entry. The description stays the short summary.
+ALTER TABLE time ADD COLUMN time_notes TEXT;
@@ -0,0 +1,20 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER time_insert_audit;
+DROP TRIGGER time_update_audit;
+DROP TRIGGER time_delete_audit;
+DROP TRIGGER ticket_time_insert_audit;
+DROP TRIGGER ticket_time_update_audit;
+DROP TRIGGER ticket_time_delete_audit;
+DROP TRIGGER time_tag_insert_audit;
+DROP TRIGGER time_tag_update_audit;
+DROP TRIGGER time_tag_delete_audit;
+DROP TRIGGER invoice_activity_insert_audit;
+DROP TRIGGER invoice_activity_update_audit;
+DROP TRIGGER invoice_activity_delete_audit;
+DROP TRIGGER invoice_insert_audit;
+DROP TRIGGER invoice_update_audit;
+DROP TRIGGER invoice_delete_audit;
+
+DROP TABLE audit;
+
+DROP TABLE command;
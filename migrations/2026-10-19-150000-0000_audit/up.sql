@@ -0,0 +1,276 @@
+-- Your SQL goes here
+-- Every run of the CLI and every API request is a command. Commands that don't change anything are
+-- removed again once they finish.
+CREATE TABLE command (
+    cmd_id          INTEGER PRIMARY KEY             NOT NULL,
+    cmd_time        DATETIME                        NOT NULL,
+    cmd_line        TEXT                            NOT NULL,
+    -- Set on an undo to the command it reverted, so that the next undo goes further back.
+    cmd_undoes      INTEGER REFERENCES command,
+    cmd_done        BOOLEAN                         NOT NULL DEFAULT FALSE
+);
+
+-- Rows are stored as JSON objects from before and after each change. Changes made while no command
+-- is running, like from another program, have none and can't be undone.
+CREATE TABLE audit (
+    aud_id          INTEGER PRIMARY KEY             NOT NULL,
+    cmd_id          INTEGER REFERENCES command,
+    aud_table       TEXT                            NOT NULL,
+    aud_action      TEXT                            NOT NULL,
+    aud_old         TEXT,
+    aud_new         TEXT,
+    CHECK (aud_action IN ('insert', 'update', 'delete'))
+);
+
+-- Changes belong to the running command, which is inserted when it starts and done when it
+-- finishes. The generated time_dur isn't recorded, since it can't be written back.
+
+CREATE TRIGGER time_insert_audit AFTER INSERT ON time BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'time',
+        'insert',
+        NULL,
+        json_object(
+            'time_id', NEW.time_id,
+            'time_start', NEW.time_start,
+            'time_end', NEW.time_end,
+            'time_desc', NEW.time_desc,
+            'act_num', NEW.act_num,
+            'time_billable', NEW.time_billable,
+            'time_notes', NEW.time_notes
+        )
+    );
+END;
+
+CREATE TRIGGER time_update_audit AFTER UPDATE ON time BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'time',
+        'update',
+        json_object(
+            'time_id', OLD.time_id,
+            'time_start', OLD.time_start,
+            'time_end', OLD.time_end,
+            'time_desc', OLD.time_desc,
+            'act_num', OLD.act_num,
+            'time_billable', OLD.time_billable,
+            'time_notes', OLD.time_notes
+        ),
+        json_object(
+            'time_id', NEW.time_id,
+            'time_start', NEW.time_start,
+            'time_end', NEW.time_end,
+            'time_desc', NEW.time_desc,
+            'act_num', NEW.act_num,
+            'time_billable', NEW.time_billable,
+            'time_notes', NEW.time_notes
+        )
+    );
+END;
+
+CREATE TRIGGER time_delete_audit AFTER DELETE ON time BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'time',
+        'delete',
+        json_object(
+            'time_id', OLD.time_id,
+            'time_start', OLD.time_start,
+            'time_end', OLD.time_end,
+            'time_desc', OLD.time_desc,
+            'act_num', OLD.act_num,
+            'time_billable', OLD.time_billable,
+            'time_notes', OLD.time_notes
+        ),
+        NULL
+    );
+END;
+
+CREATE TRIGGER ticket_time_insert_audit AFTER INSERT ON ticket_time BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'ticket_time',
+        'insert',
+        NULL,
+        json_object(
+            'proj_key', NEW.proj_key,
+            'tick_num', NEW.tick_num,
+            'time_id', NEW.time_id
+        )
+    );
+END;
+
+CREATE TRIGGER ticket_time_update_audit AFTER UPDATE ON ticket_time BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'ticket_time',
+        'update',
+        json_object(
+            'proj_key', OLD.proj_key,
+            'tick_num', OLD.tick_num,
+            'time_id', OLD.time_id
+        ),
+        json_object(
+            'proj_key', NEW.proj_key,
+            'tick_num', NEW.tick_num,
+            'time_id', NEW.time_id
+        )
+    );
+END;
+
+CREATE TRIGGER ticket_time_delete_audit AFTER DELETE ON ticket_time BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'ticket_time',
+        'delete',
+        json_object(
+            'proj_key', OLD.proj_key,
+            'tick_num', OLD.tick_num,
+            'time_id', OLD.time_id
+        ),
+        NULL
+    );
+END;
+
+CREATE TRIGGER time_tag_insert_audit AFTER INSERT ON time_tag BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'time_tag',
+        'insert',
+        NULL,
+        json_object(
+            'tag_name', NEW.tag_name,
+            'time_id', NEW.time_id
+        )
+    );
+END;
+
+CREATE TRIGGER time_tag_update_audit AFTER UPDATE ON time_tag BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'time_tag',
+        'update',
+        json_object(
+            'tag_name', OLD.tag_name,
+            'time_id', OLD.time_id
+        ),
+        json_object(
+            'tag_name', NEW.tag_name,
+            'time_id', NEW.time_id
+        )
+    );
+END;
+
+CREATE TRIGGER time_tag_delete_audit AFTER DELETE ON time_tag BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'time_tag',
+        'delete',
+        json_object(
+            'tag_name', OLD.tag_name,
+            'time_id', OLD.time_id
+        ),
+        NULL
+    );
+END;
+
+CREATE TRIGGER invoice_activity_insert_audit AFTER INSERT ON invoice_activity BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice_activity',
+        'insert',
+        NULL,
+        json_object(
+            'act_num', NEW.act_num,
+            'inv_num', NEW.inv_num,
+            'act_desc', NEW.act_desc,
+            'act_uprice', NEW.act_uprice
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_activity_update_audit AFTER UPDATE ON invoice_activity BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice_activity',
+        'update',
+        json_object(
+            'act_num', OLD.act_num,
+            'inv_num', OLD.inv_num,
+            'act_desc', OLD.act_desc,
+            'act_uprice', OLD.act_uprice
+        ),
+        json_object(
+            'act_num', NEW.act_num,
+            'inv_num', NEW.inv_num,
+            'act_desc', NEW.act_desc,
+            'act_uprice', NEW.act_uprice
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_activity_delete_audit AFTER DELETE ON invoice_activity BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice_activity',
+        'delete',
+        json_object(
+            'act_num', OLD.act_num,
+            'inv_num', OLD.inv_num,
+            'act_desc', OLD.act_desc,
+            'act_uprice', OLD.act_uprice
+        ),
+        NULL
+    );
+END;
+
+CREATE TRIGGER invoice_insert_audit AFTER INSERT ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'insert',
+        NULL,
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_update_audit AFTER UPDATE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'update',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created
+        ),
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_delete_audit AFTER DELETE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'delete',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created
+        ),
+        NULL
+    );
+END;
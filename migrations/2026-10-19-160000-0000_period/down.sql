@@ -0,0 +1,57 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER invoice_insert_audit;
+DROP TRIGGER invoice_update_audit;
+DROP TRIGGER invoice_delete_audit;
+
+CREATE TRIGGER invoice_insert_audit AFTER INSERT ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'insert',
+        NULL,
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_update_audit AFTER UPDATE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'update',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created
+        ),
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_delete_audit AFTER DELETE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'delete',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created
+        ),
+        NULL
+    );
+END;
+
+ALTER TABLE invoice DROP COLUMN inv_end;
+ALTER TABLE invoice DROP COLUMN inv_start;
@@ -0,0 +1,69 @@
+-- Your SQL goes here
+-- The first and last day an invoice bills for, when it isn't the whole of its month. Its month is
+-- then the one the period starts in.
+ALTER TABLE invoice ADD COLUMN inv_start DATE;
+ALTER TABLE invoice ADD COLUMN inv_end DATE
+    CHECK ((inv_start IS NULL) = (inv_end IS NULL) AND inv_end >= inv_start);
+
+-- The period has to be recorded for changes to invoices to be undone.
+DROP TRIGGER invoice_insert_audit;
+DROP TRIGGER invoice_update_audit;
+DROP TRIGGER invoice_delete_audit;
+
+CREATE TRIGGER invoice_insert_audit AFTER INSERT ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'insert',
+        NULL,
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created,
+            'inv_start', NEW.inv_start,
+            'inv_end', NEW.inv_end
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_update_audit AFTER UPDATE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'update',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created,
+            'inv_start', OLD.inv_start,
+            'inv_end', OLD.inv_end
+        ),
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created,
+            'inv_start', NEW.inv_start,
+            'inv_end', NEW.inv_end
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_delete_audit AFTER DELETE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'delete',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created,
+            'inv_start', OLD.inv_start,
+            'inv_end', OLD.inv_end
+        ),
+        NULL
+    );
+END;
@@ -0,0 +1,91 @@
+-- Your SQL goes here
+-- How invoices are numbered, either for one recipient or, without one, for every recipient that
+-- doesn't have a scheme of its own.
+CREATE TABLE scheme (
+    sch_id          INTEGER PRIMARY KEY             NOT NULL,
+    recip_id        VARCHAR(5) REFERENCES recipient UNIQUE,
+    sch_format      VARCHAR(50)                     NOT NULL
+);
+
+-- The formatted number an invoice was issued with, along with the series it was allocated from
+-- and its place in it. Invoices issued without a scheme only have their plain number.
+ALTER TABLE invoice ADD COLUMN inv_ref VARCHAR(50);
+ALTER TABLE invoice ADD COLUMN inv_series VARCHAR(50);
+ALTER TABLE invoice ADD COLUMN inv_seq INTEGER;
+
+CREATE UNIQUE INDEX invoice_ref ON invoice (inv_ref);
+CREATE UNIQUE INDEX invoice_series_seq ON invoice (inv_series, inv_seq);
+
+DROP TRIGGER invoice_insert_audit;
+DROP TRIGGER invoice_update_audit;
+DROP TRIGGER invoice_delete_audit;
+
+CREATE TRIGGER invoice_insert_audit AFTER INSERT ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'insert',
+        NULL,
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created,
+            'inv_start', NEW.inv_start,
+            'inv_end', NEW.inv_end,
+            'inv_ref', NEW.inv_ref,
+            'inv_series', NEW.inv_series,
+            'inv_seq', NEW.inv_seq
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_update_audit AFTER UPDATE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'update',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created,
+            'inv_start', OLD.inv_start,
+            'inv_end', OLD.inv_end,
+            'inv_ref', OLD.inv_ref,
+            'inv_series', OLD.inv_series,
+            'inv_seq', OLD.inv_seq
+        ),
+        json_object(
+            'inv_num', NEW.inv_num,
+            'inv_month', NEW.inv_month,
+            'recip_id', NEW.recip_id,
+            'inv_created', NEW.inv_created,
+            'inv_start', NEW.inv_start,
+            'inv_end', NEW.inv_end,
+            'inv_ref', NEW.inv_ref,
+            'inv_series', NEW.inv_series,
+            'inv_seq', NEW.inv_seq
+        )
+    );
+END;
+
+CREATE TRIGGER invoice_delete_audit AFTER DELETE ON invoice BEGIN
+    INSERT INTO audit (cmd_id, aud_table, aud_action, aud_old, aud_new) VALUES (
+        (SELECT MAX(cmd_id) FROM command WHERE NOT cmd_done),
+        'invoice',
+        'delete',
+        json_object(
+            'inv_num', OLD.inv_num,
+            'inv_month', OLD.inv_month,
+            'recip_id', OLD.recip_id,
+            'inv_created', OLD.inv_created,
+            'inv_start', OLD.inv_start,
+            'inv_end', OLD.inv_end,
+            'inv_ref', OLD.inv_ref,
+            'inv_series', OLD.inv_series,
+            'inv_seq', OLD.inv_seq
+        ),
+        NULL
+    );
+END;
@@ -21,7 +21,9 @@
 
 #set align(right)
 
-Reference Number: #h(1fr) #invoice.num\
+Reference Number: #h(1fr) #invoice.number\
+Period:           #h(1fr) #invoice.period.start.display("[day]/[month]/[year]")
+                          to #invoice.period.end.display("[day]/[month]/[year]")\
 Date:             #h(1fr) #nth(invoice.created.display("[day]"))
                           of #invoice.created.display("[month repr:long] [year]")
 
@@ -0,0 +1,396 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use diesel::prelude::*;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::api::response::{ApiError, Response};
+use crate::orm::filter::TimeFilter;
+use crate::orm::insert::{ActivityChanges, LoggedTime, NewEntry, ProjectChanges};
+use crate::orm::model::{Invoice, InvoiceActivity, Project, Recipient};
+use crate::orm::query::{ActivityWithTickets, InvoiceWithActivities};
+use crate::orm::ticket::{Ticket, TicketTitles};
+use crate::service::audit::{finish_command, start_command};
+use crate::service::document::{invoice_pdf, ticket_titles, timesheet_csv};
+use crate::service::invoice::{
+    GroupRule, create_invoice, draft_activities, edit_activity, new_invoice, next_invoice_num
+};
+use crate::service::project::{add_project, edit_project, select_projects};
+use crate::service::recipient::{add_recipient, select_recipients};
+use crate::service::time::{delete_time, log_time, select_time, select_times, update_time};
+use crate::util::date::{Date, DateTime, Period, Week};
+
+/// The parts of an HTTP request the API looks at, independent of the server.
+#[derive(Debug, Default)]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+impl From<diesel::result::Error> for ApiError {
+    fn from(value: diesel::result::Error) -> Self {
+        ApiError::internal(format!("Database error:\n{value}"))
+    }
+}
+
+/// Handle a request in its own transaction, so that failed writes leave nothing behind.
+pub fn handle(conn: &mut SqliteConnection, token: Option<&str>, request: &Request) -> Response {
+    if let Some(token) = token {
+        let given = request.authorization.as_deref()
+            .and_then(|a| a.strip_prefix("Bearer "));
+
+        if !given.is_some_and(|g| constant_time_eq(g.as_bytes(), token.as_bytes())) {
+            return ApiError::unauthorized().into_response();
+        }
+    }
+
+    // Each request is a command of its own, so that its changes can be undone together.
+    conn.transaction(|conn| {
+        let cmd_id = start_command(conn, &format!("{} {}", request.method, request.url))?;
+        let response = route(conn, request)?;
+        finish_command(conn, cmd_id)?;
+        Ok(response)
+    }).unwrap_or_else(ApiError::into_response)
+}
+
+/// Compare without returning early, so that response times don't reveal the token.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn route(conn: &mut SqliteConnection, request: &Request) -> Result<Response, ApiError> {
+    let (path, query) = request.url.split_once('?').unwrap_or((&request.url, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let mut query = parse_query(query)?;
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["times"]) => Response::json(200, &select_times(conn, time_filter(&query)?, None)?),
+        ("POST", ["times"]) => {
+            let time = log_time(conn, parse_entry(&request.body)?)?;
+            Response::json(201, &select_time(conn, time.time_id)?)
+        },
+        ("GET", ["times", id]) => Response::json(200, &select_time(conn, parse_id(id)?)?),
+        ("PUT", ["times", id]) => {
+            let id = parse_id(id)?;
+            update_time(conn, id, parse_entry(&request.body)?)?;
+            Response::json(200, &select_time(conn, id)?)
+        },
+        ("DELETE", ["times", id]) => {
+            delete_time(conn, parse_id(id)?)?;
+            Ok(Response::no_content())
+        },
+        ("GET", ["timesheet"]) => {
+            let titles = titles(conn, &mut query)?;
+            let detailed = detailed(&mut query);
+            let times = select_times(conn, time_filter(&query)?, None)?;
+            Ok(Response::bytes("text/csv", timesheet_csv(times, &titles, detailed)?))
+        },
+        ("GET", ["projects"]) => Response::json(200, &select_projects(conn)?),
+        ("POST", ["projects"]) => {
+            let body: ProjectBody = parse_body(&request.body, "project")?;
+            let project = Project {
+                proj_key: body.key,
+                proj_name: body.name,
+                proj_billable: body.billable.unwrap_or(true),
+            };
+            let response = Response::json(201, &project)?;
+            add_project(conn, project)?;
+            Ok(response)
+        },
+        ("PATCH", ["projects", key]) => {
+            let body: ProjectChangesBody = parse_body(&request.body, "project changes")?;
+            let project = edit_project(conn, key, ProjectChanges {
+                proj_name: body.name,
+                proj_billable: body.billable,
+            })?;
+            Response::json(200, &project)
+        },
+        ("GET", ["recipients"]) => Response::json(200, &select_recipients(conn)?),
+        ("POST", ["recipients"]) => {
+            let body: RecipientBody = parse_body(&request.body, "recipient")?;
+            let recipient = Recipient {
+                recip_id: body.id,
+                recip_name: body.name,
+                recip_addr: body.address,
+            };
+            let response = Response::json(201, &recipient)?;
+            add_recipient(conn, recipient)?;
+            Ok(response)
+        },
+        ("GET", ["invoices"]) => Response::json(200, &select_invoices(conn, None)?),
+        ("POST", ["invoices"]) => {
+            let body: InvoiceBody = parse_body(&request.body, "invoice")?;
+            let period = Period::from_str(&body.period).map_err(ApiError::bad_request)?;
+            let rule = match body.group_by {
+                Some(rule) => GroupRule::from_str(&rule, true).map_err(ApiError::bad_request)?,
+                None => GroupRule::Project,
+            };
+
+            let drafts = draft_activities(conn, &period, &body.recipient, rule)?;
+            let inv_num = next_invoice_num(conn)?;
+            let invoice = new_invoice(inv_num, &period, body.recipient);
+            create_invoice(conn, invoice, body.uprice, drafts)?;
+
+            Response::json(201, &select_invoice(conn, inv_num)?)
+        },
+        ("GET", ["invoices", num]) => {
+            Response::json(200, &select_invoice(conn, parse_id(num)?)?)
+        },
+        ("GET", ["invoices", num, "pdf"]) => {
+            let titles = titles(conn, &mut query)?;
+            let invoice = select_invoice(conn, parse_id(num)?)?;
+            Ok(Response::bytes("application/pdf", invoice_pdf(invoice, &titles)?))
+        },
+        ("GET", ["invoices", num, "timesheet"]) => {
+            let titles = titles(conn, &mut query)?;
+            let detailed = detailed(&mut query);
+            let invoice = select_invoice(conn, parse_id(num)?)?;
+            let times = select_times(conn, time_filter(&query)?, Some(invoice.inv_num))?;
+            Ok(Response::bytes("text/csv", timesheet_csv(times, &titles, detailed)?))
+        },
+        ("GET", ["activities"]) => {
+            use crate::orm::schema::invoice_activity;
+
+            let mut activities = InvoiceActivity::query()
+                .order(invoice_activity::act_num)
+                .into_boxed();
+
+            for (key, value) in &query {
+                match key.as_str() {
+                    "invoice" => activities = activities
+                        .filter(invoice_activity::inv_num.eq(parse_id(value)?)),
+                    _ => Err(ApiError::bad_request(format!("Unknown parameter '{key}'")))?,
+                }
+            }
+
+            Response::json(200, &ActivityWithTickets::from_query(activities, conn)?)
+        },
+        ("PATCH", ["activities", num]) => {
+            use crate::orm::schema::invoice_activity;
+
+            let body: ActivityBody = parse_body(&request.body, "activity changes")?;
+            let activity = edit_activity(conn, parse_id(num)?, ActivityChanges {
+                act_desc: body.desc,
+                act_uprice: body.uprice,
+            })?;
+
+            let mut edited = ActivityWithTickets::from_query(
+                InvoiceActivity::query().filter(invoice_activity::act_num.eq(activity.act_num)),
+                conn
+            )?;
+            Response::json(200, &edited.pop())
+        },
+        (
+            _,
+            ["times" | "timesheet" | "projects" | "recipients" | "invoices" | "activities"]
+                | ["times" | "invoices" | "projects" | "activities", _]
+                | ["invoices", _, "pdf" | "timesheet"]
+        ) => Err(ApiError::method_not_allowed()),
+        _ => Err(ApiError::not_found(format!("No endpoint at '{path}'"))),
+    }
+}
+
+fn parse_id(id: &str) -> Result<i32, ApiError> {
+    id.parse()
+        .map_err(|_| ApiError::bad_request(format!("'{id}' isn't a valid number")))
+}
+
+/// Split a query string into decoded key-value pairs.
+fn parse_query(query: &str) -> Result<Vec<(String, String)>, ApiError> {
+    query.split('&')
+        .filter(|p| !p.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((decode(key)?, decode(value)?))
+        })
+        .collect()
+}
+
+/// Undo percent-encoding, where `+` also stands for a space.
+fn decode(s: &str) -> Result<String, ApiError> {
+    let invalid = || ApiError::bad_request(format!("Invalid query string encoding '{s}'"));
+
+    let mut bytes = vec![];
+    let mut rest = s.bytes();
+
+    while let Some(b) = rest.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [rest.next().ok_or_else(invalid)?, rest.next().ok_or_else(invalid)?];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            },
+            b => bytes.push(b),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Build a filter from query parameters named after the `TimeFilter` options.
+fn time_filter(query: &[(String, String)]) -> Result<TimeFilter, ApiError> {
+    let mut filter = TimeFilter::default();
+
+    for (key, value) in query {
+        match key.as_str() {
+            "from" => filter.from = Some(Date::from_str(value).map_err(ApiError::bad_request)?),
+            "to" => filter.to = Some(Date::from_str(value).map_err(ApiError::bad_request)?),
+            "week" => filter.week = Some(Week::from_str(value).map_err(ApiError::bad_request)?),
+            "project" => filter.project = Some(value.clone()),
+            "ticket" => {
+                filter.ticket = Some(Ticket::from_str(value).map_err(ApiError::bad_request)?);
+            },
+            "desc" => filter.desc = Some(value.clone()),
+            "tag" => filter.tag = Some(value.trim_start_matches('#').to_owned()),
+            "unbilled" => filter.unbilled = matches!(value.as_str(), "" | "true" | "1"),
+            "billable" => match value.as_str() {
+                "" | "true" | "1" => filter.billable = true,
+                _ => filter.non_billable = true,
+            },
+            _ => Err(ApiError::bad_request(format!("Unknown parameter '{key}'")))?,
+        }
+    }
+
+    Ok(filter)
+}
+
+/// Take the `titles` parameter out of the query, loading ticket titles for documents when it's set.
+fn titles(
+    conn: &mut SqliteConnection,
+    query: &mut Vec<(String, String)>
+) -> Result<TicketTitles, ApiError> {
+    let set = query.iter()
+        .any(|(key, value)| key == "titles" && matches!(value.as_str(), "" | "true" | "1"));
+    query.retain(|(key, _)| key != "titles");
+
+    Ok(ticket_titles(conn, set)?)
+}
+
+/// Take the `detailed` parameter out of the query, which adds notes to timesheets.
+fn detailed(query: &mut Vec<(String, String)>) -> bool {
+    let set = query.iter()
+        .any(|(key, value)| key == "detailed" && matches!(value.as_str(), "" | "true" | "1"));
+    query.retain(|(key, _)| key != "detailed");
+
+    set
+}
+
+/// A time entry as sent by clients, with times and tickets in the same formats as the CLI.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimeBody {
+    start: String,
+    end: String,
+    desc: String,
+    #[serde(default)]
+    tickets: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    activity: Option<i32>,
+    #[serde(default)]
+    billable: Option<bool>,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+fn parse_entry(body: &str) -> Result<NewEntry, ApiError> {
+    let body: TimeBody = parse_body(body, "time entry")?;
+
+    Ok(NewEntry {
+        time: LoggedTime {
+            time_start: *DateTime::from_str(&body.start).map_err(ApiError::bad_request)?,
+            time_end: *DateTime::from_str(&body.end).map_err(ApiError::bad_request)?,
+            time_desc: body.desc,
+            act_num: body.activity,
+            time_notes: body.notes,
+        },
+        tickets: body.tickets.iter()
+            .map(|t| Ticket::from_str(t))
+            .collect::<Result<_, _>>()
+            .map_err(ApiError::bad_request)?,
+        tags: body.tags,
+        billable: body.billable,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectBody {
+    key: String,
+    name: String,
+    #[serde(default)]
+    billable: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectChangesBody {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    billable: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecipientBody {
+    id: String,
+    name: String,
+    address: String,
+}
+
+/// An invoice to build from the unbilled time of a period, like `invoice build`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InvoiceBody {
+    period: String,
+    recipient: String,
+    uprice: f64,
+    #[serde(default)]
+    group_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ActivityBody {
+    #[serde(default)]
+    desc: Option<String>,
+    #[serde(default)]
+    uprice: Option<f64>,
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str, what: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::bad_request(format!("Invalid {what}:\n{e}")))
+}
+
+fn select_invoices(
+    conn: &mut SqliteConnection,
+    inv_num: Option<i32>
+) -> Result<Vec<InvoiceWithActivities>, ApiError> {
+    use crate::orm::schema::{invoice, recipient};
+
+    let mut query = invoice::table
+        .inner_join(recipient::table)
+        .order(invoice::inv_num)
+        .select((Invoice::as_select(), Recipient::as_select()))
+        .into_boxed();
+
+    if let Some(inv_num) = inv_num {
+        query = query.filter(invoice::inv_num.eq(inv_num));
+    }
+
+    Ok(InvoiceWithActivities::from_query(query, conn)?)
+}
+
+fn select_invoice(
+    conn: &mut SqliteConnection,
+    inv_num: i32
+) -> Result<InvoiceWithActivities, ApiError> {
+    select_invoices(conn, Some(inv_num))?
+        .pop()
+        .ok_or_else(|| ApiError::not_found(format!("No invoice with number {inv_num}")))
+}
@@ -0,0 +1,87 @@
+use derive_more::Display;
+use serde::Serialize;
+
+use crate::service::error::ServiceError;
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json<T: Serialize>(status: u16, value: &T) -> Result<Response, ApiError> {
+        Ok(Response {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(value)
+                .map_err(|e| ApiError::internal(format!("Error serializing response:\n{e}")))?,
+        })
+    }
+
+    pub fn bytes(content_type: &'static str, body: Vec<u8>) -> Response {
+        Response { status: 200, content_type, body }
+    }
+
+    pub fn no_content() -> Response {
+        Response { status: 204, content_type: "application/json", body: vec![] }
+    }
+}
+
+/// An error along with the HTTP status it should be reported with.
+#[derive(Debug, Display)]
+#[display("{message}")]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn bad_request(message: impl Into<String>) -> ApiError {
+        ApiError { status: 400, message: message.into() }
+    }
+
+    pub fn unauthorized() -> ApiError {
+        ApiError { status: 401, message: "Missing or invalid bearer token".to_owned() }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> ApiError {
+        ApiError { status: 404, message: message.into() }
+    }
+
+    pub fn method_not_allowed() -> ApiError {
+        ApiError { status: 405, message: "Method not allowed".to_owned() }
+    }
+
+    pub fn internal(message: impl Into<String>) -> ApiError {
+        ApiError { status: 500, message: message.into() }
+    }
+
+    pub fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body {
+            error: String,
+        }
+
+        Response {
+            status: self.status,
+            content_type: "application/json",
+            body: serde_json::to_vec(&Body { error: self.message }).unwrap_or_default(),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<ServiceError> for ApiError {
+    fn from(value: ServiceError) -> Self {
+        let status = match value {
+            ServiceError::NotFound(_) => 404,
+            ServiceError::Ambiguous(_) | ServiceError::Invalid(_) => 400,
+            ServiceError::Render(_) | ServiceError::File(_) | ServiceError::Database { .. } => 500,
+        };
+
+        ApiError { status, message: value.to_string() }
+    }
+}
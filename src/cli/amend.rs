@@ -1,16 +1,115 @@
-use diesel::SqliteConnection;
+use diesel::prelude::*;
 
-use crate::{cli::args::AmendArgs, util::error::DynResult};
+use crate::cli::{args::{AmendArgs, TimeProperty}, edit::edit_text};
+use crate::orm::insert::{LoggedTime, NewEntry};
+use crate::orm::model::Time;
+use crate::service::time::{delete_time, select_time, update_time};
+use crate::util::error::DynResult;
 
 pub fn amend(conn: &mut SqliteConnection, args: AmendArgs) -> DynResult<()> {
-    dbg!(&args);
+    let time_id = match args.time_id {
+        Some(time_id) => time_id,
+        None => last_time_id(conn)?,
+    };
+
     if args.delete {
-        if !args.property.is_empty() {
-            todo!()
-        }
-        todo!()
-    } else {
-        todo!()
+        delete_time(conn, time_id)?;
+        println!("Deleted entry {time_id}");
+        return Ok(());
+    }
+
+    let time = select_time(conn, time_id)?;
+
+    let mut entry = NewEntry {
+        time: LoggedTime {
+            time_start: *time.time_start,
+            time_end: *time.time_end,
+            time_desc: time.time_desc,
+            act_num: time.act_num,
+            time_notes: time.time_notes,
+        },
+        tickets: time.tickets,
+        tags: time.tags,
+        billable: args.billable.value().or(Some(time.time_billable)),
+    };
+
+    for property in args.property {
+        apply(&mut entry, property);
+    }
+
+    if let Some(desc) = args.desc {
+        entry.time.time_desc = desc;
+    }
+
+    if let Some(notes) = args.notes {
+        let notes = match notes {
+            Some(notes) => notes,
+            None => edit_text("notes", entry.time.time_notes.as_deref().unwrap_or_default())?,
+        };
+
+        entry.time.time_notes = Some(notes.trim_end().to_owned()).filter(|n| !n.is_empty());
     }
+
+    update_time(conn, time_id, entry)?;
+
+    println!("Amended entry {time_id}");
+
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Ask for the notes in $EDITOR when they're to be edited, filling them and the entry into the
+/// arguments. This happens before the command's transaction, so that the database isn't locked
+/// while the editor is open.
+pub fn edit_notes(conn: &mut SqliteConnection, mut args: AmendArgs) -> DynResult<AmendArgs> {
+    if args.notes != Some(None) {
+        return Ok(args);
+    }
+
+    let time_id = match args.time_id {
+        Some(time_id) => time_id,
+        None => last_time_id(conn)?,
+    };
+    let time = select_time(conn, time_id)?;
+
+    args.time_id = Some(time_id);
+    args.notes = Some(Some(edit_text("notes", time.time_notes.as_deref().unwrap_or_default())?));
+
+    Ok(args)
+}
+
+fn apply(entry: &mut NewEntry, property: TimeProperty) {
+    let time = &mut entry.time;
+
+    match property {
+        // Moving an entry to another day keeps its times.
+        TimeProperty::Date(date) => {
+            time.time_start = date.and_time(time.time_start.time());
+            time.time_end = date.and_time(time.time_end.time());
+        },
+        TimeProperty::Time(range) => {
+            let date = time.time_start.date();
+            time.time_start = date.and_time(range.start);
+            time.time_end = date.and_time(range.end);
+        },
+        TimeProperty::Activity(act_num) => time.act_num = Some(act_num),
+        TimeProperty::Ticket(ticket) => entry.tickets.push(ticket),
+        TimeProperty::Untick(ticket) => entry.tickets.retain(|t| *t != ticket),
+        TimeProperty::Tag(tag) => entry.tags.push(tag),
+        TimeProperty::Untag(tag) => entry.tags.retain(|t| *t != tag),
+        TimeProperty::Desc(desc) => time.time_desc = desc,
+    }
+}
+
+fn last_time_id(conn: &mut SqliteConnection) -> DynResult<i32> {
+    use crate::orm::schema::time;
+
+    Ok(
+        Time::query()
+            .order(time::time_id.desc())
+            .first(conn)
+            .optional()
+            .map_err(|e| format!("Error retrieving time from database:\n{e}"))?
+            .ok_or("No time has been logged yet")?
+            .time_id
+    )
+}
@@ -1,9 +1,14 @@
 use std::{path::PathBuf, str::FromStr};
 
 use chrono::{NaiveTime, TimeDelta};
-use clap::{Args, Parser, Subcommand, builder::styling::Styles};
+use clap::{Args, Parser, Subcommand, ValueEnum, builder::styling::Styles};
+use derive_more::Display as DisplayDerive;
 
-use crate::{cli::patterns::{ACTIVITY_PATTERN, DATE_PATTERN, TICKET_PATTERN, TIME_RANGE_PATTERN, TimeRangePatternCaptures}, orm::ticket::Ticket, util::date::{Date, Month}};
+use crate::{cli::patterns::{ACTIVITY_PATTERN, DATE_PATTERN, TAG_PATTERN, TICKET_PATTERN, TIME_RANGE_PATTERN, TicketPatternCaptures, TimeRangePatternCaptures}, orm::ticket::Ticket, util::date::{Date, Period}};
+use crate::service::numbering::NumberFormat;
+
+pub use crate::orm::filter::{DocIdentifier, TimeFilter};
+pub use crate::service::{budget::BudgetTarget, invoice::GroupRule};
 
 pub const CARGO_STYLES: Styles = {
     use clap_cargo::style::*;
@@ -28,6 +33,11 @@ pub struct CliArgs {
 
     #[arg(long, short = 'i', global = true)]
     pub database: Option<String>,
+
+    /// How many automatic backups to keep, which are taken before commands that change data. Zero
+    /// turns them off. Defaults to $TIME_TRACKER_KEEP_BACKUPS, or 10.
+    #[arg(long, global = true)]
+    pub keep_backups: Option<usize>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -38,6 +48,52 @@ pub enum Action {
     Amend(AmendArgs),
     #[command(visible_alias = "ls")]
     List(ListArgs),
+    #[command(visible_alias = "inv")]
+    Invoice(InvoiceArgs),
+    Report(ReportArgs),
+    Import(ImportArgs),
+    Export(ExportArgs),
+    Edit(EditArgs),
+    Suggest(SuggestArgs),
+    Timer(TimerArgs),
+    Hook(HookArgs),
+    Tui(TuiArgs),
+    Serve(ServeArgs),
+    #[command(visible_alias = "proj")]
+    Project(ProjectArgs),
+    Ticket(TicketArgs),
+    Budget(BudgetArgs),
+    Undo(UndoArgs),
+    History(HistoryArgs),
+    Backup(BackupArgs),
+    Doctor(DoctorArgs),
+}
+
+impl Action {
+    /// Whether a backup is taken before the action runs, because it can change data. The timer
+    /// and hook run too often for that, as their backups would soon rotate out the ones taken
+    /// before anything worth undoing.
+    pub fn takes_backup(&self) -> bool {
+        match self {
+            Action::Generate(_)
+                | Action::List(_)
+                | Action::Report(_)
+                | Action::Export(_)
+                | Action::Suggest(_)
+                | Action::Timer(_)
+                | Action::Hook(_)
+                | Action::History(_)
+                | Action::Backup(_) => false,
+            Action::Project(args) => !matches!(args.action, ProjectAction::List(_)),
+            Action::Ticket(args) => !matches!(args.action, TicketAction::List(_)),
+            Action::Budget(args) => !matches!(args.action, BudgetAction::Report(_)),
+            Action::Invoice(args) => !matches!(
+                &args.action,
+                InvoiceAction::Numbering(NumberingArgs { action: NumberingAction::List(_) })
+            ),
+            _ => true,
+        }
+    }
 }
 
 #[derive(Debug, Args)]
@@ -48,8 +104,17 @@ pub struct GenerateArgs {
     #[arg(global = true, value_parser = DocIdentifier::from_str)]
     pub ident: Option<DocIdentifier>,
 
+    /// A formatted invoice number, which is otherwise only recognised if it fits a numbering
+    /// scheme.
+    #[arg(long = "ref", global = true, conflicts_with = "ident")]
+    pub inv_ref: Option<String>,
+
     #[arg(long, short, global = true)]
     pub output: Option<PathBuf>,
+
+    /// Show tickets with their titles, e.g. "ABC-12 Fix login timeout", instead of just the key.
+    #[arg(long, global = true)]
+    pub titles: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -57,33 +122,17 @@ pub enum DocType {
     #[command(visible_alias = "inv")]
     Invoice,
     #[command(visible_alias = "ts")]
-    Timesheet,
-}
-
-#[derive(Debug, Clone)]
-pub enum DocIdentifier {
-    Num(i32),
-    Month(Month),
+    Timesheet(TimesheetArgs),
 }
 
-impl Default for DocIdentifier {
-    fn default() -> Self {
-        DocIdentifier::Month(Month::default())
-    }
-}
-
-impl FromStr for DocIdentifier {
-    type Err = String;
+#[derive(Debug, Args)]
+pub struct TimesheetArgs {
+    #[command(flatten)]
+    pub filter: TimeFilter,
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(num) = s.parse() {
-            Ok(DocIdentifier::Num(num))
-        } else if let Ok(date) = s.parse() {
-            Ok(DocIdentifier::Month(date))
-        } else {
-            Err("Value doesn't match format of numeric id or month".into())
-        }
-    }
+    /// Add a column with the notes of each entry.
+    #[arg(long)]
+    pub detailed: bool,
 }
 
 #[derive(Debug, Args)]
@@ -99,8 +148,75 @@ pub struct LogArgs {
 
     pub description: String,
 
-    #[arg(trailing_var_arg = true, value_parser = Ticket::from_str)]
-    pub tickets: Vec<Ticket>,
+    #[command(flatten)]
+    pub billable: BillableFlags,
+
+    /// Longer notes to keep with the entry, which can span several lines.
+    #[arg(long, short)]
+    pub notes: Option<String>,
+
+    /// Tickets, and tags written as #tag.
+    #[arg(trailing_var_arg = true, value_parser = EntryLabel::from_str)]
+    pub labels: Vec<EntryLabel>,
+}
+
+// Flags overriding whether time is billable. Without either, time follows its projects.
+#[derive(Debug, Clone, Default, Args)]
+pub struct BillableFlags {
+    /// Mark as billable.
+    #[arg(long, conflicts_with = "non_billable")]
+    pub billable: bool,
+
+    /// Mark as non-billable, so that it's never invoiced.
+    #[arg(long)]
+    pub non_billable: bool,
+}
+
+impl BillableFlags {
+    pub fn value(&self) -> Option<bool> {
+        match (self.billable, self.non_billable) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// A ticket or tag given after the description of an entry.
+#[derive(Debug, Clone)]
+pub enum EntryLabel {
+    Ticket(Ticket),
+    Tag(String),
+}
+
+impl EntryLabel {
+    /// Separate labels into tickets and tag names.
+    pub fn split(labels: Vec<EntryLabel>) -> (Vec<Ticket>, Vec<String>) {
+        let mut tickets = vec![];
+        let mut tags = vec![];
+
+        for label in labels {
+            match label {
+                EntryLabel::Ticket(ticket) => tickets.push(ticket),
+                EntryLabel::Tag(tag) => tags.push(tag),
+            }
+        }
+
+        (tickets, tags)
+    }
+}
+
+impl FromStr for EntryLabel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match TAG_PATTERN.captures(s) {
+            Some(groups) if groups.remove.is_none() => {
+                Ok(EntryLabel::Tag(groups.name.content.to_owned()))
+            },
+            _ => Ok(EntryLabel::Ticket(s.parse()?)),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -148,14 +264,37 @@ impl FromStr for TimeRange {
 
 #[derive(Debug, Args)]
 pub struct AmendArgs {
-    // TODO: Need a method of showing the ids without manual access.
+    /// The entry to amend, as shown by `list time`. Defaults to the most recently logged entry.
     #[arg(long, short)]
     pub time_id: Option<i32>,
 
-    #[arg(long, short, conflicts_with = "property")]
+    /// Delete the entry instead.
+    #[arg(
+        long,
+        short,
+        conflicts_with_all = ["property", "billable", "non_billable", "notes", "desc"]
+    )]
     pub delete: bool,
 
-    #[arg(required = true, value_parser = TimeProperty::from_str, trailing_var_arg = true)]
+    #[command(flatten)]
+    pub billable: BillableFlags,
+
+    /// Replace the notes, or edit them in $EDITOR when no text is given. Empty notes are removed.
+    #[arg(long, short, num_args = 0..=1, require_equals = true)]
+    pub notes: Option<Option<String>>,
+
+    /// Replace the description. Unlike one given as a property, it doesn't need a space.
+    #[arg(long)]
+    pub desc: Option<String>,
+
+    /// Properties to change: a date, time range, activity number, ticket to add, -ticket to
+    /// remove, #tag to add, -#tag to remove or a description.
+    #[arg(
+        required_unless_present_any = ["delete", "billable", "non_billable", "notes", "desc"],
+        value_parser = TimeProperty::from_str,
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
     pub property: Vec<TimeProperty>,
 }
 
@@ -165,58 +304,747 @@ pub enum TimeProperty {
     Time(TimeRange),
     Activity(i32),
     Ticket(Ticket),
+    Untick(Ticket),
+    Tag(String),
+    Untag(String),
     Desc(String),
 }
 
-// TODO: Test that this actually works.
-
 impl FromStr for TimeProperty {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Patterns are sorted by complexity.
-        Ok(if ACTIVITY_PATTERN.is_match(s) {
+        // Patterns are sorted by complexity, after tags, which are marked by their '#'. Dates go
+        // before tickets, since the ticket pattern also matches them, and a ticket has to be the
+        // whole value rather than a mention in a description.
+        Ok(if let Some(groups) = TAG_PATTERN.captures(s) {
+            match groups.remove {
+                Some(_) => TimeProperty::Untag(groups.name.content.to_owned()),
+                None => TimeProperty::Tag(groups.name.content.to_owned()),
+            }
+        } else if ACTIVITY_PATTERN.is_match(s) {
             TimeProperty::Activity(
                 s.parse()
                     .map_err(|e| format!("Error parsing activity number:\n{e}"))?
             )
-        } else if let Some(groups) = TICKET_PATTERN.captures(s) {
-            TimeProperty::Ticket(
-                Ticket::try_from_match(groups)?
-            )
         } else if DATE_PATTERN.is_match(s) {
             TimeProperty::Date(
                 s.parse()?
             )
+        } else if let Some(groups) = whole_ticket(s) {
+            TimeProperty::Ticket(
+                Ticket::try_from_match(groups)?
+            )
+        } else if let Some(groups) = s.strip_prefix('-').and_then(whole_ticket) {
+            TimeProperty::Untick(
+                Ticket::try_from_match(groups)?
+            )
         } else if let Some(groups) = TIME_RANGE_PATTERN.captures(s) {
             TimeProperty::Time(
                 TimeRange::try_from_match(groups)?
             )
         } else if !s.contains(' ') {
-            Err("Value would be parsed as a description but contains no space.")?
+            Err("Value would be parsed as a description but contains no space, use --desc.")?
         } else {
             TimeProperty::Desc(s.to_owned())
         })
     }
 }
 
+/// Match a ticket that is the whole value, rather than one mentioned in a description.
+fn whole_ticket(s: &str) -> Option<TicketPatternCaptures<'_>> {
+    TICKET_PATTERN.captures(s)
+        .filter(|groups| groups.proj_key.start == 0 && groups.tick_num.end == s.len())
+}
+
 #[derive(Debug, Args)]
 pub struct ListArgs {
     #[command(subcommand)]
     pub entry_type: EntryType,
 
-    #[arg(global = true, long, short, conflicts_with = "ident")]
+    #[arg(global = true, long, short, conflicts_with_all = ["ident", "inv_ref"])]
     pub all: bool,
 
     #[arg(global = true, value_parser = DocIdentifier::from_str)]
     pub ident: Option<DocIdentifier>,
+
+    /// A formatted invoice number, which is otherwise only recognised if it fits a numbering
+    /// scheme.
+    #[arg(long = "ref", global = true, conflicts_with = "ident")]
+    pub inv_ref: Option<String>,
+
+    #[arg(global = true, long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Ndjson,
+    Csv,
 }
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum EntryType {
-    Time,
+    Time(TimeFilter),
     #[command(visible_alias = "act")]
     Activity,
     #[command(visible_alias = "inv")]
     Invoice,
-}
\ No newline at end of file
+    Unbilled(UnbilledArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct UnbilledArgs {
+    #[arg(long, short, value_enum, default_value_t = GroupRule::Project)]
+    pub group_by: GroupRule,
+
+    /// Fail if any unbilled time started more than this many days ago.
+    #[arg(long, short)]
+    pub older_than: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct InvoiceArgs {
+    #[command(subcommand)]
+    pub action: InvoiceAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum InvoiceAction {
+    Build(BuildArgs),
+    /// Number invoices with a format such as INV-{year}-{seq:4} instead of plain numbers.
+    Numbering(NumberingArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct BuildArgs {
+    /// The month to bill for, or a period such as 2026-03-01..2026-03-14 or 2026-01..2026-03.
+    #[arg(value_parser = Period::from_str)]
+    pub period: Period,
+
+    pub recipient: String,
+
+    #[arg(long, short, value_enum, default_value_t = GroupRule::Project)]
+    pub group_by: GroupRule,
+
+    #[arg(long, short)]
+    pub uprice: f64,
+
+    /// Invoice number to use, defaults to one more than the current highest.
+    #[arg(long, short)]
+    pub num: Option<i32>,
+
+    /// Skip the confirmation prompt after the preview.
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct NumberingArgs {
+    #[command(subcommand)]
+    pub action: NumberingAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum NumberingAction {
+    /// Set the numbering scheme of a recipient, or of all recipients without one of their own.
+    Set(NumberingSetArgs),
+    #[command(visible_alias = "rm")]
+    Remove(NumberingRemoveArgs),
+    #[command(visible_alias = "ls")]
+    List(NumberingListArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct NumberingSetArgs {
+    /// Placeholders are {year}, {month}, {recip} and {seq}, zero-padded with {seq:4}.
+    #[arg(value_parser = NumberFormat::from_str)]
+    pub format: NumberFormat,
+
+    /// Only number invoices for this recipient with the format.
+    #[arg(long, short)]
+    pub recipient: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct NumberingRemoveArgs {
+    /// Remove the recipient's scheme instead of the global one.
+    #[arg(long, short)]
+    pub recipient: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct NumberingListArgs {
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Groupings to sum hours by, nested in the order provided. Entries with several tickets or
+    /// tags count towards each of them.
+    #[arg(long, short, value_enum, value_delimiter = ',', default_value = "project")]
+    pub by: Vec<ReportGroup>,
+
+    #[command(flatten)]
+    pub filter: TimeFilter,
+
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportGroup {
+    Project,
+    Ticket,
+    Tag,
+    #[value(alias = "recip")]
+    Recipient,
+    #[value(alias = "act")]
+    Activity,
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    #[command(subcommand)]
+    pub source: ImportSource,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ImportSource {
+    Csv(CsvImportArgs),
+    #[command(visible_alias = "timew")]
+    Timewarrior(FileImportArgs),
+    Toggl(FileImportArgs),
+    Clockify(ClockifyImportArgs),
+    Ical(IcalImportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct IcalImportArgs {
+    pub file: PathBuf,
+
+    /// Only import events with a summary containing this text (case insensitive).
+    #[arg(long)]
+    pub include: Vec<String>,
+
+    /// Skip events with a summary containing this text (case insensitive).
+    #[arg(long)]
+    pub exclude: Vec<String>,
+
+    /// Tag every imported event with this ticket, in addition to any it mentions.
+    #[arg(long, short, value_parser = Ticket::from_str)]
+    pub ticket: Vec<Ticket>,
+
+    /// Choose which events to import and how to tag them, one at a time.
+    #[arg(long, short = 'I')]
+    pub interactive: bool,
+
+    #[command(flatten)]
+    pub options: ImportOptions,
+}
+
+#[derive(Debug, Args)]
+pub struct FileImportArgs {
+    pub file: PathBuf,
+
+    /// Link entries with a project, task or tag of this name to a ticket, e.g. `Website=WEB-1`.
+    #[arg(long, short, value_parser = TicketMapping::from_str)]
+    pub map: Vec<TicketMapping>,
+
+    #[command(flatten)]
+    pub options: ImportOptions,
+}
+
+#[derive(Debug, Args)]
+pub struct ClockifyImportArgs {
+    pub file: PathBuf,
+
+    /// Read dates as day/month/year instead of month/day/year.
+    #[arg(long)]
+    pub day_first: bool,
+
+    /// Link entries with a project, task or tag of this name to a ticket, e.g. `Website=WEB-1`.
+    #[arg(long, short, value_parser = TicketMapping::from_str)]
+    pub map: Vec<TicketMapping>,
+
+    #[command(flatten)]
+    pub options: ImportOptions,
+}
+
+// Options shared by every import source.
+#[derive(Debug, Args)]
+pub struct ImportOptions {
+    /// Report what would be imported without changing the database.
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
+    /// Create projects referenced by tickets if they don't exist yet. Tickets that are only
+    /// mentioned in the text of an entry, rather than given or mapped, need an existing project.
+    #[arg(long)]
+    pub create_projects: bool,
+
+    /// Import entries that overlap existing or other imported entries, instead of skipping them.
+    #[arg(long)]
+    pub allow_overlaps: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CsvImportArgs {
+    pub file: PathBuf,
+
+    /// Read a field from a differently named column, e.g. `start=Begin`.
+    #[arg(long, short, value_parser = ColumnMapping::from_str)]
+    pub column: Vec<ColumnMapping>,
+
+    #[command(flatten)]
+    pub options: ImportOptions,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnMapping {
+    pub field: CsvField,
+    pub header: String,
+}
+
+impl FromStr for ColumnMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, header) = s.split_once('=')
+            .ok_or("Column mapping doesn't match the format field=header")?;
+
+        Ok(ColumnMapping {
+            field: CsvField::from_str(field, true)?,
+            header: header.to_owned(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TicketMapping {
+    pub name: String,
+    pub ticket: Ticket,
+}
+
+impl FromStr for TicketMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, ticket) = s.rsplit_once('=')
+            .ok_or("Ticket mapping doesn't match the format name=ticket")?;
+
+        Ok(TicketMapping {
+            name: name.trim().to_owned(),
+            ticket: ticket.parse()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CsvField {
+    Start,
+    End,
+    #[value(alias = "dur")]
+    Duration,
+    Tickets,
+    #[value(alias = "desc")]
+    Description,
+}
+
+impl CsvField {
+    /// The header used for this field by generated timesheets.
+    pub fn default_header(self) -> &'static str {
+        match self {
+            CsvField::Start => "Start",
+            CsvField::End => "End",
+            CsvField::Duration => "Duration",
+            CsvField::Tickets => "Tickets",
+            CsvField::Description => "Description",
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct EditArgs {
+    /// Edit the time assigned to this invoice.
+    #[arg(value_parser = DocIdentifier::from_str)]
+    pub ident: Option<DocIdentifier>,
+
+    /// Edit the time assigned to the invoice with this formatted number.
+    #[arg(long = "ref", conflicts_with = "ident")]
+    pub inv_ref: Option<String>,
+
+    #[command(flatten)]
+    pub filter: TimeFilter,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    #[command(subcommand)]
+    pub target: ExportTarget,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportTarget {
+    Ical(IcalExportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct IcalExportArgs {
+    #[command(flatten)]
+    pub filter: TimeFilter,
+
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct SuggestArgs {
+    #[command(subcommand)]
+    pub source: SuggestSource,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SuggestSource {
+    Git(GitSuggestArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct GitSuggestArgs {
+    pub repo: PathBuf,
+
+    #[arg(long, short, value_parser = Date::from_str)]
+    pub date: Option<Date>,
+
+    /// Only use commits by this author, instead of the repository's configured user.email.
+    #[arg(long)]
+    pub author: Option<String>,
+
+    /// Minutes between commits that start a new entry.
+    #[arg(long, default_value_t = 60)]
+    pub gap: u32,
+
+    /// Minutes of work to assume before the first commit of each entry.
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    pub lead: u32,
+
+    /// Log the suggested entries instead of only showing them.
+    #[arg(long)]
+    pub log: bool,
+
+    /// Log entries that overlap existing entries, instead of skipping them.
+    #[arg(long)]
+    pub allow_overlaps: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TimerArgs {
+    #[command(subcommand)]
+    pub action: TimerAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TimerAction {
+    Start(StartArgs),
+    /// Stop the running timer and log it.
+    Stop,
+    Status,
+}
+
+#[derive(Debug, Args)]
+pub struct StartArgs {
+    pub description: String,
+
+    #[arg(trailing_var_arg = true, value_parser = Ticket::from_str)]
+    pub tickets: Vec<Ticket>,
+}
+
+#[derive(Debug, Args)]
+pub struct HookArgs {
+    #[command(subcommand)]
+    pub action: HookAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HookAction {
+    /// Install a post-checkout hook that attaches tickets in branch names to the timer.
+    Install(HookInstallArgs),
+    /// Run by the installed hook after a branch is checked out.
+    #[command(hide = true)]
+    Checkout(HookCheckoutArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct HookInstallArgs {
+    #[arg(default_value = ".")]
+    pub repo: PathBuf,
+
+    /// Split the running timer when switching branches, instead of adding to its tickets.
+    #[arg(long)]
+    pub split: bool,
+
+    /// Replace an existing hook that wasn't installed by time-tracker.
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct HookCheckoutArgs {
+    pub branch: String,
+
+    #[arg(long)]
+    pub split: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TuiArgs {
+    /// The day to show first, which defaults to today.
+    #[arg(long, short, value_parser = Date::from_str)]
+    pub date: Option<Date>,
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Address and port to listen on.
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    pub bind: String,
+
+    /// Require requests to send this as a bearer token.
+    #[arg(long)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ProjectArgs {
+    #[command(subcommand)]
+    pub action: ProjectAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProjectAction {
+    Add(ProjectAddArgs),
+    Edit(ProjectEditArgs),
+    #[command(visible_alias = "ls")]
+    List(ProjectListArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ProjectAddArgs {
+    pub proj_key: String,
+
+    pub name: String,
+
+    /// Log time on the project as non-billable unless told otherwise.
+    #[arg(long)]
+    pub non_billable: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ProjectEditArgs {
+    pub proj_key: String,
+
+    #[arg(long, short)]
+    pub name: Option<String>,
+
+    #[command(flatten)]
+    pub billable: BillableFlags,
+}
+
+#[derive(Debug, Args)]
+pub struct ProjectListArgs {
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct TicketArgs {
+    #[command(subcommand)]
+    pub action: TicketAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TicketAction {
+    Add(TicketAddArgs),
+    Edit(TicketEditArgs),
+    #[command(visible_alias = "ls")]
+    List(TicketListArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct TicketAddArgs {
+    #[arg(value_parser = Ticket::from_str)]
+    pub ticket: Ticket,
+
+    pub title: String,
+
+    /// Link to the ticket in an external tracker.
+    #[arg(long, short)]
+    pub url: Option<String>,
+
+    #[arg(long, short, value_enum, default_value_t = TicketStatus::Open)]
+    pub status: TicketStatus,
+
+    /// Estimated hours of work.
+    #[arg(long, short)]
+    pub estimate: Option<f64>,
+}
+
+#[derive(Debug, Args)]
+pub struct TicketEditArgs {
+    #[arg(value_parser = Ticket::from_str)]
+    pub ticket: Ticket,
+
+    #[arg(long, short)]
+    pub title: Option<String>,
+
+    #[arg(long, short)]
+    pub url: Option<String>,
+
+    #[arg(long, short, value_enum)]
+    pub status: Option<TicketStatus>,
+
+    #[arg(long, short)]
+    pub estimate: Option<f64>,
+}
+
+#[derive(Debug, Args)]
+pub struct TicketListArgs {
+    /// Only list tickets belonging to this project.
+    #[arg(long, short)]
+    pub project: Option<String>,
+
+    #[arg(long, short, value_enum)]
+    pub status: Option<TicketStatus>,
+
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, DisplayDerive)]
+pub enum TicketStatus {
+    #[display("open")]
+    Open,
+    #[display("in-progress")]
+    InProgress,
+    #[display("done")]
+    Done,
+}
+
+#[derive(Debug, Args)]
+pub struct BudgetArgs {
+    #[command(subcommand)]
+    pub action: BudgetAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BudgetAction {
+    /// Set the budget of a project or ticket, replacing any it already has.
+    Set(BudgetSetArgs),
+    #[command(visible_alias = "rm")]
+    Remove(BudgetRemoveArgs),
+    /// Show what has been spent and how long each budget is projected to last.
+    Report(BudgetReportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct BudgetSetArgs {
+    /// A project key for a project budget, or a ticket for a ticket budget.
+    #[arg(value_parser = BudgetTarget::from_str)]
+    pub target: BudgetTarget,
+
+    #[arg(long, required_unless_present = "money")]
+    pub hours: Option<f64>,
+
+    #[arg(long, short, requires = "rate")]
+    pub money: Option<f64>,
+
+    /// Hourly rate that time spends a money budget at.
+    #[arg(long, short)]
+    pub rate: Option<f64>,
+}
+
+#[derive(Debug, Args)]
+pub struct BudgetRemoveArgs {
+    #[arg(value_parser = BudgetTarget::from_str)]
+    pub target: BudgetTarget,
+}
+
+#[derive(Debug, Args)]
+pub struct BudgetReportArgs {
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct UndoArgs {
+    /// Skip the confirmation prompt.
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct HistoryArgs {
+    /// Show the changes made by this command instead of listing commands.
+    pub cmd_id: Option<i32>,
+
+    /// How many of the most recent commands to list.
+    #[arg(long, short, default_value_t = 20)]
+    pub limit: i64,
+
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct BackupArgs {
+    #[command(subcommand)]
+    pub action: BackupAction,
+}
+
+/// Backups are kept in a directory next to the database, named after it with `.backups` added.
+#[derive(Debug, Subcommand)]
+pub enum BackupAction {
+    #[command(visible_alias = "ls")]
+    List(BackupListArgs),
+    /// Take a backup now. These aren't removed to make room for automatic ones.
+    Create,
+    /// Replace the database with a backup, after backing up its current state.
+    Restore(BackupRestoreArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct BackupListArgs {
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct BackupRestoreArgs {
+    /// The name of the backup as listed, or the path to any copy of the database.
+    pub backup: String,
+
+    /// Skip the confirmation prompt.
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+/// Check the database for problems, offering to fix those that can be fixed safely.
+#[derive(Debug, Args)]
+pub struct DoctorArgs {
+    /// Only report problems, without offering to fix them.
+    #[arg(long, conflicts_with = "yes")]
+    pub check: bool,
+
+    /// Apply every fix without asking.
+    #[arg(long, short)]
+    pub yes: bool,
+
+    #[arg(long, short = 'F', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
@@ -0,0 +1,66 @@
+use diesel::prelude::*;
+
+use crate::cli::{args::{BackupAction, BackupArgs, BackupRestoreArgs}, confirm::confirm, output::print_items};
+use crate::csv::convert::ListBackup;
+use crate::service::backup::{BackupKind, check_backup, create_backup, database_path, find_backup, list_backups, prune_backups, restore_backup};
+use crate::util::error::DynResult;
+
+pub fn backup(conn: &mut SqliteConnection, args: BackupArgs, db_url: &str) -> DynResult<()> {
+    let database = database_path(db_url)
+        .ok_or_else(|| format!("The database {db_url} isn't a file, so it can't be backed up"))?;
+
+    match args.action {
+        BackupAction::List(list_args) => {
+            let backups = list_backups(&database)?;
+            print_items(list_args.format, &backups, |b| ListBackup::from(b))
+        },
+        BackupAction::Create => {
+            let backup = create_backup(conn, &database, BackupKind::Manual)?;
+            println!("Backed up the database to {}", backup.path.display());
+            Ok(())
+        },
+        BackupAction::Restore(_) => unreachable!("Handled before connecting to the database"),
+    }
+}
+
+/// Replace the database with a backup. This has to happen without a connection to the database,
+/// so the current state is backed up through one of its own first.
+pub fn restore(db_url: &str, args: BackupRestoreArgs) -> DynResult<()> {
+    let database = database_path(db_url)
+        .ok_or_else(|| format!("The database {db_url} isn't a file, so it can't be restored"))?;
+
+    let source = find_backup(&database, &args.backup)?;
+    check_backup(&source)?;
+
+    if !args.yes && !confirm(&format!(
+        "Replace {} with {}?",
+        database.display(),
+        source.display()
+    ))? {
+        println!("Nothing was restored");
+        return Ok(());
+    }
+
+    let conn = SqliteConnection::establish(db_url)
+        .map_err(|e| format!("Error connecting to {db_url}:\n{e}"))?;
+    let previous = restore_backup(conn, &database, &source)?;
+
+    println!(
+        "Restored {}, the replaced database is backed up to {}",
+        source.display(),
+        previous.path.display()
+    );
+
+    Ok(())
+}
+
+/// Take a backup before a command that changes data, then remove the oldest automatic backups
+/// beyond the number to keep. Keeping none turns this off.
+pub fn auto_backup(conn: &mut SqliteConnection, db_url: &str, keep: usize) -> DynResult<()> {
+    let Some(database) = database_path(db_url).filter(|_| keep > 0) else {
+        return Ok(());
+    };
+
+    create_backup(conn, &database, BackupKind::Auto)?;
+    Ok(prune_backups(&database, keep)?)
+}
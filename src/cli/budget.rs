@@ -0,0 +1,51 @@
+use diesel::prelude::*;
+
+use crate::cli::args::{BudgetAction, BudgetArgs, BudgetSetArgs};
+use crate::cli::output::print_items;
+use crate::csv::convert::ListBudget;
+use crate::service::budget::{BudgetUsage, budget_usage, remove_budget, set_budget};
+use crate::util::date::Date;
+use crate::util::error::DynResult;
+
+pub fn budget(conn: &mut SqliteConnection, args: BudgetArgs) -> DynResult<()> {
+    match args.action {
+        BudgetAction::Set(set_args) => set(conn, set_args),
+        BudgetAction::Remove(remove_args) => {
+            remove_budget(conn, &remove_args.target)?;
+            println!("Removed budget for {}", remove_args.target);
+            Ok(())
+        },
+        BudgetAction::Report(report_args) => {
+            let usage = budget_usage(conn, None, *Date::now())?;
+            print_items(report_args.format, &usage, |u| ListBudget::from(u))
+        },
+    }
+}
+
+fn set(conn: &mut SqliteConnection, args: BudgetSetArgs) -> DynResult<()> {
+    set_budget(conn, &args.target, args.hours, args.money, args.rate)?;
+
+    println!("Set budget for {}", args.target);
+
+    Ok(())
+}
+
+/// Warn about every budget that has passed a threshold between two usages of the same budgets.
+pub fn warn_crossed(before: &[BudgetUsage], after: &[BudgetUsage]) {
+    for (before, after) in before.iter().zip(after) {
+        match after.crossed_since(before) {
+            Some(threshold) if threshold >= 1.0 => println!(
+                "Warning: the budget for {} is exhausted ({:.0}% spent)",
+                after.target(),
+                after.used * 100.0
+            ),
+            Some(_) => println!(
+                "Warning: the budget for {} is {:.0}% spent, {:.1} hours remain",
+                after.target(),
+                after.used * 100.0,
+                after.remaining_hours
+            ),
+            None => (),
+        }
+    }
+}
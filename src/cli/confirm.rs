@@ -0,0 +1,22 @@
+use std::io::{self, Write};
+
+use crate::util::error::DynResult;
+
+pub fn confirm(prompt: &str) -> DynResult<bool> {
+    let answer = ask(&format!("{prompt} [y/N]"))?;
+
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Prompt for a line of input, returning it without surrounding whitespace.
+pub fn ask(prompt: &str) -> DynResult<String> {
+    print!("{prompt} ");
+    io::stdout().flush()
+        .map_err(|e| format!("Error writing prompt:\n{e}"))?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)
+        .map_err(|e| format!("Error reading response:\n{e}"))?;
+
+    Ok(answer.trim().to_owned())
+}
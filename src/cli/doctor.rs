@@ -0,0 +1,36 @@
+use diesel::prelude::*;
+
+use crate::cli::{args::DoctorArgs, confirm::confirm, output::print_items};
+use crate::csv::convert::ListProblem;
+use crate::service::doctor::{Fix, apply_fix, diagnose};
+use crate::util::error::DynResult;
+
+pub fn doctor(conn: &mut SqliteConnection, args: DoctorArgs) -> DynResult<()> {
+    let problems = diagnose(conn)?;
+
+    if problems.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    print_items(args.format, &problems, |p| ListProblem::from(p))?;
+
+    let fixes: Vec<&Fix> = problems.iter().filter_map(|p| p.fix.as_ref()).collect();
+
+    if args.check || fixes.is_empty() {
+        return Ok(());
+    }
+
+    if !args.yes && !confirm(&format!("Apply {} fix(es)?", fixes.len()))? {
+        println!("Nothing was fixed");
+        return Ok(());
+    }
+
+    for fix in &fixes {
+        apply_fix(conn, fix)?;
+    }
+
+    println!("Applied {} fix(es)", fixes.len());
+
+    Ok(())
+}
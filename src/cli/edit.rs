@@ -0,0 +1,192 @@
+use std::{collections::{BTreeSet, HashMap}, env, fs, io::{self, Write}, path::{Path, PathBuf}, process::{self, Command}};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use diesel::prelude::*;
+
+use crate::cli::{args::{DocIdentifier, EditArgs}, confirm::confirm};
+use crate::csv::edit::{EditedEntry, read_edit_file, write_edit_file};
+use crate::orm::{insert::NewEntry, model::Invoice, query::TimeWithTickets};
+use crate::service::audit::{finish_command, start_command};
+use crate::service::time::{delete_time, log_time, select_times, update_time};
+use crate::util::error::{DynError, DynResult};
+
+/// Edit entries in $EDITOR, then apply the changes as a command of their own. The editor runs
+/// before the transaction starts, so that the database isn't locked while it's open, so this
+/// shouldn't be called inside one.
+pub fn edit(conn: &mut SqliteConnection, args: EditArgs, cmd_line: &str) -> DynResult<()> {
+    let ident = args.inv_ref.map(DocIdentifier::Ref).or(args.ident);
+
+    if ident.is_none() && args.filter.is_empty() {
+        Err("Provide an invoice or at least one filter to select the entries to edit")?
+    }
+
+    let invoice = ident.map(|i| Invoice::select_by_identifier(i, conn))
+        .transpose()?;
+
+    let times = select_times(conn, args.filter, invoice.map(|i| i.inv_num))?;
+
+    let (temp, file) = TempFile::create("edit", "csv")?;
+    write_edit_file(file, &times)?;
+    open_editor(&temp.0)?;
+    let edited = read_edit_file(&temp.0)?;
+
+    // Emptying the file is more likely a mistake, such as an editor that saved nothing, than a
+    // way of deleting everything.
+    if !times.is_empty()
+        && edited.iter().all(|row| row.time_id.is_none())
+        && !confirm(&format!(
+            "The edited file has none of the {} selected entries, delete all of them?",
+            times.len()
+        ))?
+    {
+        println!("No changes were made");
+        return Ok(());
+    }
+
+    conn.transaction::<_, DynError, _>(|conn| {
+        let cmd_id = start_command(conn, cmd_line)?;
+        apply_edit(conn, times, edited)?;
+        Ok(finish_command(conn, cmd_id)?)
+    })
+}
+
+/// Apply an edited file to the entries that were written to it. Rows without an id are inserted,
+/// changed rows are updated and entries without a row are deleted.
+pub fn apply_edit(
+    conn: &mut SqliteConnection,
+    times: Vec<TimeWithTickets>,
+    edited: Vec<EditedEntry>
+) -> DynResult<()> {
+    let mut originals: HashMap<i32, TimeWithTickets> = times.into_iter()
+        .map(|t| (t.time_id, t))
+        .collect();
+
+    let mut inserted = 0;
+    let mut updated = 0;
+
+    for row in edited {
+        let Some(time_id) = row.time_id else {
+            let time = log_time(conn, row.entry)
+                .map_err(|e| format!("Error on line {} of edit file:\n{e}", row.line))?;
+            println!("Inserted entry {}", time.time_id);
+            inserted += 1;
+            continue;
+        };
+
+        let original = originals.remove(&time_id)
+            .ok_or_else(|| format!(
+                "Error on line {} of edit file:\nEntry {time_id} wasn't selected for editing or \
+                appears more than once",
+                row.line
+            ))?;
+
+        if !is_unchanged(&original, &row.entry) {
+            update_time(conn, time_id, row.entry)
+                .map_err(|e| format!("Error on line {} of edit file:\n{e}", row.line))?;
+            println!("Updated entry {time_id}");
+            updated += 1;
+        }
+    }
+
+    // Anything left wasn't in the edited file, so it has been removed.
+    let mut deleted: Vec<i32> = originals.into_keys().collect();
+    deleted.sort();
+
+    for time_id in &deleted {
+        delete_time(conn, *time_id)?;
+        println!("Deleted entry {time_id}");
+    }
+
+    println!("{inserted} inserted, {updated} updated, {} deleted", deleted.len());
+
+    Ok(())
+}
+
+/// Edit some text in a temporary file, returning what it was changed to.
+pub fn edit_text(name: &str, text: &str) -> DynResult<String> {
+    let (temp, mut file) = TempFile::create(name, "txt")?;
+
+    file.write_all(text.as_bytes())
+        .map_err(|e| format!("Error writing file {}:\n{e}", temp.0.display()))?;
+    drop(file);
+
+    open_editor(&temp.0)?;
+
+    Ok(
+        fs::read_to_string(&temp.0)
+            .map_err(|e| format!("Error reading file {}:\n{e}", temp.0.display()))?
+    )
+}
+
+/// A file in the temporary directory, removed again when dropped. That directory is shared with
+/// other users, so the file is always newly created rather than reusing or following one that's
+/// already there.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn create(name: &str, extension: &str) -> DynResult<(TempFile, fs::File)> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut attempt = 0;
+
+        loop {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.subsec_nanos());
+            let path = env::temp_dir().join(format!(
+                "time-tracker-{name}-{}-{nanos:08x}.{extension}",
+                process::id()
+            ));
+
+            match options.open(&path) {
+                Ok(file) => return Ok((TempFile(path), file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 10 => attempt += 1,
+                Err(e) => Err(format!("Error creating file {}:\n{e}", path.display()))?,
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn open_editor(path: &Path) -> DynResult<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+
+    // Run through the shell so that editors configured with arguments (e.g. `code --wait`) work.
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(path)
+        .status()
+        .map_err(|e| format!("Error launching editor '{editor}':\n{e}"))?;
+
+    if !status.success() {
+        Err(format!("Editor '{editor}' exited unsuccessfully, no changes were made"))?
+    }
+
+    Ok(())
+}
+
+fn is_unchanged(original: &TimeWithTickets, entry: &NewEntry) -> bool {
+    *original.time_start == entry.time.time_start
+        && *original.time_end == entry.time.time_end
+        && original.time_desc == entry.time.time_desc
+        && original.act_num == entry.time.act_num
+        && original.tickets.iter().collect::<BTreeSet<_>>()
+            == entry.tickets.iter().collect::<BTreeSet<_>>()
+        && original.tags.iter().collect::<BTreeSet<_>>()
+            == entry.tags.iter().collect::<BTreeSet<_>>()
+        && entry.billable.is_none_or(|b| b == original.time_billable)
+        && original.time_notes == entry.time.time_notes
+}
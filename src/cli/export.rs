@@ -0,0 +1,28 @@
+use std::fs;
+
+use diesel::prelude::*;
+
+use crate::cli::args::{ExportArgs, ExportTarget, IcalExportArgs};
+use crate::ical::convert::to_calendar;
+use crate::orm::query::TimeWithTickets;
+use crate::util::{date::Date, error::DynResult};
+
+pub fn export(conn: &mut SqliteConnection, args: ExportArgs) -> DynResult<()> {
+    match args.target {
+        ExportTarget::Ical(ical_args) => export_ical(conn, ical_args),
+    }
+}
+
+pub fn export_ical(conn: &mut SqliteConnection, args: IcalExportArgs) -> DynResult<()> {
+    let times = TimeWithTickets::from_query(args.filter.query(), conn)
+        .map_err(|e| format!("Error retrieving times from database:\n{e}"))?;
+
+    let output = args.output.unwrap_or_else(|| format!("./time-{}.ics", Date::now()).into());
+
+    fs::write(&output, to_calendar(&times))
+        .map_err(|e| format!("Error writing calendar:\n{e}"))?;
+
+    println!("Created calendar: '{}'", output.display());
+
+    Ok(())
+}
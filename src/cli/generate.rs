@@ -1,57 +1,42 @@
 use std::{fs, path::PathBuf};
 
-use csv::{QuoteStyle, WriterBuilder};
 use diesel::prelude::*;
-use typst::{Library, LibraryExt};
-use typst_pdf::PdfOptions;
 
-use crate::cli::args::{DocIdentifier, DocType, GenerateArgs};
-use crate::csv::convert::CsvTime;
-use crate::orm::model::Time;
-use crate::orm::query::TimeWithTickets;
-use crate::orm::{model::Invoice, query::InvoiceWithActivities};
-use crate::typst::error::DisplayErrors;
-use crate::typst::{convert::IntoTypst, world::MinimalWorld};
+use crate::cli::args::{DocIdentifier, DocType, GenerateArgs, TimesheetArgs};
+use crate::service::document::{Document, render_invoice, render_timesheet};
 use crate::util::error::DynResult;
 
 pub fn generate(conn: &mut SqliteConnection, args: GenerateArgs) -> DynResult<()> {
-    let ident = args.ident.unwrap_or_default();
+    let ident = args.inv_ref.map(DocIdentifier::Ref).or(args.ident);
 
     match args.doc_type {
-        DocType::Invoice => generate_invoice(conn, ident, args.output),
-        DocType::Timesheet => generate_timesheet(conn, ident, args.output),
+        DocType::Invoice => generate_invoice(
+            conn,
+            ident.unwrap_or_default(),
+            args.output,
+            args.titles
+        ),
+        // A filtered timesheet doesn't need to belong to an invoice.
+        DocType::Timesheet(ts_args) if !ts_args.filter.is_empty() => {
+            generate_timesheet(conn, ident, ts_args, args.output, args.titles)
+        },
+        DocType::Timesheet(ts_args) => generate_timesheet(
+            conn,
+            Some(ident.unwrap_or_default()),
+            ts_args,
+            args.output,
+            args.titles
+        ),
     }
 }
 
 pub fn generate_invoice(
     conn: &mut SqliteConnection,
     ident: DocIdentifier,
-    output: Option<PathBuf>
+    output: Option<PathBuf>,
+    titles: bool
 ) -> DynResult<()> {
-    let invoice = InvoiceWithActivities::select_by_identifier(ident, conn)?;
-
-    let output = output.unwrap_or_else(
-        || format!(
-            "./{}-tax-invoice-{}.pdf",
-            invoice.inv_month,
-            invoice.inv_num
-        ).into()
-    );
-    
-    let lib = Library::builder()
-        .with_inputs(invoice.into_typst())
-        .build();
-
-    let world = MinimalWorld::new("../", include_str!("../../res/template.typ"), lib);
-
-    let document = typst::compile(&world)
-        .output
-        .map_err(|e| format!("Error compiling typst template:\n{}", DisplayErrors(e)))?;
-
-    let pdf = typst_pdf::pdf(&document, &PdfOptions::default())
-        .map_err(|e| format!("Error exporting PDF:\n{}", DisplayErrors(e)))?;
-
-    fs::write(&output, pdf)
+    let output = save(render_invoice(conn, ident, titles)?, output)
         .map_err(|e| format!("Error writing PDF:\n{e}"))?;
 
     println!("Created invoice: '{}'", output.display());
@@ -61,44 +46,28 @@ pub fn generate_invoice(
 
 pub fn generate_timesheet(
     conn: &mut SqliteConnection,
-    ident: DocIdentifier,
-    output: Option<PathBuf>
+    ident: Option<DocIdentifier>,
+    args: TimesheetArgs,
+    output: Option<PathBuf>,
+    titles: bool
 ) -> DynResult<()> {
-    use crate::orm::schema::invoice_activity;
-
-    let invoice = Invoice::select_by_identifier(ident, conn)?;
-
-    let times = TimeWithTickets::from_query(
-        Time::query()
-            .inner_join(invoice_activity::table)
-            .filter(invoice_activity::inv_num.eq(invoice.inv_num)),
-        conn
-    ).map_err(|e| format!("Error retrieving timesheet from database:\n{e}"))?;
+    let document = render_timesheet(conn, ident, args.filter, titles, args.detailed)?;
 
-    let output = output.unwrap_or_else(
-        || format!(
-            "./{}-timesheet-{}.csv",
-            invoice.inv_month,
-            invoice.inv_num
-        ).into()
-    );
-
-    let mut writer = WriterBuilder::new()
-        .quote_style(QuoteStyle::Always)
-        .has_headers(false)
-        .from_path(&output)
-        .map_err(|e| format!("Error opening file {}:\n{e}", output.display()))?;
-
-    // Just manually write the headers so that they are pretty.
-    writer.write_record(["Start", "End", "Duration", "Tickets", "Description"])
-        .map_err(|e| format!("Error writing time entry to timesheet:\n{e}"))?;
-
-    for time in times {
-        writer.serialize(CsvTime::from(time))
-            .map_err(|e| format!("Error writing time entry to timesheet:\n{e}"))?;
+    for warning in &document.warnings {
+        eprintln!("Warning: {warning}");
     }
 
+    let output = save(document, output)
+        .map_err(|e| format!("Error writing timesheet:\n{e}"))?;
+
     println!("Created timesheet: '{}'", output.display());
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Write a document to the output path, or to its own file name in the current directory.
+fn save(document: Document, output: Option<PathBuf>) -> std::io::Result<PathBuf> {
+    let output = output.unwrap_or_else(|| format!("./{}", document.file_name).into());
+    fs::write(&output, document.bytes)?;
+    Ok(output)
+}
@@ -0,0 +1,19 @@
+use diesel::prelude::*;
+
+use crate::cli::{args::HistoryArgs, output::print_items};
+use crate::csv::convert::{ListChange, ListCommand};
+use crate::service::audit::{select_changes, select_commands};
+use crate::util::error::DynResult;
+
+pub fn history(conn: &mut SqliteConnection, args: HistoryArgs) -> DynResult<()> {
+    match args.cmd_id {
+        Some(cmd_id) => {
+            let changes = select_changes(conn, cmd_id)?;
+            print_items(args.format, &changes, |c| ListChange::from(c))
+        },
+        None => {
+            let commands = select_commands(conn, args.limit)?;
+            print_items(args.format, &commands, |c| ListCommand::from(c))
+        },
+    }
+}
@@ -0,0 +1,83 @@
+use std::{env, fs};
+
+use diesel::prelude::*;
+
+use crate::cli::args::{HookAction, HookArgs, HookCheckoutArgs, HookInstallArgs};
+use crate::cli::timer::now;
+use crate::git::hook::{install, post_checkout_script};
+use crate::orm::ticket::Ticket;
+use crate::service::project::known_tickets;
+use crate::service::timer::{attach_tickets, running_timer, split_timer};
+use crate::util::error::DynResult;
+
+pub fn hook(conn: &mut SqliteConnection, args: HookArgs, database: &str) -> DynResult<()> {
+    match args.action {
+        HookAction::Install(install_args) => hook_install(install_args, database),
+        HookAction::Checkout(checkout_args) => hook_checkout(conn, checkout_args),
+    }
+}
+
+fn hook_install(args: HookInstallArgs, database: &str) -> DynResult<()> {
+    let exe = env::current_exe()
+        .map_err(|e| format!("Error finding the time-tracker executable:\n{e}"))?;
+
+    // The hook runs from the repository, so a relative database path would point elsewhere.
+    let database = fs::canonicalize(database)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| database.to_owned());
+
+    let mut command = vec![
+        exe.display().to_string(),
+        "--database".to_owned(),
+        database,
+        "hook".to_owned(),
+        "checkout".to_owned(),
+    ];
+
+    if args.split {
+        command.push("--split".to_owned());
+    }
+
+    let path = install(&args.repo, "post-checkout", &post_checkout_script(&command), args.force)?;
+
+    println!("Installed hook: '{}'", path.display());
+
+    Ok(())
+}
+
+/// Attach the tickets in a branch name to the timer, or split the running timer onto them.
+pub fn hook_checkout(conn: &mut SqliteConnection, args: HookCheckoutArgs) -> DynResult<()> {
+    let tickets = known_tickets(conn, Ticket::find_all(&args.branch))?;
+
+    let listed = tickets.iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if args.split && running_timer(conn)?.is_some() {
+        match split_timer(conn, tickets, now())? {
+            Some(time) => println!(
+                "time-tracker: logged entry {}, continuing on {}",
+                time.time_id,
+                args.branch
+            ),
+            None => println!("time-tracker: restarted timer on {}", args.branch),
+        }
+        return Ok(());
+    }
+
+    if tickets.is_empty() {
+        return Ok(());
+    }
+
+    let running = running_timer(conn)?.is_some();
+    attach_tickets(conn, tickets)?;
+
+    if running {
+        println!("time-tracker: attached {listed} to the running timer");
+    } else {
+        println!("time-tracker: {listed} will be attached to the next timer");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+use diesel::prelude::*;
+use tabled::{Table, Tabled, settings::Style};
+
+use crate::cli::args::{IcalImportArgs, ImportArgs, ImportOptions, ImportSource};
+use crate::cli::confirm::{ask, confirm};
+use crate::csv::import::read_timesheet;
+use crate::csv::tracker::{self, CLOCKIFY, CLOCKIFY_DAY_FIRST, TOGGL};
+use crate::ical::import::read_calendar;
+use crate::json::timewarrior;
+use crate::orm::{insert::NewEntry, model::{Project, Time}, ticket::Ticket};
+use crate::service::project::known_tickets;
+use crate::service::time::log_time;
+use crate::util::{date::DateTime, error::DynResult};
+
+pub fn import(conn: &mut SqliteConnection, args: ImportArgs) -> DynResult<()> {
+    match args.source {
+        ImportSource::Csv(csv_args) => {
+            let entries = read_timesheet(&csv_args.file, &csv_args.column)?;
+            import_entries(conn, entries, csv_args.options)
+        },
+        ImportSource::Timewarrior(file_args) => {
+            let entries = timewarrior::read_export(
+                &file_args.file,
+                &file_args.map,
+                |tickets| Ok(known_tickets(conn, tickets)?)
+            )?;
+            import_entries(conn, entries, file_args.options)
+        },
+        ImportSource::Toggl(file_args) => {
+            let entries = tracker::read_export(
+                &file_args.file,
+                &TOGGL,
+                &file_args.map,
+                |tickets| Ok(known_tickets(conn, tickets)?)
+            )?;
+            import_entries(conn, entries, file_args.options)
+        },
+        ImportSource::Clockify(clockify_args) => {
+            let layout = if clockify_args.day_first { &CLOCKIFY_DAY_FIRST } else { &CLOCKIFY };
+            let entries = tracker::read_export(
+                &clockify_args.file,
+                layout,
+                &clockify_args.map,
+                |tickets| Ok(known_tickets(conn, tickets)?)
+            )?;
+            import_entries(conn, entries, clockify_args.options)
+        },
+        ImportSource::Ical(ical_args) => {
+            let entries = select_events(conn, &ical_args)?;
+            import_entries(conn, entries, ical_args.options)
+        },
+    }
+}
+
+/// Read the events from a calendar and turn the ones that pass the summary filters into entries,
+/// asking about each of them first when importing interactively.
+fn select_events(
+    conn: &mut SqliteConnection,
+    args: &IcalImportArgs
+) -> DynResult<Vec<(String, NewEntry)>> {
+    let include: Vec<String> = args.include.iter().map(|i| i.to_lowercase()).collect();
+    let exclude: Vec<String> = args.exclude.iter().map(|e| e.to_lowercase()).collect();
+
+    let mut entries = vec![];
+
+    for (i, event) in read_calendar(&args.file)?.into_iter().enumerate() {
+        let summary = event.summary.to_lowercase();
+
+        if !include.is_empty() && !include.iter().any(|i| summary.contains(i)) {
+            continue;
+        }
+        if exclude.iter().any(|e| summary.contains(e)) {
+            continue;
+        }
+
+        // Summaries like `Sprint-3 planning` only look like they mention a ticket, so only tickets
+        // of existing projects are taken from them, while the given tickets are always added.
+        let known = known_tickets(conn, event.tickets())?;
+        let mut tickets = known.clone();
+        tickets.extend(args.ticket.iter().cloned());
+        tickets.sort();
+        tickets.dedup();
+
+        if args.interactive {
+            let prompt = format!(
+                "Import '{}' ({} - {})?",
+                event.summary,
+                event.start.format("%Y-%m-%d %H:%M"),
+                event.end.format("%H:%M")
+            );
+            if !confirm(&prompt)? {
+                continue;
+            }
+            tickets = ask_tickets(tickets)?;
+        }
+
+        let source = event.uid.clone().unwrap_or_else(|| format!("event {}", i + 1));
+        entries.push((source, event.into_entry(tickets, &known)));
+    }
+
+    Ok(entries)
+}
+
+/// Ask which tickets an event should be logged against, keeping the current ones on empty input.
+fn ask_tickets(current: Vec<Ticket>) -> DynResult<Vec<Ticket>> {
+    let current_list = current.iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    loop {
+        let answer = ask(&format!("Tickets [{current_list}]:"))?;
+
+        if answer.is_empty() {
+            return Ok(current);
+        }
+
+        match answer.split([' ', ',']).filter(|t| !t.is_empty()).map(Ticket::from_str).collect() {
+            Ok(tickets) => return Ok(tickets),
+            Err(e) => println!("{e}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Insert,
+    Overlapping,
+    Duplicate,
+    Overlap,
+}
+
+impl Status {
+    fn inserted(self) -> bool {
+        matches!(self, Status::Insert | Status::Overlapping)
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Status::Insert => "insert",
+            Status::Overlapping => "insert (overlaps)",
+            Status::Duplicate => "skip (duplicate)",
+            Status::Overlap => "skip (overlaps)",
+        }
+    }
+}
+
+#[derive(Debug, Tabled)]
+struct ImportRow {
+    #[tabled(rename = "Source")]
+    source: String,
+    #[tabled(rename = "Start")]
+    start: DateTime,
+    #[tabled(rename = "End")]
+    end: DateTime,
+    #[tabled(rename = "Tickets")]
+    tickets: String,
+    #[tabled(rename = "Description")]
+    desc: String,
+    #[tabled(rename = "Status")]
+    status: &'static str,
+}
+
+/// Check parsed entries against the database and each other, then insert the ones that are new.
+/// Exact duplicates are always skipped, while overlapping entries are skipped unless allowed.
+pub fn import_entries(
+    conn: &mut SqliteConnection,
+    entries: Vec<(String, NewEntry)>,
+    options: ImportOptions
+) -> DynResult<()> {
+    use crate::orm::schema::{project, time};
+
+    if entries.is_empty() {
+        println!("No entries to import");
+        return Ok(());
+    }
+
+    let first = entries.iter().map(|(_, e)| e.time.time_start).min().unwrap();
+    let last = entries.iter().map(|(_, e)| e.time.time_end).max().unwrap();
+
+    let mut existing: Vec<(_, _, String)> = Time::query()
+        .filter(time::time_start.lt(last))
+        .filter(time::time_end.gt(first))
+        .load(conn)
+        .map_err(|e| format!("Error retrieving times from database:\n{e}"))?
+        .into_iter()
+        .map(|t| (*t.time_start, *t.time_end, t.time_desc))
+        .collect();
+
+    let mut checked = vec![];
+
+    for (source, entry) in entries {
+        let time = &entry.time;
+
+        let status = if existing.iter().any(|(start, end, desc)| {
+            *start == time.time_start && *end == time.time_end && *desc == time.time_desc
+        }) {
+            Status::Duplicate
+        } else if existing.iter().any(|(start, end, _)| {
+            *start < time.time_end && time.time_start < *end
+        }) {
+            if options.allow_overlaps { Status::Overlapping } else { Status::Overlap }
+        } else {
+            Status::Insert
+        };
+
+        // Later entries are checked against earlier ones from the same import too.
+        if status.inserted() {
+            existing.push((time.time_start, time.time_end, time.time_desc.clone()));
+        }
+
+        checked.push((source, entry, status));
+    }
+
+    let proj_keys: BTreeSet<String> = checked.iter()
+        .filter(|(_, _, status)| status.inserted())
+        .flat_map(|(_, entry, _)| entry.tickets.iter().map(|t| t.proj_key.clone()))
+        .collect();
+
+    let known: Vec<String> = project::table
+        .filter(project::proj_key.eq_any(&proj_keys))
+        .select(project::proj_key)
+        .load(conn)
+        .map_err(|e| format!("Error retrieving projects from database:\n{e}"))?;
+
+    let missing: Vec<String> = proj_keys.into_iter()
+        .filter(|k| !known.contains(k))
+        .collect();
+
+    if !missing.is_empty() && !options.create_projects {
+        Err(format!(
+            "Imported tickets reference unknown projects: {} (use --create-projects to add them)",
+            missing.join(", ")
+        ))?
+    }
+
+    println!("{}", Table::new(
+        checked.iter().map(|(source, entry, status)| ImportRow {
+            source: source.clone(),
+            start: entry.time.time_start.into(),
+            end: entry.time.time_end.into(),
+            tickets: entry.tickets.iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            desc: entry.time.time_desc.clone(),
+            status: status.describe(),
+        })
+    ).with(Style::psql()));
+
+    let count = checked.iter().filter(|(_, _, s)| s.inserted()).count();
+
+    if options.dry_run {
+        if !missing.is_empty() {
+            println!("Would create projects: {}", missing.join(", "));
+        }
+        println!("Would import {count} of {} entries", checked.len());
+        return Ok(());
+    }
+
+    for proj_key in missing {
+        Project {
+            proj_name: proj_key.clone(),
+            proj_key,
+            proj_billable: true,
+        }.insert_into(project::table)
+            .execute(conn)
+            .map_err(|e| format!("Error inserting project into database:\n{e}"))?;
+    }
+
+    for (source, entry, status) in checked {
+        if status.inserted() {
+            log_time(conn, entry)
+                .map_err(|e| format!("Error importing entry from {source}:\n{e}"))?;
+        }
+    }
+
+    println!("Imported {count} entries");
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+
+use diesel::prelude::*;
+use tabled::{Table, Tabled, settings::Style};
+
+use crate::cli::args::{BuildArgs, InvoiceAction, InvoiceArgs, NumberingAction, NumberingArgs};
+use crate::cli::{confirm::confirm, output::print_items};
+use crate::csv::convert::ListScheme;
+use crate::service::invoice::{DraftActivity, create_invoice, draft_activities, new_invoice, next_invoice_num};
+use crate::service::numbering::{next_reference, remove_scheme, select_schemes, set_scheme};
+use crate::util::{date::Month, error::DynResult};
+
+pub fn invoice(conn: &mut SqliteConnection, args: InvoiceArgs) -> DynResult<()> {
+    match args.action {
+        InvoiceAction::Build(build_args) => build_invoice(conn, build_args),
+        InvoiceAction::Numbering(numbering_args) => numbering(conn, numbering_args),
+    }
+}
+
+fn numbering(conn: &mut SqliteConnection, args: NumberingArgs) -> DynResult<()> {
+    let describe = |recip_id: Option<&str>| match recip_id {
+        Some(recip_id) => format!("recipient '{recip_id}'"),
+        None => "all recipients".to_owned(),
+    };
+
+    match args.action {
+        NumberingAction::Set(set_args) => {
+            set_scheme(conn, set_args.recipient.as_deref(), &set_args.format)?;
+            println!(
+                "Invoices for {} are now numbered {}",
+                describe(set_args.recipient.as_deref()),
+                set_args.format
+            );
+        },
+        NumberingAction::Remove(remove_args) => {
+            remove_scheme(conn, remove_args.recipient.as_deref())?;
+            println!("Removed numbering scheme for {}", describe(remove_args.recipient.as_deref()));
+        },
+        NumberingAction::List(list_args) => {
+            let schemes = select_schemes(conn)?;
+            print_items(list_args.format, &schemes, |s| ListScheme::from(s))?;
+        },
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Tabled)]
+struct PreviewActivity {
+    #[tabled(rename = "Description")]
+    desc: String,
+    #[tabled(rename = "Tickets")]
+    tickets: String,
+    #[tabled(rename = "Entries")]
+    entries: usize,
+    #[tabled(rename = "Duration")]
+    dur: f64,
+    #[tabled(rename = "Price")]
+    price: f64,
+}
+
+pub fn build_invoice(conn: &mut SqliteConnection, args: BuildArgs) -> DynResult<()> {
+    let drafts = draft_activities(conn, &args.period, &args.recipient, args.group_by)?;
+
+    let inv_num = match args.num {
+        Some(n) => n,
+        None => next_invoice_num(conn)?,
+    };
+
+    // Invoices are found by month, so one for another period goes by its first month.
+    let inv_month = Month::containing(args.period.first());
+    let number = next_reference(conn, &args.recipient, &inv_month)?
+        .map_or_else(|| inv_num.to_string(), |r| r.inv_ref);
+
+    println!("Invoice {number} for '{}' ({}):", args.recipient, args.period);
+    println!("{}", Table::new(
+        drafts.iter().map(|d| PreviewActivity::new(d, args.uprice))
+    ).with(Style::psql()));
+
+    if !args.yes && !confirm("Create this invoice?")? {
+        println!("Invoice not created");
+        return Ok(());
+    }
+
+    let invoice = create_invoice(
+        conn,
+        new_invoice(inv_num, &args.period, args.recipient),
+        args.uprice,
+        drafts
+    )?;
+
+    println!("Created invoice {}", invoice.number());
+
+    Ok(())
+}
+
+impl PreviewActivity {
+    fn new(draft: &DraftActivity, uprice: f64) -> PreviewActivity {
+        let dur = draft.dur();
+
+        PreviewActivity {
+            desc: draft.desc.clone(),
+            tickets: draft.times.iter()
+                .flat_map(|t| &t.tickets)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            entries: draft.times.len(),
+            dur,
+            price: (uprice * dur * 100.0).round() / 100.0,
+        }
+    }
+}
@@ -1,42 +1,186 @@
-use diesel::{prelude::*, query_builder::AsQuery};
-use tabled::{Table, settings::Style};
+use std::collections::BTreeMap;
 
-use crate::{cli::args::{DocIdentifier, EntryType, ListArgs}, csv::convert::CsvTime, orm::{model::{Invoice, Time}, query::TimeWithTickets}, util::error::DynResult};
+use chrono::Days;
+use diesel::prelude::*;
+use serde::Serialize;
+use tabled::{Table, Tabled, settings::Style};
 
+use crate::{cli::{args::{DocIdentifier, EntryType, GroupRule, ListArgs, OutputFormat, TimeFilter, UnbilledArgs}, output::print_items}, csv::convert::{CsvTime, ListActivity, ListInvoice, ListTime}, orm::{model::{Invoice, InvoiceActivity, Recipient, Time}, query::{ActivityWithTickets, InvoiceWithActivities, TimeWithTickets}}, service::time::select_times, util::{date::{Date, Month}, error::DynResult}};
 
 pub fn list(conn: &mut SqliteConnection, args: ListArgs) -> DynResult<()> {
-    let ident = if args.all {
-        args.ident
+    // Filters select time independently of invoices, so the current month is only implied when
+    // nothing else narrows down the entries.
+    let implied = match &args.entry_type {
+        EntryType::Time(filter) => filter.is_empty(),
+        _ => true,
+    };
+
+    let ident = args.inv_ref.map(DocIdentifier::Ref).or(args.ident);
+    let ident = if args.all || !implied {
+        ident
     } else {
-        Some(args.ident.unwrap_or_default())
+        Some(ident.unwrap_or_default())
     };
 
     match args.entry_type {
-        EntryType::Time => list_time(conn, ident),
-        EntryType::Activity => todo!(),
-        EntryType::Invoice => todo!(),
+        EntryType::Time(filter) => list_time(conn, ident, filter, args.format),
+        EntryType::Activity => list_activity(conn, ident, args.format),
+        EntryType::Invoice => list_invoice(conn, ident, args.format),
+        EntryType::Unbilled(unbilled_args) => list_unbilled(conn, unbilled_args, args.format),
     }
 }
 
-pub fn list_time(conn: &mut SqliteConnection, ident: Option<DocIdentifier>) -> DynResult<()> {
-    use crate::orm::schema::{invoice_activity, time};
+pub fn list_time(
+    conn: &mut SqliteConnection,
+    ident: Option<DocIdentifier>,
+    filter: TimeFilter,
+    format: OutputFormat
+) -> DynResult<()> {
+    let invoice = ident.map(|i| Invoice::select_by_identifier(i, conn))
+        .transpose()?;
+
+    let times = select_times(conn, filter, invoice.map(|i| i.inv_num))?;
+
+    print_items(format, &times, |t| ListTime::from(t))
+}
+
+pub fn list_activity(
+    conn: &mut SqliteConnection,
+    ident: Option<DocIdentifier>,
+    format: OutputFormat
+) -> DynResult<()> {
+    use crate::orm::schema::invoice_activity;
 
-    let times = if let Some(ident) = ident {
+    let activities = if let Some(ident) = ident {
         let invoice = Invoice::select_by_identifier(ident, conn)?;
 
-        TimeWithTickets::from_query(
-            Time::query()
-                .inner_join(invoice_activity::table)
-                .filter(invoice_activity::inv_num.eq(invoice.inv_num)),
+        ActivityWithTickets::from_query(
+            InvoiceActivity::query()
+                .filter(invoice_activity::inv_num.eq(invoice.inv_num))
+                .order(invoice_activity::act_num),
             conn
         )
     } else {
-        TimeWithTickets::from_query(time::table.as_query(), conn)
-    }.map_err(|e| format!("Error retrieving times from database:\n{e}"))?;
+        ActivityWithTickets::from_query(
+            InvoiceActivity::query().order(invoice_activity::act_num),
+            conn
+        )
+    }.map_err(|e| format!("Error retrieving activities from database:\n{e}"))?;
+
+    print_items(format, &activities, |a| ListActivity::from(a))
+}
+
+pub fn list_invoice(
+    conn: &mut SqliteConnection,
+    ident: Option<DocIdentifier>,
+    format: OutputFormat
+) -> DynResult<()> {
+    use crate::orm::schema::{invoice, recipient};
+
+    let invoices = if let Some(ident) = ident {
+        vec![InvoiceWithActivities::select_by_identifier(ident, conn)?]
+    } else {
+        InvoiceWithActivities::from_query(
+            invoice::table
+                .inner_join(recipient::table)
+                .order(invoice::inv_num)
+                .select((Invoice::as_select(), Recipient::as_select())),
+            conn
+        ).map_err(|e| format!("Error retrieving invoices from database:\n{e}"))?
+    };
+
+    print_items(format, &invoices, |i| ListInvoice::from(i))
+}
+
+/// Unbilled hours of a month, grouped by project, ticket or description.
+#[derive(Debug, Clone, PartialEq, Serialize, Tabled)]
+pub struct UnbilledGroup {
+    #[tabled(rename = "Month")]
+    pub month: Month,
+    #[tabled(rename = "Group")]
+    pub group: String,
+    #[tabled(rename = "Entries")]
+    pub entries: usize,
+    #[tabled(rename = "Hours")]
+    pub hours: f64,
+}
+
+pub fn list_unbilled(
+    conn: &mut SqliteConnection,
+    args: UnbilledArgs,
+    format: OutputFormat
+) -> DynResult<()> {
+    use crate::orm::schema::time;
+
+    let times = TimeWithTickets::from_query(
+        Time::query()
+            .filter(time::act_num.is_null())
+            .filter(time::time_billable.eq(true))
+            .order(time::time_start),
+        conn
+    ).map_err(|e| format!("Error retrieving unbilled times from database:\n{e}"))?;
+
+    let total: f64 = times.iter().flat_map(|t| t.time_dur).sum();
+    let groups = unbilled_groups(&times, args.group_by);
+
+    print_items(format, &groups, UnbilledGroup::clone)?;
+
+    if format == OutputFormat::Table {
+        println!("Total unbilled: {:.1} hrs", total);
+    }
+
+    if let Some(days) = args.older_than {
+        let cutoff = Date::now().checked_sub_days(Days::new(days.into()))
+            .ok_or("Age threshold is out of range")?;
+
+        let overdue: Vec<CsvTime> = times.into_iter()
+            .filter(|t| t.time_start.date() < cutoff)
+            .map(CsvTime::from)
+            .collect();
+
+        // Written to stderr so that structured output on stdout stays parseable.
+        if !overdue.is_empty() {
+            eprintln!("Unbilled entries older than {days} days:");
+            eprintln!("{}", Table::new(&overdue).with(Style::psql()));
+
+            Err(format!("Found {} unbilled entries older than {days} days", overdue.len()))?
+        }
+    }
 
-    println!("{}", Table::new(
-        times.into_iter().map(CsvTime::from)
-    ).with(Style::psql()));
-    
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Group unbilled times by the month they started in and then by `group_by`. Entries without
+/// tickets are grouped under an empty project or ticket.
+pub fn unbilled_groups(times: &[TimeWithTickets], group_by: GroupRule) -> Vec<UnbilledGroup> {
+    let mut groups: BTreeMap<(Month, String), (usize, f64)> = BTreeMap::new();
+
+    for time in times {
+        let group = match group_by {
+            GroupRule::Project => time.tickets.iter()
+                .min()
+                .map(|t| t.proj_key.clone())
+                .unwrap_or_default(),
+            GroupRule::Ticket => time.tickets.iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            GroupRule::Desc => time.time_desc.clone(),
+        };
+
+        let entry = groups.entry((Month::containing(time.time_start.date()), group))
+            .or_default();
+        entry.0 += 1;
+        entry.1 += time.time_dur.unwrap_or_default();
+    }
+
+    groups.into_iter()
+        .map(|((month, group), (entries, hours))| UnbilledGroup {
+            month,
+            group,
+            entries,
+            // Avoid floating point noise from summing rounded durations.
+            hours: (hours * 10.0).round() / 10.0,
+        })
+        .collect()
+}
@@ -1,12 +1,8 @@
-use std::iter;
+use diesel::prelude::*;
 
-use diesel::{insert_into, prelude::*};
-
-use crate::{cli::args::LogArgs, orm::{insert::LoggedTime, model::TicketTime}, util::error::DynResult};
+use crate::{cli::{args::{EntryLabel, LogArgs}, budget::warn_crossed}, orm::insert::{LoggedTime, NewEntry}, service::{budget::budget_usage, time::log_time}, util::{date::Date, error::DynResult}};
 
 pub fn log(conn: &mut SqliteConnection, args: LogArgs) -> DynResult<()> {
-    use crate::orm::schema::{ticket_time, time};
-
     let date = args.date.unwrap_or_default();
 
     let log = LoggedTime {
@@ -14,24 +10,23 @@ pub fn log(conn: &mut SqliteConnection, args: LogArgs) -> DynResult<()> {
         time_end: date.and_time(args.time_range.end),
         time_desc: args.description,
         act_num: args.activity,
+        time_notes: args.notes.filter(|n| !n.trim().is_empty()),
     };
 
-    let id: i32 = log.insert_into(time::table)
-        .returning(time::time_id)
-        .get_result(conn)
-        .map_err(|e| format!("Error inserting time into database:\n{e}"))?;
+    let (tickets, tags) = EntryLabel::split(args.labels);
 
-    let tickets: Vec<TicketTime> = args.tickets.into_iter()
-        .zip(iter::repeat(id))
-        .map(TicketTime::from)
-        .collect();
+    let before = budget_usage(conn, Some(&tickets), *Date::now())?;
 
-    insert_into(ticket_time::table)
-        .values(tickets)
-        .execute(conn)
-        .map_err(|e| format!("Error inserting ticket-time relations into database:\n{e}"))?;
+    log_time(conn, NewEntry {
+        time: log,
+        tickets: tickets.clone(),
+        tags,
+        billable: args.billable.value(),
+    })?;
 
     println!("Time logged successfully");
 
+    warn_crossed(&before, &budget_usage(conn, Some(&tickets), *Date::now())?);
+
     Ok(())
-}
\ No newline at end of file
+}
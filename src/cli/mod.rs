@@ -1,6 +1,25 @@
 pub mod amend;
 pub mod args;
+pub mod backup;
+pub mod budget;
+pub mod confirm;
+pub mod doctor;
+pub mod edit;
+pub mod export;
 pub mod generate;
+pub mod history;
+pub mod hook;
+pub mod import;
+pub mod invoice;
 pub mod list;
 pub mod log;
-pub mod patterns;
\ No newline at end of file
+pub mod output;
+pub mod patterns;
+pub mod project;
+pub mod report;
+pub mod serve;
+pub mod suggest;
+pub mod ticket;
+pub mod timer;
+pub mod tui;
+pub mod undo;
@@ -0,0 +1,69 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+use tabled::{Table, Tabled, settings::Style};
+
+use crate::cli::args::OutputFormat;
+use crate::util::error::DynResult;
+
+/// Print a listing in the requested format. Structured formats serialize each item in full, while
+/// tables and CSV use a flattened row, because neither can represent nested values.
+pub fn print_items<T, R>(
+    format: OutputFormat,
+    items: &[T],
+    to_row: impl Fn(&T) -> R
+) -> DynResult<()> where
+    T: Serialize,
+    R: Serialize + Tabled
+{
+    write_items(io::stdout(), format, items, to_row)
+}
+
+/// Write a listing like [`print_items`], but to any writer.
+pub fn write_items<W, T, R>(
+    mut writer: W,
+    format: OutputFormat,
+    items: &[T],
+    to_row: impl Fn(&T) -> R
+) -> DynResult<()> where
+    W: Write,
+    T: Serialize,
+    R: Serialize + Tabled
+{
+    let write_error = |e: io::Error| format!("Error writing output:\n{e}");
+
+    match format {
+        OutputFormat::Table => writeln!(
+            writer,
+            "{}",
+            Table::new(items.iter().map(to_row)).with(Style::psql())
+        ).map_err(write_error)?,
+        OutputFormat::Json => writeln!(
+            writer,
+            "{}",
+            serde_json::to_string_pretty(items)
+                .map_err(|e| format!("Error serializing output:\n{e}"))?
+        ).map_err(write_error)?,
+        OutputFormat::Ndjson => for item in items {
+            writeln!(
+                writer,
+                "{}",
+                serde_json::to_string(item)
+                    .map_err(|e| format!("Error serializing output:\n{e}"))?
+            ).map_err(write_error)?;
+        },
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+
+            for item in items {
+                writer.serialize(to_row(item))
+                    .map_err(|e| format!("Error writing output:\n{e}"))?;
+            }
+
+            writer.flush()
+                .map_err(write_error)?;
+        },
+    }
+
+    Ok(())
+}
@@ -16,4 +16,8 @@ pub static ACTIVITY_PATTERN: LazyLock<ActivityPattern> = LazyLock::new(ActivityP
 
 regex! { pub TicketPattern = r"(?<proj_key>\w+)-(?<tick_num>\d+)" }
 
-pub static TICKET_PATTERN: LazyLock<TicketPattern> = LazyLock::new(TicketPattern::new);
\ No newline at end of file
+pub static TICKET_PATTERN: LazyLock<TicketPattern> = LazyLock::new(TicketPattern::new);
+
+regex! { pub TagPattern = r"^(?<remove>-)?#(?<name>[\w-]+)$" }
+
+pub static TAG_PATTERN: LazyLock<TagPattern> = LazyLock::new(TagPattern::new);
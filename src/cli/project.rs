@@ -0,0 +1,48 @@
+use diesel::prelude::*;
+
+use crate::cli::args::{ProjectAction, ProjectAddArgs, ProjectArgs, ProjectEditArgs, ProjectListArgs};
+use crate::cli::output::print_items;
+use crate::csv::convert::ListProject;
+use crate::orm::insert::ProjectChanges;
+use crate::orm::model::Project;
+use crate::service::project::{add_project, edit_project, select_projects};
+use crate::util::error::DynResult;
+
+pub fn project(conn: &mut SqliteConnection, args: ProjectArgs) -> DynResult<()> {
+    match args.action {
+        ProjectAction::Add(add_args) => add(conn, add_args),
+        ProjectAction::Edit(edit_args) => edit(conn, edit_args),
+        ProjectAction::List(list_args) => list(conn, list_args),
+    }
+}
+
+fn add(conn: &mut SqliteConnection, args: ProjectAddArgs) -> DynResult<()> {
+    let key = args.proj_key.clone();
+
+    add_project(conn, Project {
+        proj_key: args.proj_key,
+        proj_name: args.name,
+        proj_billable: !args.non_billable,
+    })?;
+
+    println!("Added project {key}");
+
+    Ok(())
+}
+
+fn edit(conn: &mut SqliteConnection, args: ProjectEditArgs) -> DynResult<()> {
+    let project = edit_project(conn, &args.proj_key, ProjectChanges {
+        proj_name: args.name,
+        proj_billable: args.billable.value(),
+    })?;
+
+    println!("Updated project {} {}", project.proj_key, project.proj_name);
+
+    Ok(())
+}
+
+fn list(conn: &mut SqliteConnection, args: ProjectListArgs) -> DynResult<()> {
+    let projects = select_projects(conn)?;
+
+    print_items(args.format, &projects, |p| ListProject::from(p))
+}
@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use diesel::prelude::*;
+use serde_json::{Map, Value};
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::args::{OutputFormat, ReportArgs, ReportGroup, TimeFilter};
+use crate::orm::model::{Invoice, InvoiceActivity};
+use crate::orm::query::TimeWithTickets;
+use crate::util::date::{Date, Month, Week};
+use crate::util::error::DynResult;
+
+pub fn report(conn: &mut SqliteConnection, args: ReportArgs) -> DynResult<()> {
+    let report = summarize(conn, &args.by, args.filter)?;
+
+    match args.format {
+        OutputFormat::Table => print_table(&args.by, &report),
+        format => write_structured(io::stdout(), format, &args.by, &report)?,
+    }
+
+    Ok(())
+}
+
+/// Hours summed per nested group, in the order the groups first appear.
+#[derive(Debug)]
+pub struct Report {
+    pub rows: Vec<(Vec<String>, Total)>,
+    /// The sum over all entries. An entry with several tickets or tags is counted in the group of
+    /// each, but only once here.
+    pub total: Total,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Total {
+    pub entries: usize,
+    pub hours: f64,
+    /// The part of the hours that is billable.
+    pub billable: f64,
+}
+
+impl Total {
+    fn add(&mut self, time: &TimeWithTickets) {
+        self.entries += 1;
+        self.hours += time.time_dur.unwrap_or_default();
+        if time.time_billable {
+            self.billable += time.time_dur.unwrap_or_default();
+        }
+    }
+
+    /// The billable share of the hours, which is zero when there aren't any.
+    fn ratio(&self) -> f64 {
+        if self.hours > 0.0 { self.billable / self.hours } else { 0.0 }
+    }
+}
+
+/// Sum the hours of the times matching `filter` by the groups in `by`, nested in that order.
+pub fn summarize(
+    conn: &mut SqliteConnection,
+    by: &[ReportGroup],
+    filter: TimeFilter
+) -> DynResult<Report> {
+    use crate::orm::schema::{invoice, invoice_activity};
+
+    let times = TimeWithTickets::from_query(filter.query(), conn)
+        .map_err(|e| format!("Error retrieving times from database:\n{e}"))?;
+
+    let activities: HashMap<i32, (InvoiceActivity, Invoice)> = invoice_activity::table
+        .inner_join(invoice::table)
+        .select((InvoiceActivity::as_select(), Invoice::as_select()))
+        .load(conn)
+        .map_err(|e| format!("Error retrieving activities from database:\n{e}"))?
+        .into_iter()
+        .map(|(a, i)| (a.act_num, (a, i)))
+        .collect();
+
+    // Groups are ordered by when each of their keys first appears, which keeps dates chronological
+    // without comparing them as strings.
+    let mut ranks: Vec<HashMap<String, usize>> = vec![HashMap::new(); by.len()];
+    let mut totals: BTreeMap<Vec<(usize, String)>, Total> = BTreeMap::new();
+    let mut total = Total::default();
+
+    for time in &times {
+        // Every combination of the entry's keys gets the entry, such as one row per ticket.
+        let mut combinations: Vec<Vec<(usize, String)>> = vec![vec![]];
+
+        for (group, ranks) in by.iter().zip(&mut ranks) {
+            let keys: Vec<(usize, String)> = group_keys(time, *group, &activities)
+                .into_iter()
+                .map(|key| {
+                    let next = ranks.len();
+                    (*ranks.entry(key.clone()).or_insert(next), key)
+                })
+                .collect();
+
+            combinations = combinations.into_iter()
+                .flat_map(|keys_so_far| keys.iter().map(move |key| {
+                    let mut keys_so_far = keys_so_far.clone();
+                    keys_so_far.push(key.clone());
+                    keys_so_far
+                }))
+                .collect();
+        }
+
+        for keys in combinations {
+            totals.entry(keys).or_default().add(time);
+        }
+        total.add(time);
+    }
+
+    Ok(Report {
+        rows: totals.into_iter()
+            .map(|(keys, total)| (keys.into_iter().map(|(_, k)| k).collect(), total))
+            .collect(),
+        total,
+    })
+}
+
+fn print_table(by: &[ReportGroup], report: &Report) {
+    let mut builder = Builder::new();
+
+    builder.push_record(
+        by.iter()
+            .map(|g| format!("{g:?}"))
+            .chain(["Entries".into(), "Hours".into(), "Billable".into(), "Billable %".into()])
+    );
+
+    let mut previous: &[String] = &[];
+
+    for (keys, total) in &report.rows {
+        // Only print a group's label on its first row, so that nested groups read as a tree.
+        let shared = keys.iter()
+            .zip(previous)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        builder.push_record(
+            keys.iter()
+                .enumerate()
+                .map(|(i, k)| match k {
+                    _ if i < shared => String::new(),
+                    // Distinguish entries without a value from a repeated label.
+                    k if k.is_empty() => "-".to_owned(),
+                    k => k.clone(),
+                })
+                .chain(format_total(total))
+        );
+
+        previous = keys;
+    }
+
+    builder.push_record(
+        ["TOTAL".to_owned()].into_iter()
+            .chain(by.iter().skip(1).map(|_| String::new()))
+            .chain(format_total(&report.total))
+    );
+
+    println!("{}", builder.build().with(Style::psql()));
+}
+
+/// Write one record per group, keyed by the lowercase group names. Totals are left to the
+/// consumer, so that every record has the same shape. Entries with several tickets or tags are in
+/// the record of each, so summing the records over such a group counts them more than once.
+pub fn write_structured(
+    mut writer: impl Write,
+    format: OutputFormat,
+    by: &[ReportGroup],
+    report: &Report
+) -> DynResult<()> {
+    let names: Vec<String> = by.iter()
+        .map(|g| g.to_possible_value().expect("No groups are skipped").get_name().to_owned())
+        .collect();
+
+    let rows: Vec<Map<String, Value>> = report.rows.iter()
+        .map(|(keys, total)| names.iter()
+            .cloned()
+            .zip(keys.iter().map(|k| Value::from(k.as_str())))
+            .chain([
+                ("entries".to_owned(), Value::from(total.entries)),
+                ("hours".to_owned(), Value::from((total.hours * 10.0).round() / 10.0)),
+                ("billable".to_owned(), Value::from((total.billable * 10.0).round() / 10.0)),
+                ("billable_ratio".to_owned(), Value::from((total.ratio() * 100.0).round() / 100.0)),
+            ])
+            .collect()
+        )
+        .collect();
+
+    match format {
+        OutputFormat::Json => writeln!(
+            writer,
+            "{}",
+            serde_json::to_string_pretty(&rows)
+                .map_err(|e| format!("Error serializing output:\n{e}"))?
+        ).map_err(|e| format!("Error writing output:\n{e}"))?,
+        OutputFormat::Ndjson => for row in &rows {
+            writeln!(
+                writer,
+                "{}",
+                serde_json::to_string(row)
+                    .map_err(|e| format!("Error serializing output:\n{e}"))?
+            ).map_err(|e| format!("Error writing output:\n{e}"))?;
+        },
+        _ => {
+            let mut writer = csv::Writer::from_writer(writer);
+
+            writer.write_record(
+                names.iter()
+                    .map(String::as_str)
+                    .chain(["entries", "hours", "billable", "billable_ratio"])
+            ).map_err(|e| format!("Error writing output:\n{e}"))?;
+
+            for (keys, total) in &report.rows {
+                writer.write_record(
+                    keys.iter()
+                        .cloned()
+                        .chain([
+                            total.entries.to_string(),
+                            format_hours(total.hours),
+                            format_hours(total.billable),
+                            format!("{:.2}", total.ratio()),
+                        ])
+                ).map_err(|e| format!("Error writing output:\n{e}"))?;
+            }
+
+            writer.flush()
+                .map_err(|e| format!("Error writing output:\n{e}"))?;
+        },
+    }
+
+    Ok(())
+}
+
+/// The groups an entry falls into. Entries are in the group of each of their tickets or tags, and
+/// in a group with an empty key if they have none.
+fn group_keys(
+    time: &TimeWithTickets,
+    group: ReportGroup,
+    activities: &HashMap<i32, (InvoiceActivity, Invoice)>
+) -> Vec<String> {
+    let activity = time.act_num.and_then(|a| activities.get(&a));
+
+    let key = match group {
+        ReportGroup::Project => time.tickets.iter()
+            .min()
+            .map(|t| t.proj_key.clone())
+            .unwrap_or_default(),
+        ReportGroup::Ticket if !time.tickets.is_empty() => {
+            return time.tickets.iter().map(|t| t.to_string()).collect()
+        },
+        ReportGroup::Tag if !time.tags.is_empty() => {
+            return time.tags.iter().map(|t| format!("#{t}")).collect()
+        },
+        ReportGroup::Ticket | ReportGroup::Tag => String::new(),
+        ReportGroup::Recipient => activity
+            .map(|(_, i)| i.recip_id.clone())
+            .unwrap_or_default(),
+        ReportGroup::Activity => activity
+            .map(|(a, _)| format!("{}: {}", a.act_num, a.act_desc))
+            .unwrap_or_default(),
+        ReportGroup::Day => Date::from(time.time_start.date()).to_string(),
+        ReportGroup::Week => Week::containing(time.time_start.date()).to_string(),
+        ReportGroup::Month => Month::containing(time.time_start.date()).to_string(),
+    };
+
+    vec![key]
+}
+
+fn format_total(total: &Total) -> [String; 4] {
+    [
+        total.entries.to_string(),
+        format_hours(total.hours),
+        format_hours(total.billable),
+        format!("{:.0}%", total.ratio() * 100.0),
+    ]
+}
+
+fn format_hours(hours: f64) -> String {
+    format!("{hours:.1}")
+}
@@ -0,0 +1,58 @@
+use diesel::prelude::*;
+use tiny_http::{Header, Server};
+
+use crate::api::{handler::{Request, handle}, response::ApiError};
+use crate::cli::args::ServeArgs;
+use crate::util::error::DynResult;
+
+/// Serve the API until interrupted. Each request runs in its own transaction, so this shouldn't be
+/// called inside one.
+pub fn serve(conn: &mut SqliteConnection, args: ServeArgs) -> DynResult<()> {
+    let server = Server::http(&args.bind)
+        .map_err(|e| format!("Error binding to {}:\n{e}", args.bind))?;
+
+    let local = args.bind.starts_with("127.") || args.bind.starts_with("localhost");
+
+    if args.token.is_none() && !local {
+        eprintln!(
+            "Warning: serving on {} without a token, so anyone who can connect can make changes",
+            args.bind
+        );
+    }
+
+    println!("Listening on http://{}", args.bind);
+
+    for mut http_request in server.incoming_requests() {
+        let mut request = Request {
+            method: http_request.method().to_string(),
+            url: http_request.url().to_owned(),
+            authorization: http_request.headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.to_string()),
+            body: String::new(),
+        };
+
+        let response = match http_request.as_reader().read_to_string(&mut request.body) {
+            Ok(_) => handle(conn, args.token.as_deref(), &request),
+            Err(e) => ApiError::bad_request(format!("Error reading body:\n{e}")).into_response(),
+        };
+
+        println!("{} {} {}", request.method, request.url, response.status);
+
+        let content_type = Header::from_bytes("Content-Type", response.content_type)
+            .expect("Content types are valid header values");
+
+        let result = http_request.respond(
+            tiny_http::Response::from_data(response.body)
+                .with_status_code(response.status)
+                .with_header(content_type)
+        );
+
+        if let Err(e) = result {
+            eprintln!("Error sending response:\n{e}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use chrono::TimeDelta;
+use diesel::prelude::*;
+
+use crate::cli::args::{GitSuggestArgs, ImportOptions, SuggestArgs, SuggestSource};
+use crate::cli::import::import_entries;
+use crate::git::history::{configured_author, read_commits, sessions};
+use crate::service::project::known_tickets;
+use crate::util::error::DynResult;
+
+pub fn suggest(conn: &mut SqliteConnection, args: SuggestArgs) -> DynResult<()> {
+    match args.source {
+        SuggestSource::Git(git_args) => suggest_git(conn, git_args),
+    }
+}
+
+/// Propose entries for the sessions of work visible in a repository's history. Suggestions go
+/// through the same checks as an import, so anything already logged is skipped.
+pub fn suggest_git(conn: &mut SqliteConnection, args: GitSuggestArgs) -> DynResult<()> {
+    let date = args.date.unwrap_or_default();
+    let author = match args.author {
+        Some(author) => author,
+        None => configured_author(&args.repo)?,
+    };
+
+    let commits = read_commits(&args.repo, &author, *date)?;
+
+    if commits.is_empty() {
+        println!("No commits by {author} on {date}");
+        return Ok(());
+    }
+
+    let sessions = sessions(
+        commits,
+        TimeDelta::minutes(args.gap.into()),
+        TimeDelta::minutes(args.lead.into())
+    );
+
+    let entries = sessions.into_iter()
+        .map(|session| {
+            let first = &session.commits[0].hash;
+            let last = &session.commits[session.commits.len() - 1].hash;
+            let source = if first == last { first.clone() } else { format!("{first}..{last}") };
+
+            let tickets = known_tickets(conn, session.tickets())?;
+
+            Ok((source, session.into_entry(tickets)))
+        })
+        .collect::<DynResult<_>>()?;
+
+    import_entries(conn, entries, ImportOptions {
+        dry_run: !args.log,
+        create_projects: false,
+        allow_overlaps: args.allow_overlaps,
+    })?;
+
+    if !args.log {
+        println!("Use --log to log these entries");
+    }
+
+    Ok(())
+}
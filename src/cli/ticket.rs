@@ -0,0 +1,53 @@
+use diesel::prelude::*;
+
+use crate::cli::args::{TicketAction, TicketAddArgs, TicketArgs, TicketEditArgs, TicketListArgs};
+use crate::cli::output::print_items;
+use crate::csv::convert::ListTicket;
+use crate::orm::insert::TicketChanges;
+use crate::orm::model::TicketDetails;
+use crate::service::ticket::{add_ticket, edit_ticket, select_tickets};
+use crate::util::error::DynResult;
+
+pub fn ticket(conn: &mut SqliteConnection, args: TicketArgs) -> DynResult<()> {
+    match args.action {
+        TicketAction::Add(add_args) => add(conn, add_args),
+        TicketAction::Edit(edit_args) => edit(conn, edit_args),
+        TicketAction::List(list_args) => list(conn, list_args),
+    }
+}
+
+fn add(conn: &mut SqliteConnection, args: TicketAddArgs) -> DynResult<()> {
+    let key = args.ticket.to_string();
+
+    add_ticket(conn, TicketDetails {
+        proj_key: args.ticket.proj_key,
+        tick_num: args.ticket.tick_num,
+        tick_title: args.title,
+        tick_url: args.url,
+        tick_status: args.status.to_string(),
+        tick_estimate: args.estimate,
+    })?;
+
+    println!("Added ticket {key}");
+
+    Ok(())
+}
+
+fn edit(conn: &mut SqliteConnection, args: TicketEditArgs) -> DynResult<()> {
+    let details = edit_ticket(conn, &args.ticket, TicketChanges {
+        tick_title: args.title,
+        tick_url: args.url,
+        tick_status: args.status.map(|s| s.to_string()),
+        tick_estimate: args.estimate,
+    })?;
+
+    println!("Updated ticket {} {}", args.ticket, details.tick_title);
+
+    Ok(())
+}
+
+fn list(conn: &mut SqliteConnection, args: TicketListArgs) -> DynResult<()> {
+    let tickets = select_tickets(conn, args.project, args.status.map(|s| s.to_string()))?;
+
+    print_items(args.format, &tickets, |t| ListTicket::from(t))
+}
@@ -0,0 +1,68 @@
+use chrono::{DurationRound, Local, NaiveDateTime, TimeDelta};
+use diesel::prelude::*;
+
+use crate::cli::args::{StartArgs, TimerAction, TimerArgs};
+use crate::orm::ticket::Ticket;
+use crate::service::timer::{running_timer, start_timer, stop_timer, timer_tickets};
+use crate::util::error::DynResult;
+
+pub fn timer(conn: &mut SqliteConnection, args: TimerArgs) -> DynResult<()> {
+    match args.action {
+        TimerAction::Start(start_args) => start(conn, start_args),
+        TimerAction::Stop => {
+            let time = stop_timer(conn, now())?;
+            println!("Logged entry {}", time.time_id);
+            Ok(())
+        },
+        TimerAction::Status => status(conn),
+    }
+}
+
+/// The current time, to the minute, which is as precise as entries get.
+pub fn now() -> NaiveDateTime {
+    let now = Local::now().naive_local();
+    now.duration_trunc(TimeDelta::minutes(1)).unwrap_or(now)
+}
+
+fn start(conn: &mut SqliteConnection, args: StartArgs) -> DynResult<()> {
+    start_timer(conn, args.description, args.tickets, now())?;
+
+    let tickets = timer_tickets(conn)?;
+
+    if tickets.is_empty() {
+        println!("Timer started");
+    } else {
+        println!("Timer started for {}", join(&tickets));
+    }
+
+    Ok(())
+}
+
+fn status(conn: &mut SqliteConnection) -> DynResult<()> {
+    let tickets = timer_tickets(conn)?;
+
+    match running_timer(conn)? {
+        Some(timer) => {
+            let hours = (now() - *timer.timer_start).num_minutes() as f64 / 60.0;
+            println!("Running since {} ({hours:.1}h): {}", timer.timer_start, timer.timer_desc);
+            if !tickets.is_empty() {
+                println!("Tickets: {}", join(&tickets));
+            }
+        },
+        None => {
+            println!("No timer is running");
+            if !tickets.is_empty() {
+                println!("Tickets for the next timer: {}", join(&tickets));
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn join(tickets: &[Ticket]) -> String {
+    tickets.iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,99 @@
+use diesel::prelude::*;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+
+use crate::cli::args::TuiArgs;
+use crate::orm::insert::NewEntry;
+use crate::orm::model::{Invoice, Recipient};
+use crate::orm::query::InvoiceWithActivities;
+use crate::service::audit::{finish_command, start_command};
+use crate::service::time::{select_times, update_time};
+use crate::tui::{app::{App, Effect, Key}, view::draw};
+use crate::util::error::{DynError, DynResult};
+
+/// Run the interface until it's quit. Each edit is saved in its own transaction, so this shouldn't
+/// be called inside one.
+pub fn tui(conn: &mut SqliteConnection, args: TuiArgs) -> DynResult<()> {
+    let mut app = App::new(*args.date.unwrap_or_default());
+    reload(conn, &mut app)?;
+
+    let mut terminal = ratatui::init();
+    let result = run(conn, &mut terminal, &mut app);
+    ratatui::restore();
+
+    result
+}
+
+fn run(
+    conn: &mut SqliteConnection,
+    terminal: &mut DefaultTerminal,
+    app: &mut App
+) -> DynResult<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))
+            .map_err(|e| format!("Error drawing interface:\n{e}"))?;
+
+        let Event::Key(event) = event::read()
+            .map_err(|e| format!("Error reading input:\n{e}"))? else {
+            continue;
+        };
+
+        if event.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let key = match event.code {
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Esc => Key::Esc,
+            KeyCode::Backspace => Key::Backspace,
+            _ => continue,
+        };
+
+        match app.handle(key) {
+            Some(Effect::Quit) => return Ok(()),
+            Some(Effect::Reload) => reload(conn, app)?,
+            Some(Effect::Save { time_id, entry }) => {
+                // Invalid edits are reported in the interface rather than ending it.
+                match save(conn, time_id, entry) {
+                    Ok(_) => app.message = Some(format!("Updated entry {time_id}")),
+                    Err(e) => app.message = Some(e.to_string().replace('\n', " ")),
+                }
+                reload(conn, app)?;
+            },
+            None => {},
+        }
+    }
+}
+
+/// Save an edited entry as a command of its own, so that it can be undone like any other.
+fn save(conn: &mut SqliteConnection, time_id: i32, entry: NewEntry) -> DynResult<()> {
+    conn.transaction::<_, DynError, _>(|conn| {
+        let cmd_id = start_command(conn, &format!("tui amend {time_id}"))?;
+        update_time(conn, time_id, entry)?;
+        Ok(finish_command(conn, cmd_id)?)
+    })
+}
+
+fn reload(conn: &mut SqliteConnection, app: &mut App) -> DynResult<()> {
+    use crate::orm::schema::{invoice, recipient};
+
+    let times = select_times(conn, app.filter(), None)?;
+
+    let invoices = InvoiceWithActivities::from_query(
+        invoice::table
+            .inner_join(recipient::table)
+            .filter(invoice::inv_month.eq(app.month()))
+            .order(invoice::inv_num)
+            .select((Invoice::as_select(), Recipient::as_select())),
+        conn
+    ).map_err(|e| format!("Error retrieving invoices from database:\n{e}"))?;
+
+    app.load(times, invoices);
+
+    Ok(())
+}
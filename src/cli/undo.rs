@@ -0,0 +1,28 @@
+use diesel::prelude::*;
+
+use crate::cli::{args::UndoArgs, confirm::confirm};
+use crate::service::audit::{last_undoable, select_changes, undo as undo_command};
+use crate::util::error::DynResult;
+
+pub fn undo(conn: &mut SqliteConnection, args: UndoArgs) -> DynResult<()> {
+    let target = last_undoable(conn)?;
+    let changes = select_changes(conn, target.cmd_id)?.len();
+
+    println!(
+        "Command {} at {} made {changes} change(s):\n  {}",
+        target.cmd_id,
+        target.cmd_time,
+        target.cmd_line
+    );
+
+    if !args.yes && !confirm("Undo it?")? {
+        println!("Nothing was undone");
+        return Ok(());
+    }
+
+    let reverted = undo_command(conn, &target)?;
+
+    println!("Undid command {}, reverting {reverted} change(s)", target.cmd_id);
+
+    Ok(())
+}
@@ -1,7 +1,14 @@
+use std::fmt::Display;
+
 use serde::Serialize;
+use serde_json::Value;
 use tabled::Tabled;
 
-use crate::orm::query::TimeWithTickets;
+use crate::orm::{model::{Audit, Project, Scheme}, query::{ActivityWithTickets, InvoiceWithActivities, TicketWithTime, TimeWithTickets}, ticket::Ticket};
+use crate::service::audit::{CommandSummary, parse_row, primary_key};
+use crate::service::backup::Backup;
+use crate::service::budget::BudgetUsage;
+use crate::service::doctor::Problem;
 
 #[derive(Debug, Serialize, Tabled)]
 pub struct CsvTime {
@@ -25,4 +32,322 @@ impl From<TimeWithTickets> for CsvTime {
                 .join(", ")
         }
     }
-}
\ No newline at end of file
+}
+/// A timesheet row that also has the notes of the entry.
+#[derive(Debug, Serialize)]
+pub struct CsvDetailedTime {
+    pub time_start: String,
+    pub time_end: String,
+    pub time_dur: f64,
+    pub tickets: String,
+    pub time_desc: String,
+    pub time_notes: String,
+}
+
+impl From<TimeWithTickets> for CsvDetailedTime {
+    fn from(value: TimeWithTickets) -> Self {
+        CsvDetailedTime {
+            time_start: value.time_start.to_string(),
+            time_end: value.time_end.to_string(),
+            time_dur: value.time_dur.unwrap(),
+            tickets: join_tickets(&value.tickets),
+            time_desc: value.time_desc,
+            time_notes: value.time_notes.unwrap_or_default(),
+        }
+    }
+}
+
+fn join_tickets<'a>(tickets: impl IntoIterator<Item = &'a Ticket>) -> String {
+    tickets.into_iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListTime {
+    pub time_id: i32,
+    pub time_start: String,
+    pub time_end: String,
+    pub time_dur: f64,
+    pub tickets: String,
+    pub tags: String,
+    pub time_desc: String,
+    #[tabled(display = "display_option")]
+    pub act_num: Option<i32>,
+    pub time_billable: bool,
+}
+
+impl From<&TimeWithTickets> for ListTime {
+    fn from(value: &TimeWithTickets) -> Self {
+        ListTime {
+            time_id: value.time_id,
+            time_start: value.time_start.to_string(),
+            time_end: value.time_end.to_string(),
+            time_dur: value.time_dur.unwrap_or_default(),
+            tickets: join_tickets(&value.tickets),
+            tags: value.tags.iter()
+                .map(|t| format!("#{t}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            time_desc: value.time_desc.clone(),
+            act_num: value.act_num,
+            time_billable: value.time_billable,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListActivity {
+    pub act_num: i32,
+    pub inv_num: i32,
+    pub act_desc: String,
+    pub act_uprice: f64,
+    pub act_dur: f64,
+    pub act_price: f64,
+    pub tickets: String,
+}
+
+impl From<&ActivityWithTickets> for ListActivity {
+    fn from(value: &ActivityWithTickets) -> Self {
+        ListActivity {
+            act_num: value.act_num,
+            inv_num: value.inv_num,
+            act_desc: value.act_desc.clone(),
+            act_uprice: value.act_uprice,
+            act_dur: value.act_dur,
+            act_price: value.act_price,
+            tickets: join_tickets(&value.tickets),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListInvoice {
+    pub inv_num: i32,
+    #[tabled(display = "display_option")]
+    pub inv_ref: Option<String>,
+    pub inv_month: String,
+    pub inv_period: String,
+    #[tabled(display = "display_option")]
+    pub inv_created: Option<String>,
+    pub recip_id: String,
+    pub activities: usize,
+    pub inv_dur: f64,
+    pub inv_total: f64,
+}
+
+impl From<&InvoiceWithActivities> for ListInvoice {
+    fn from(value: &InvoiceWithActivities) -> Self {
+        ListInvoice {
+            inv_num: value.inv_num,
+            inv_ref: value.inv_ref.clone(),
+            inv_month: value.inv_month.to_string(),
+            inv_period: value.inv_period.to_string(),
+            inv_created: value.inv_created.as_ref().map(|d| d.to_string()),
+            recip_id: value.recipient.recip_id.clone(),
+            activities: value.activities.len(),
+            inv_dur: value.inv_dur,
+            inv_total: value.inv_total,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListProject {
+    pub proj_key: String,
+    pub proj_name: String,
+    pub proj_billable: bool,
+}
+
+impl From<&Project> for ListProject {
+    fn from(value: &Project) -> Self {
+        ListProject {
+            proj_key: value.proj_key.clone(),
+            proj_name: value.proj_name.clone(),
+            proj_billable: value.proj_billable,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListTicket {
+    pub ticket: String,
+    pub tick_title: String,
+    pub tick_status: String,
+    #[tabled(display = "display_option")]
+    pub tick_estimate: Option<f64>,
+    pub tick_logged: f64,
+    #[tabled(display = "display_option")]
+    pub tick_url: Option<String>,
+}
+
+impl From<&TicketWithTime> for ListTicket {
+    fn from(value: &TicketWithTime) -> Self {
+        ListTicket {
+            ticket: value.details.ticket().to_string(),
+            tick_title: value.details.tick_title.clone(),
+            tick_status: value.details.tick_status.clone(),
+            tick_estimate: value.details.tick_estimate,
+            tick_logged: value.tick_logged,
+            tick_url: value.details.tick_url.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListBudget {
+    pub budget: String,
+    #[tabled(display = "display_option")]
+    pub bud_hours: Option<f64>,
+    #[tabled(display = "display_option")]
+    pub bud_money: Option<f64>,
+    pub spent_hours: f64,
+    #[tabled(display = "display_option")]
+    pub spent_money: Option<f64>,
+    pub used: String,
+    pub remaining_hours: f64,
+    pub weekly_hours: f64,
+    #[tabled(display = "display_option")]
+    pub runs_out: Option<String>,
+}
+
+impl From<&BudgetUsage> for ListBudget {
+    fn from(value: &BudgetUsage) -> Self {
+        let round = |n: f64| (n * 10.0).round() / 10.0;
+
+        ListBudget {
+            budget: value.target().to_string(),
+            bud_hours: value.budget.bud_hours,
+            bud_money: value.budget.bud_money,
+            spent_hours: round(value.spent_hours),
+            spent_money: value.spent_money.map(|m| (m * 100.0).round() / 100.0),
+            used: format!("{:.0}%", value.used * 100.0),
+            remaining_hours: round(value.remaining_hours),
+            weekly_hours: round(value.weekly_hours),
+            runs_out: value.runs_out.as_ref().map(|d| d.to_string()),
+        }
+    }
+}
+
+fn display_option<T: Display>(value: &Option<T>) -> String {
+    value.as_ref()
+        .map(|v| v.to_string())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListCommand {
+    pub cmd_id: i32,
+    pub cmd_time: String,
+    pub cmd_line: String,
+    pub changes: i64,
+    #[tabled(display = "display_option")]
+    pub status: Option<String>,
+}
+
+impl From<&CommandSummary> for ListCommand {
+    fn from(value: &CommandSummary) -> Self {
+        ListCommand {
+            cmd_id: value.command.cmd_id,
+            cmd_time: value.command.cmd_time.to_string(),
+            cmd_line: value.command.cmd_line.clone(),
+            changes: value.changes,
+            status: match (value.command.cmd_undoes, value.undone_by) {
+                (Some(undoes), _) => Some(format!("undid {undoes}")),
+                (_, Some(undone_by)) => Some(format!("undone by {undone_by}")),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// A recorded change. Updates only show the columns that changed, while inserts and deletes show
+/// the whole row.
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListChange {
+    pub aud_table: String,
+    pub aud_action: String,
+    pub row: String,
+    pub details: String,
+}
+
+impl From<&Audit> for ListChange {
+    fn from(value: &Audit) -> Self {
+        let old = parse_row(value, value.aud_old.as_deref()).unwrap_or_default();
+        let new = parse_row(value, value.aud_new.as_deref()).unwrap_or_default();
+        let row = if new.is_empty() { &old } else { &new };
+
+        let key = primary_key(&value.aud_table).unwrap_or_default();
+
+        ListChange {
+            aud_table: value.aud_table.clone(),
+            aud_action: value.aud_action.clone(),
+            row: key.iter()
+                .map(|c| format!("{c}={}", row.get(*c).unwrap_or(&Value::Null)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            details: row.iter()
+                .filter(|(c, _)| !key.contains(&c.as_str()))
+                .filter(|(c, v)| old.is_empty() || new.is_empty() || old.get(*c) != Some(*v))
+                .map(|(c, v)| match old.get(c) {
+                    Some(before) if !new.is_empty() => format!("{c}: {before} -> {v}"),
+                    _ => format!("{c}: {v}"),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListBackup {
+    pub name: String,
+    pub kind: String,
+    pub created: String,
+    pub size: u64,
+}
+
+impl From<&Backup> for ListBackup {
+    fn from(value: &Backup) -> Self {
+        ListBackup {
+            name: value.name.clone(),
+            kind: value.kind.name().to_owned(),
+            created: value.created.format("%Y-%m-%d %H:%M:%S").to_string(),
+            size: value.size,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListScheme {
+    pub recipient: String,
+    pub format: String,
+}
+
+impl From<&Scheme> for ListScheme {
+    fn from(value: &Scheme) -> Self {
+        ListScheme {
+            recipient: value.recip_id.clone().unwrap_or_else(|| "(all)".to_owned()),
+            format: value.sch_format.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+pub struct ListProblem {
+    pub check: String,
+    pub problem: String,
+    #[tabled(display = "display_option")]
+    pub fix: Option<String>,
+}
+
+impl From<&Problem> for ListProblem {
+    fn from(value: &Problem) -> Self {
+        ListProblem {
+            check: value.check.name().to_owned(),
+            problem: value.description.clone(),
+            fix: value.fix.as_ref().map(|f| f.to_string()),
+        }
+    }
+}
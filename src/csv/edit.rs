@@ -0,0 +1,153 @@
+use std::{fmt::Display, fs, io::Write, path::Path};
+
+use chrono::Timelike;
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+
+use crate::orm::{insert::{LoggedTime, NewEntry}, query::TimeWithTickets, ticket::Ticket};
+use crate::util::{date::DateTime, error::DynResult};
+
+const HEADERS: [&str; 9] = [
+    "Id", "Start", "End", "Activity", "Tickets", "Tags", "Billable", "Description", "Notes"
+];
+
+/// A row read back from an edit file. Rows without an id are new entries.
+#[derive(Debug)]
+pub struct EditedEntry {
+    pub line: u64,
+    pub time_id: Option<i32>,
+    pub entry: NewEntry,
+}
+
+pub fn write_edit_file(mut file: impl Write, times: &[TimeWithTickets]) -> DynResult<()> {
+    writeln!(
+        file,
+        "# Edit entries below. Rows without an id are inserted and removed rows are deleted.\n\
+        # Lines starting with '#' are ignored."
+    ).map_err(|e| format!("Error writing edit file:\n{e}"))?;
+
+    let mut writer = WriterBuilder::new().from_writer(file);
+
+    writer.write_record(HEADERS)
+        .map_err(|e| format!("Error writing edit file:\n{e}"))?;
+
+    for time in times {
+        writer.write_record([
+            time.time_id.to_string(),
+            format_time(&time.time_start),
+            format_time(&time.time_end),
+            time.act_num.map(|a| a.to_string()).unwrap_or_default(),
+            time.tickets.iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            time.tags.iter()
+                .map(|t| format!("#{t}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            if time.time_billable { "yes" } else { "no" }.to_owned(),
+            time.time_desc.clone(),
+            time.time_notes.clone().unwrap_or_default(),
+        ]).map_err(|e| format!("Error writing edit file:\n{e}"))?;
+    }
+
+    writer.flush()
+        .map_err(|e| format!("Error writing edit file:\n{e}"))?;
+
+    Ok(())
+}
+
+/// Times are written to the minute like elsewhere, unless that would drop their seconds and make
+/// every entry with seconds look edited.
+fn format_time(time: &DateTime) -> String {
+    if time.second() == 0 {
+        time.to_string()
+    } else {
+        time.format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+}
+
+pub fn read_edit_file(path: &Path) -> DynResult<Vec<EditedEntry>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Error reading file {}:\n{e}", path.display()))?;
+
+    let mut reader = ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .has_headers(true)
+        .from_reader(contents.as_bytes());
+
+    let line_error = |line: u64, e: &dyn Display| format!(
+        "Error on line {line} of edit file:\n{e}\n> {}",
+        contents.lines().nth((line as usize).saturating_sub(1)).unwrap_or_default()
+    );
+
+    // Values are read by position, so columns that were moved or removed would end up in the
+    // wrong fields.
+    let headers = reader.headers()
+        .map_err(|e| format!("Error reading edit file:\n{e}"))?;
+    if !headers.iter().map(str::trim).eq(HEADERS) {
+        let line = headers.position().map_or(0, |p| p.line());
+        Err(line_error(line, &format!("The columns must be {}", HEADERS.join(", "))))?
+    }
+
+    reader.records()
+        .map(|record| {
+            let record = record.map_err(|e| format!("Error reading edit file:\n{e}"))?;
+            let line = record.position().map_or(0, |p| p.line());
+
+            parse_row(line, &record).map_err(|e| line_error(line, &e).into())
+        })
+        .collect()
+}
+
+fn parse_row(line: u64, record: &StringRecord) -> DynResult<EditedEntry> {
+    let get = |i: usize| record.get(i).unwrap_or_default().trim();
+
+    let time_id = match get(0) {
+        "" => None,
+        id => Some(id.parse().map_err(|e| format!("Error parsing id:\n{e}"))?),
+    };
+
+    let act_num = match get(3) {
+        "" => None,
+        act => Some(act.parse().map_err(|e| format!("Error parsing activity number:\n{e}"))?),
+    };
+
+    let tickets = get(4).split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<Ticket>, _>>()?;
+
+    // Tags are written with their '#', but it's optional when reading them back.
+    let tags = get(5).split([' ', ','])
+        .map(|t| t.trim_start_matches('#'))
+        .filter(|t| !t.is_empty())
+        .map(str::to_owned)
+        .collect();
+
+    // New rows without a value follow the default of their projects.
+    let billable = match get(6).to_lowercase().as_str() {
+        "" => None,
+        "yes" | "y" | "true" => Some(true),
+        "no" | "n" | "false" => Some(false),
+        other => Err(format!("Billable must be 'yes' or 'no', not '{other}'"))?,
+    };
+
+    Ok(EditedEntry {
+        line,
+        time_id,
+        entry: NewEntry {
+            time: LoggedTime {
+                time_start: *get(1).parse::<DateTime>()?,
+                time_end: *get(2).parse::<DateTime>()?,
+                time_desc: get(7).to_owned(),
+                // Notes keep their inner line breaks, which the CSV quotes.
+                time_notes: Some(get(8).to_owned()).filter(|n| !n.is_empty()),
+                act_num,
+            },
+            tickets,
+            tags,
+            billable,
+        },
+    })
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use csv::{Reader, ReaderBuilder, StringRecord};
+
+use crate::cli::args::{ColumnMapping, CsvField};
+use crate::orm::{insert::{LoggedTime, NewEntry}, ticket::Ticket};
+use crate::util::{date::DateTime, error::DynResult};
+
+/// Column indices for each field, after applying any mappings to the timesheet headers.
+struct Columns {
+    start: usize,
+    end: Option<usize>,
+    duration: Option<usize>,
+    tickets: Option<usize>,
+    description: usize,
+}
+
+impl Columns {
+    fn new(headers: &StringRecord, mappings: &[ColumnMapping]) -> DynResult<Columns> {
+        let find = |field: CsvField| {
+            let header = mappings.iter()
+                .rfind(|m| m.field == field)
+                .map_or(field.default_header(), |m| m.header.as_str());
+
+            headers.iter().position(|h| h.trim() == header)
+        };
+
+        let columns = Columns {
+            start: find(CsvField::Start).ok_or("Timesheet has no start column")?,
+            end: find(CsvField::End),
+            duration: find(CsvField::Duration),
+            tickets: find(CsvField::Tickets),
+            description: find(CsvField::Description).ok_or("Timesheet has no description column")?,
+        };
+
+        if columns.end.is_none() && columns.duration.is_none() {
+            Err("Timesheet needs either an end or a duration column")?
+        }
+
+        Ok(columns)
+    }
+
+    fn entry(&self, record: &StringRecord) -> DynResult<NewEntry> {
+        let get = |i: usize| record.get(i).unwrap_or_default().trim();
+
+        let start: DateTime = get(self.start).parse()?;
+
+        let end = match (self.end.map(get), self.duration.map(get)) {
+            (Some(end), _) if !end.is_empty() => *end.parse::<DateTime>()?,
+            (_, Some(dur)) if !dur.is_empty() => {
+                let hours: f64 = dur.parse()
+                    .map_err(|e| format!("Error parsing duration:\n{e}"))?;
+                *start + TimeDelta::minutes((hours * 60.0).round() as i64)
+            },
+            _ => Err("Entry has neither an end nor a duration")?,
+        };
+
+        Ok(NewEntry {
+            time: LoggedTime {
+                time_start: *start,
+                time_end: end,
+                time_desc: get(self.description).to_owned(),
+                act_num: None,
+                time_notes: None,
+            },
+            tickets: self.tickets
+                .map(|i| Ticket::find_all(get(i)))
+                .unwrap_or_default(),
+            tags: vec![],
+            billable: None,
+        })
+    }
+}
+
+/// Read time entries from a CSV file laid out like a generated timesheet, labelling each with the
+/// line it came from.
+pub fn read_timesheet(
+    path: &Path,
+    mappings: &[ColumnMapping]
+) -> DynResult<Vec<(String, NewEntry)>> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(path)
+        .map_err(|e| format!("Error opening file {}:\n{e}", path.display()))?;
+
+    let headers = reader.headers()
+        .map_err(|e| format!("Error reading timesheet headers:\n{e}"))?
+        .clone();
+
+    let columns = Columns::new(&headers, mappings)?;
+
+    reader.records()
+        .map(|record| {
+            let record = record.map_err(|e| format!("Error reading timesheet:\n{e}"))?;
+            let line = record.position().map_or(0, |p| p.line());
+
+            let entry = columns.entry(&record)
+                .map_err(|e| format!("Error on line {line} of timesheet:\n{e}"))?;
+
+            Ok((format!("line {line}"), entry))
+        })
+        .collect()
+}
+
+/// Open a CSV export with headers, returning the reader along with a copy of the headers.
+pub(crate) fn open_export(path: &Path) -> DynResult<(Reader<std::fs::File>, StringRecord)> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Error opening file {}:\n{e}", path.display()))?;
+
+    let headers = reader.headers()
+        .map_err(|e| format!("Error reading export headers:\n{e}"))?
+        .clone();
+
+    Ok((reader, headers))
+}
+
+/// Find a column by its header, ignoring case and any byte order mark left on the first header.
+pub(crate) fn header_index(headers: &StringRecord, name: &str) -> Option<usize> {
+    headers.iter()
+        .position(|h| h.trim_start_matches('\u{feff}').trim().eq_ignore_ascii_case(name))
+}
+
+pub(crate) fn require_header(headers: &StringRecord, name: &str) -> DynResult<usize> {
+    Ok(header_index(headers, name).ok_or_else(|| format!("Export has no '{name}' column"))?)
+}
+
+/// Combine separate date and time columns, trying each of the provided formats in turn.
+pub(crate) fn parse_date_time(
+    date: &str,
+    time: &str,
+    date_formats: &[&str],
+    time_formats: &[&str]
+) -> DynResult<NaiveDateTime> {
+    let date = date_formats.iter()
+        .find_map(|f| NaiveDate::parse_from_str(date.trim(), f).ok())
+        .ok_or_else(|| format!("Error parsing date: '{date}'"))?;
+
+    let time = time_formats.iter()
+        .find_map(|f| NaiveTime::parse_from_str(time.trim(), f).ok())
+        .ok_or_else(|| format!("Error parsing time: '{time}'"))?;
+
+    Ok(date.and_time(time))
+}
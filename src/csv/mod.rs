@@ -1 +1,4 @@
-pub mod convert;
\ No newline at end of file
+pub mod convert;
+pub mod edit;
+pub mod import;
+pub mod tracker;
\ No newline at end of file
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use crate::cli::args::TicketMapping;
+use crate::csv::import::{header_index, open_export, parse_date_time, require_header};
+use crate::orm::{insert::{LoggedTime, NewEntry}, ticket::Ticket};
+use crate::util::error::DynResult;
+
+/// The columns and formats used by another time tracker's detailed CSV export.
+pub struct ExportLayout {
+    pub name: &'static str,
+    pub start_date: &'static str,
+    pub start_time: &'static str,
+    pub end_date: &'static str,
+    pub end_time: &'static str,
+    pub description: &'static str,
+    pub task: &'static str,
+    pub project: &'static str,
+    pub tags: &'static str,
+    pub billable: &'static str,
+    pub date_formats: &'static [&'static str],
+    pub time_formats: &'static [&'static str],
+}
+
+pub const TOGGL: ExportLayout = ExportLayout {
+    name: "Toggl",
+    start_date: "Start date",
+    start_time: "Start time",
+    end_date: "End date",
+    end_time: "End time",
+    description: "Description",
+    task: "Task",
+    project: "Project",
+    tags: "Tags",
+    billable: "Billable",
+    date_formats: &["%Y-%m-%d"],
+    time_formats: &["%H:%M:%S", "%H:%M"],
+};
+
+const CLOCKIFY_TIME_FORMATS: &[&str] = &["%I:%M:%S %p", "%I:%M %p", "%H:%M:%S", "%H:%M"];
+
+pub const CLOCKIFY: ExportLayout = ExportLayout {
+    name: "Clockify",
+    start_date: "Start Date",
+    start_time: "Start Time",
+    end_date: "End Date",
+    end_time: "End Time",
+    description: "Description",
+    task: "Task",
+    project: "Project",
+    tags: "Tags",
+    billable: "Billable",
+    date_formats: &["%m/%d/%Y", "%Y-%m-%d"],
+    time_formats: CLOCKIFY_TIME_FORMATS,
+};
+
+/// Clockify formats dates according to the workspace settings, so this is for workspaces that put
+/// the day first.
+pub const CLOCKIFY_DAY_FIRST: ExportLayout = ExportLayout {
+    date_formats: &["%d/%m/%Y", "%d.%m.%Y", "%Y-%m-%d"],
+    ..CLOCKIFY
+};
+
+/// The tickets mapped to any of an entry's labels, such as its project or tags. Names are compared
+/// case insensitively.
+pub fn mapped_tickets<'a>(
+    mappings: &[TicketMapping],
+    labels: impl IntoIterator<Item = &'a str>
+) -> Vec<Ticket> {
+    labels.into_iter()
+        .flat_map(|label| mappings.iter().filter(|m| m.name.eq_ignore_ascii_case(label.trim())))
+        .map(|m| m.ticket.clone())
+        .collect()
+}
+
+/// Read time entries from a detailed CSV export. Tickets are taken from anywhere they're mentioned
+/// in the tags, task, project or description, because time can only be linked to a project through
+/// a ticket. Projects, tasks and tags without a ticket in them can be mapped onto one instead.
+///
+/// Free text can contain words that only look like tickets, so the tickets found in it are passed
+/// through `known`, which keeps those of existing projects. Mapped tickets are always kept.
+pub fn read_export(
+    path: &Path,
+    layout: &ExportLayout,
+    mappings: &[TicketMapping],
+    mut known: impl FnMut(Vec<Ticket>) -> DynResult<Vec<Ticket>>
+) -> DynResult<Vec<(String, NewEntry)>> {
+    let (mut reader, headers) = open_export(path)?;
+
+    let start_date = require_header(&headers, layout.start_date)?;
+    let start_time = require_header(&headers, layout.start_time)?;
+    let end_date = require_header(&headers, layout.end_date)?;
+    let end_time = require_header(&headers, layout.end_time)?;
+    let description = require_header(&headers, layout.description)?;
+    let others: Vec<usize> = [layout.task, layout.project, layout.tags].into_iter()
+        .flat_map(|name| header_index(&headers, name))
+        .collect();
+    let labels: Vec<usize> = [layout.task, layout.project].into_iter()
+        .flat_map(|name| header_index(&headers, name))
+        .collect();
+    let tags = header_index(&headers, layout.tags);
+    let billable = header_index(&headers, layout.billable);
+
+    reader.records()
+        .map(|record| {
+            let record = record.map_err(|e| format!("Error reading {} export:\n{e}", layout.name))?;
+            let line = record.position().map_or(0, |p| p.line());
+            let get = |i: usize| record.get(i).unwrap_or_default().trim();
+
+            let parse = |date, time| parse_date_time(
+                get(date),
+                get(time),
+                layout.date_formats,
+                layout.time_formats
+            ).map_err(|e| format!("Error on line {line} of {} export:\n{e}", layout.name));
+
+            let mut tickets = Ticket::find_all(get(description));
+            tickets.extend(others.iter().flat_map(|i| Ticket::find_all(get(*i))));
+            let mut tickets = known(tickets)?;
+            tickets.extend(mapped_tickets(mappings, labels.iter()
+                .map(|i| get(*i))
+                .chain(tags.map(get).unwrap_or_default().split(','))
+            ));
+            tickets.sort();
+            tickets.dedup();
+
+            // Fall back to the task or project when an entry has no description of its own.
+            let time_desc = [description].into_iter()
+                .chain(others.iter().copied())
+                .map(get)
+                .find(|s| !s.is_empty())
+                .unwrap_or_default()
+                .to_owned();
+
+            Ok((
+                format!("line {line}"),
+                NewEntry {
+                    time: LoggedTime {
+                        time_start: parse(start_date, start_time)?,
+                        time_end: parse(end_date, end_time)?,
+                        time_desc,
+                        act_num: None,
+                        time_notes: None,
+                    },
+                    tickets,
+                    tags: vec![],
+                    // Entries without a recognised value follow the default of their projects.
+                    billable: billable.and_then(|i| match get(i).to_lowercase().as_str() {
+                        "yes" | "true" => Some(true),
+                        "no" | "false" => Some(false),
+                        _ => None,
+                    }),
+                }
+            ))
+        })
+        .collect()
+}
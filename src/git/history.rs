@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+
+use crate::git::git;
+use crate::orm::{insert::{LoggedTime, NewEntry}, ticket::Ticket};
+use crate::util::error::DynResult;
+
+/// A commit from `git log`, with its author time in local time.
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub hash: String,
+    pub time: NaiveDateTime,
+    /// The ref the commit was reached from, which is usually the branch it was made on.
+    pub branch: String,
+    pub subject: String,
+}
+
+impl Commit {
+    /// Tickets mentioned in the commit subject or its branch name.
+    pub fn tickets(&self) -> Vec<Ticket> {
+        Ticket::find_all(&self.subject).into_iter()
+            .chain(Ticket::find_all(&self.branch))
+            .collect()
+    }
+}
+
+/// Commits made close enough together to count as a single stretch of work.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub commits: Vec<Commit>,
+}
+
+impl Session {
+    /// Every ticket mentioned by the commits in the session, in order of appearance.
+    pub fn tickets(&self) -> Vec<Ticket> {
+        let mut tickets = vec![];
+
+        for ticket in self.commits.iter().flat_map(Commit::tickets) {
+            if !tickets.contains(&ticket) {
+                tickets.push(ticket);
+            }
+        }
+
+        tickets
+    }
+
+    pub fn description(&self) -> String {
+        self.commits.iter()
+            .map(|c| c.subject.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    pub fn into_entry(self, tickets: Vec<Ticket>) -> NewEntry {
+        NewEntry {
+            time: LoggedTime {
+                time_start: self.start,
+                time_end: self.end,
+                time_desc: self.description(),
+                act_num: None,
+                time_notes: None,
+            },
+            tickets,
+            tags: vec![],
+            billable: None,
+        }
+    }
+}
+
+/// The email git is configured to commit with in a repository.
+pub fn configured_author(repo: &Path) -> DynResult<String> {
+    let email = git(repo, &["config", "user.email"])
+        .map_err(|_| "No author given and git user.email isn't set")?;
+
+    Ok(email.trim().to_owned())
+}
+
+/// Read the commits on any branch of a repository that were authored by `author` on `date`,
+/// oldest first.
+pub fn read_commits(repo: &Path, author: &str, date: NaiveDate) -> DynResult<Vec<Commit>> {
+    // git filters on commit time, which can differ from author time after a rebase, so a wider
+    // range is requested and then narrowed down by author time.
+    let since = format!("--since={} 00:00", date - TimeDelta::days(1));
+    let until = format!("--until={} 00:00", date + TimeDelta::days(2));
+    let author = format!("--author={author}");
+
+    let log = git(repo, &[
+        "log",
+        "--all",
+        "--source",
+        &author,
+        &since,
+        &until,
+        "--date=format-local:%Y-%m-%d %H:%M:%S",
+        "--format=%h%x1f%ad%x1f%S%x1f%s",
+    ])?;
+
+    let mut commits = vec![];
+
+    for line in log.lines() {
+        let [hash, time, branch, subject] = line.splitn(4, '\x1f').collect::<Vec<_>>()[..] else {
+            Err(format!("Unexpected line in git log output: '{line}'"))?
+        };
+
+        let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+            .map_err(|e| format!("Error parsing commit time '{time}':\n{e}"))?;
+
+        if time.date() == date {
+            commits.push(Commit {
+                hash: hash.to_owned(),
+                time,
+                branch: branch.trim_start_matches("refs/heads/").to_owned(),
+                subject: subject.to_owned(),
+            });
+        }
+    }
+
+    commits.sort_by_key(|c| c.time);
+
+    Ok(commits)
+}
+
+/// Group commits into sessions, starting a new one whenever there is more than `gap` between
+/// commits. A commit marks the end of some work, so each session starts `lead` before its first
+/// commit.
+pub fn sessions(commits: Vec<Commit>, gap: TimeDelta, lead: TimeDelta) -> Vec<Session> {
+    let mut sessions: Vec<Session> = vec![];
+
+    for commit in commits {
+        // Entries are logged to the minute.
+        let time = commit.time.with_second(0).unwrap_or(commit.time);
+
+        match sessions.last_mut() {
+            Some(session) if time - session.end <= gap => {
+                session.end = time;
+                session.commits.push(commit);
+            },
+            _ => sessions.push(Session {
+                start: time - lead,
+                end: time,
+                commits: vec![commit],
+            }),
+        }
+    }
+
+    sessions
+}
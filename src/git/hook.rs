@@ -0,0 +1,63 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use crate::git::git;
+use crate::util::error::DynResult;
+
+/// Marks hooks written by time-tracker, which can be replaced without asking.
+const MARKER: &str = "# Installed by time-tracker";
+
+/// Quote a value for a POSIX shell.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// A post-checkout hook that passes the branch that was checked out to `command`. File checkouts
+/// and detached heads don't change the branch, so they're ignored.
+pub fn post_checkout_script(command: &[String]) -> String {
+    let command = command.iter()
+        .map(|c| quote(c))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "#!/bin/sh\n\
+        {MARKER}, to attach tickets in branch names to the running timer.\n\
+        [ \"$3\" = 1 ] || exit 0\n\
+        branch=$(git symbolic-ref --short -q HEAD) || exit 0\n\
+        {command} \"$branch\" || true\n"
+    )
+}
+
+/// Write a hook into a repository, returning where it was written. Existing hooks are only
+/// replaced if they were installed by time-tracker, unless `force` is given.
+pub fn install(repo: &Path, name: &str, script: &str, force: bool) -> DynResult<PathBuf> {
+    // Respects core.hooksPath and works from within worktrees.
+    let hooks = PathBuf::from(git(repo, &["rev-parse", "--git-path", "hooks"])?.trim());
+    let hooks = if hooks.is_absolute() { hooks } else { repo.join(hooks) };
+    let path = hooks.join(name);
+
+    if let Ok(existing) = fs::read_to_string(&path)
+        && !existing.contains(MARKER)
+        && !force
+    {
+        Err(format!(
+            "A {name} hook already exists at {} (use --force to replace it)",
+            path.display()
+        ))?
+    }
+
+    fs::create_dir_all(&hooks)
+        .map_err(|e| format!("Error creating directory {}:\n{e}", hooks.display()))?;
+    fs::write(&path, script)
+        .map_err(|e| format!("Error writing hook {}:\n{e}", path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Error making hook {} executable:\n{e}", path.display()))?;
+    }
+
+    Ok(path)
+}
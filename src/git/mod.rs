@@ -0,0 +1,27 @@
+pub mod history;
+pub mod hook;
+
+use std::{path::Path, process::Command};
+
+use crate::util::error::DynResult;
+
+/// Run git in a repository, returning its output.
+pub(crate) fn git(repo: &Path, args: &[&str]) -> DynResult<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Error running git:\n{e}"))?;
+
+    if !output.status.success() {
+        Err(format!(
+            "Error running git in {}:\n{}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))?
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|e| format!("Error reading git output:\n{e}").into())
+}
@@ -0,0 +1,81 @@
+use chrono::{NaiveDateTime, Utc};
+
+use crate::orm::query::TimeWithTickets;
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Escape a text value (RFC 5545 section 3.3.11).
+pub fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Write a content line, folding it so that no line is longer than 75 octets.
+fn write_line(out: &mut String, line: &str) {
+    let mut width = 0;
+
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+
+    out.push_str("\r\n");
+}
+
+fn format_local(time: &NaiveDateTime) -> String {
+    // Times are stored without a timezone, so they are written as floating local times.
+    time.format(DATE_TIME_FORMAT).to_string()
+}
+
+/// Convert time entries into a calendar, with one event per entry. Tickets are listed as
+/// categories and prefix the summary, so that they show up in calendar views.
+pub fn to_calendar(times: &[TimeWithTickets]) -> String {
+    let mut out = String::new();
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+
+    write_line(&mut out, "BEGIN:VCALENDAR");
+    write_line(&mut out, "VERSION:2.0");
+    write_line(&mut out, "PRODID:-//time-tracker//time entries//EN");
+
+    for time in times {
+        let tickets: Vec<String> = time.tickets.iter()
+            .map(|t| t.to_string())
+            .collect();
+
+        let summary = if tickets.is_empty() {
+            time.time_desc.clone()
+        } else {
+            format!("{}: {}", tickets.join(", "), time.time_desc)
+        };
+
+        write_line(&mut out, "BEGIN:VEVENT");
+        write_line(&mut out, &format!("UID:time-{}@time-tracker", time.time_id));
+        write_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        write_line(&mut out, &format!("DTSTART:{}", format_local(&time.time_start)));
+        write_line(&mut out, &format!("DTEND:{}", format_local(&time.time_end)));
+        write_line(&mut out, &format!("SUMMARY:{}", escape(&summary)));
+
+        if !tickets.is_empty() {
+            write_line(&mut out, &format!(
+                "CATEGORIES:{}",
+                tickets.iter()
+                    .map(|t| escape(t))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ));
+        }
+
+        write_line(&mut out, "END:VEVENT");
+    }
+
+    write_line(&mut out, "END:VCALENDAR");
+
+    out
+}
@@ -0,0 +1,249 @@
+use std::{fs, mem, path::Path};
+
+use chrono::{Local, NaiveDateTime, TimeDelta};
+
+use crate::orm::{insert::{LoggedTime, NewEntry}, ticket::Ticket};
+use crate::util::error::DynResult;
+
+/// A timed event read from a calendar. All-day events aren't time entries, so they're skipped.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub uid: Option<String>,
+    pub summary: String,
+    pub categories: Vec<String>,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl Event {
+    /// Tickets mentioned in the summary or categories of the event.
+    pub fn tickets(&self) -> Vec<Ticket> {
+        let mut tickets: Vec<Ticket> = self.categories.iter()
+            .map(String::as_str)
+            .chain([self.summary.as_str()])
+            .flat_map(Ticket::find_all)
+            .collect();
+
+        tickets.sort();
+        tickets.dedup();
+        tickets
+    }
+
+    /// The summary without a leading list of tickets, as written by the calendar export, so that
+    /// exported entries import back with the same description. The list is only taken off when it
+    /// consists of nothing but `known` tickets, so that a summary like `Note: call back` is kept.
+    pub fn description(&self, known: &[Ticket]) -> &str {
+        let exported = |prefix: &str| prefix.split(", ").all(|part| {
+            part.parse::<Ticket>().is_ok_and(|t| t.to_string() == part && known.contains(&t))
+        });
+
+        match self.summary.split_once(": ") {
+            Some((prefix, rest)) if exported(prefix) => rest,
+            _ => &self.summary,
+        }
+    }
+
+    /// Turn the event into an entry for `tickets`, with `known` the tickets whose projects exist.
+    pub fn into_entry(self, tickets: Vec<Ticket>, known: &[Ticket]) -> NewEntry {
+        NewEntry {
+            time: LoggedTime {
+                time_start: self.start,
+                time_end: self.end,
+                time_desc: self.description(known).to_owned(),
+                act_num: None,
+                time_notes: None,
+            },
+            tickets,
+            tags: vec![],
+            billable: None,
+        }
+    }
+}
+
+/// The properties of an event collected so far, while its lines are being read.
+#[derive(Debug, Default)]
+struct PartialEvent {
+    uid: Option<String>,
+    summary: String,
+    categories: Vec<String>,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    duration: Option<TimeDelta>,
+    all_day: bool,
+}
+
+impl PartialEvent {
+    fn finish(self) -> Option<Event> {
+        if self.all_day {
+            return None;
+        }
+
+        let start = self.start?;
+
+        Some(Event {
+            uid: self.uid,
+            summary: self.summary,
+            categories: self.categories,
+            start,
+            end: self.end.or(self.duration.map(|d| start + d))?,
+        })
+    }
+}
+
+/// Undo escaping of a text value (RFC 5545 section 3.3.11).
+pub fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {},
+        }
+    }
+
+    out
+}
+
+/// Split a list value on unescaped commas.
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = vec![];
+    let mut current = String::new();
+    let mut escaped = false;
+
+    for c in value.chars() {
+        match c {
+            ',' if !escaped => items.push(mem::take(&mut current)),
+            _ => current.push(c),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+
+    items.push(current);
+    items.iter()
+        .map(|i| unescape(i.trim()))
+        .filter(|i| !i.is_empty())
+        .collect()
+}
+
+/// Parse a DATE-TIME value. UTC times are converted to local time, while floating times and times
+/// with a TZID are assumed to already be local. Returns `None` for all-day dates.
+fn parse_date_time(params: &str, value: &str) -> DynResult<Option<NaiveDateTime>> {
+    let params = params.to_uppercase();
+
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return Ok(None);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .map_err(|e| format!("Error parsing calendar time '{value}':\n{e}"))?;
+        return Ok(Some(utc.and_utc().with_timezone(&Local).naive_local()));
+    }
+
+    match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Ok(time) => Ok(Some(time)),
+        // A bare date without the VALUE parameter is still an all-day event.
+        Err(_) if value.len() == 8 => Ok(None),
+        Err(e) => Err(format!("Error parsing calendar time '{value}':\n{e}"))?,
+    }
+}
+
+/// Parse a DURATION value such as `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> DynResult<TimeDelta> {
+    let body = value.trim_start_matches('+')
+        .strip_prefix('P')
+        .ok_or_else(|| format!("Error parsing calendar duration '{value}'"))?;
+
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+
+    for c in body.chars() {
+        match c {
+            'T' => {},
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse()
+                    .map_err(|_| format!("Error parsing calendar duration '{value}'"))?;
+                number.clear();
+
+                total += match unit {
+                    'W' => TimeDelta::weeks(n),
+                    'D' => TimeDelta::days(n),
+                    'H' => TimeDelta::hours(n),
+                    'M' => TimeDelta::minutes(n),
+                    'S' => TimeDelta::seconds(n),
+                    _ => Err(format!("Error parsing calendar duration '{value}'"))?,
+                };
+            },
+        }
+    }
+
+    Ok(total)
+}
+
+/// Read every timed event from an iCalendar file. Recurring events are imported as their first
+/// occurrence only.
+pub fn read_calendar(path: &Path) -> DynResult<Vec<Event>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Error reading file {}:\n{e}", path.display()))?;
+
+    // Unfold continuation lines, which start with a space or tab.
+    let mut lines: Vec<String> = vec![];
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    let mut events = vec![];
+    let mut current: Option<PartialEvent> = None;
+    // Components nested in an event, like alarms, have properties of their own that aren't the
+    // event's.
+    let mut nested = 0;
+
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        let name = name.to_uppercase();
+
+        if current.is_some() && name == "BEGIN" {
+            nested += 1;
+            continue;
+        }
+        if nested > 0 {
+            if name == "END" {
+                nested -= 1;
+            }
+            continue;
+        }
+
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some(PartialEvent::default());
+            },
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                events.extend(current.take().and_then(PartialEvent::finish));
+            },
+            ("UID", Some(event)) => event.uid = Some(value.to_owned()),
+            ("SUMMARY", Some(event)) => event.summary = unescape(value),
+            ("CATEGORIES", Some(event)) => event.categories.extend(split_list(value)),
+            ("DTSTART", Some(event)) => match parse_date_time(params, value)? {
+                Some(start) => event.start = Some(start),
+                None => event.all_day = true,
+            },
+            ("DTEND", Some(event)) => event.end = parse_date_time(params, value)?,
+            ("DURATION", Some(event)) => event.duration = Some(parse_duration(value)?),
+            _ => {},
+        }
+    }
+
+    Ok(events)
+}
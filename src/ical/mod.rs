@@ -0,0 +1,2 @@
+pub mod convert;
+pub mod import;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod timewarrior;
\ No newline at end of file
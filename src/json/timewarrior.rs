@@ -0,0 +1,94 @@
+use std::{fs, path::Path};
+
+use chrono::{Local, NaiveDateTime};
+use serde::Deserialize;
+
+use crate::cli::args::TicketMapping;
+use crate::csv::tracker::mapped_tickets;
+use crate::orm::{insert::{LoggedTime, NewEntry}, ticket::Ticket};
+use crate::util::error::DynResult;
+
+/// An interval from `timew export`. Times are in UTC and open intervals have no end.
+#[derive(Debug, Deserialize)]
+struct Interval {
+    id: Option<i64>,
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+fn parse_utc(s: &str) -> DynResult<NaiveDateTime> {
+    let utc = NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ")
+        .map_err(|e| format!("Error parsing Timewarrior time '{s}':\n{e}"))?;
+
+    Ok(utc.and_utc().with_timezone(&Local).naive_local())
+}
+
+/// Read time entries from the output of `timew export`. Tags that are tickets of a project kept by
+/// `known` are linked as tickets, while the annotation (or remaining tags) becomes the description.
+/// Intervals that are still running are skipped. Other tags can be mapped onto a ticket.
+pub fn read_export(
+    path: &Path,
+    mappings: &[TicketMapping],
+    mut known: impl FnMut(Vec<Ticket>) -> DynResult<Vec<Ticket>>
+) -> DynResult<Vec<(String, NewEntry)>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Error reading file {}:\n{e}", path.display()))?;
+
+    let intervals: Vec<Interval> = serde_json::from_str(&contents)
+        .map_err(|e| format!("Error parsing Timewarrior export:\n{e}"))?;
+
+    intervals.into_iter()
+        .enumerate()
+        .filter(|(_, interval)| interval.end.is_some())
+        .map(|(i, interval)| {
+            let source = format!("interval @{}", interval.id.unwrap_or(i as i64 + 1));
+
+            let entry = interval.into_entry(mappings, &mut known)
+                .map_err(|e| format!("Error in {source} of Timewarrior export:\n{e}"))?;
+
+            Ok((source, entry))
+        })
+        .collect()
+}
+
+impl Interval {
+    fn into_entry(
+        self,
+        mappings: &[TicketMapping],
+        known: &mut impl FnMut(Vec<Ticket>) -> DynResult<Vec<Ticket>>
+    ) -> DynResult<NewEntry> {
+        // Tags such as a version `v2-3` look like tickets, so they're only taken as tickets when
+        // their project exists, and are treated like any other tag otherwise.
+        let mut tickets = vec![];
+        let mut tags = vec![];
+
+        for tag in &self.tags {
+            match known(Ticket::find_all(tag))? {
+                found if found.is_empty() => tags.push(tag.as_str()),
+                found => tickets.extend(found),
+            }
+        }
+
+        let mapped = mapped_tickets(mappings, tags.iter().copied());
+
+        let time_desc = self.annotation
+            .filter(|a| !a.trim().is_empty())
+            .unwrap_or_else(|| tags.join(" "));
+
+        Ok(NewEntry {
+            time: LoggedTime {
+                time_start: parse_utc(&self.start)?,
+                time_end: parse_utc(self.end.as_deref().unwrap_or_default())?,
+                time_desc,
+                act_num: None,
+                time_notes: None,
+            },
+            tickets: tickets.into_iter().chain(mapped).collect(),
+            tags: vec![],
+            billable: None,
+        })
+    }
+}
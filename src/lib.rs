@@ -1,5 +1,11 @@
+pub mod api;
 pub mod cli;
 pub mod csv;
+pub mod git;
+pub mod ical;
+pub mod json;
 pub mod orm;
+pub mod service;
+pub mod tui;
 pub mod typst;
 pub mod util;
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::env;
 
 use clap::Parser;
 use diesel::{Connection, RunQueryDsl, SqliteConnection, sql_query};
-use time_tracker::{cli::{args::{Action, CliArgs}, generate, list, log}, util::error::DynError};
+use time_tracker::{cli::{amend, args::{Action, BackupAction, BackupArgs, CliArgs}, backup, budget, doctor, edit, export, generate, history, hook, import, invoice, list, log, project, report, serve, suggest, ticket, timer, tui, undo}, service::audit::{finish_command, start_command}, util::error::DynError};
 
 fn main() {
     let args = CliArgs::parse();
@@ -12,6 +12,19 @@ fn main() {
             .expect("DATABASE_URL must be set when no database argument is provided")
     );
 
+    let keep_backups = args.keep_backups.unwrap_or_else(
+        || env::var("TIME_TRACKER_KEEP_BACKUPS")
+            .map(|keep| keep.parse().expect("TIME_TRACKER_KEEP_BACKUPS must be a number"))
+            .unwrap_or(10)
+    );
+
+    // Restoring replaces the database file, so it can't have a connection open.
+    if let Action::Backup(BackupArgs { action: BackupAction::Restore(restore_args) }) =
+        args.action
+    {
+        return backup::restore(&db_url, restore_args).unwrap_or_else(|e| panic!("{e}"));
+    }
+
     let conn = &mut SqliteConnection::establish(&db_url)
         .unwrap_or_else(|_| panic!("Error connecting to {}", db_url));
 
@@ -20,10 +33,69 @@ fn main() {
         .execute(conn)
         .expect("Unable to enable foreign keys for database session");
 
-    conn.transaction::<(), DynError, _>(|conn| match args.action {
-        Action::Generate(gen_args) => generate::generate(conn, gen_args),
-        Action::Log(log_args) => log::log(conn, log_args),
-        Action::Amend(_) => todo!(),
-        Action::List(list_args) => list::list(conn, list_args),
-    }).unwrap_or_else(|e| panic!("{e}"));
-}
\ No newline at end of file
+    // Backing up can't happen inside a transaction, so it's done before the command starts.
+    if args.action.takes_backup() {
+        backup::auto_backup(conn, &db_url, keep_backups).unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    // Notes are written before the transaction starts, so that the database isn't locked while
+    // the editor is open.
+    let action = match args.action {
+        Action::Amend(amend_args) => {
+            Action::Amend(amend::edit_notes(conn, amend_args).unwrap_or_else(|e| panic!("{e}")))
+        },
+        action => action,
+    };
+
+    match action {
+        Action::Backup(backup_args) => backup::backup(conn, backup_args, &db_url),
+        // The server runs a transaction per request, rather than one for as long as it's up.
+        Action::Serve(serve_args) => serve::serve(conn, serve_args),
+        // Likewise, the interface saves each edit in a transaction of its own.
+        Action::Tui(tui_args) => tui::tui(conn, tui_args),
+        // The database isn't locked while the editor is open, only while the edits are saved.
+        Action::Edit(edit_args) => edit::edit(conn, edit_args, &command_line()),
+        action => conn.transaction::<(), DynError, _>(|conn| {
+            // Changes are recorded against the command, so that they can be undone together.
+            let cmd_id = start_command(conn, &command_line())?;
+
+            match action {
+                Action::Generate(gen_args) => generate::generate(conn, gen_args),
+                Action::Log(log_args) => log::log(conn, log_args),
+                Action::Amend(amend_args) => amend::amend(conn, amend_args),
+                Action::List(list_args) => list::list(conn, list_args),
+                Action::Invoice(invoice_args) => invoice::invoice(conn, invoice_args),
+                Action::Report(report_args) => report::report(conn, report_args),
+                Action::Import(import_args) => import::import(conn, import_args),
+                Action::Export(export_args) => export::export(conn, export_args),
+                Action::Suggest(suggest_args) => suggest::suggest(conn, suggest_args),
+                Action::Timer(timer_args) => timer::timer(conn, timer_args),
+                Action::Hook(hook_args) => hook::hook(conn, hook_args, &db_url),
+                Action::Project(project_args) => project::project(conn, project_args),
+                Action::Ticket(ticket_args) => ticket::ticket(conn, ticket_args),
+                Action::Budget(budget_args) => budget::budget(conn, budget_args),
+                Action::Undo(undo_args) => undo::undo(conn, undo_args),
+                Action::History(history_args) => history::history(conn, history_args),
+                Action::Doctor(doctor_args) => doctor::doctor(conn, doctor_args),
+                Action::Serve(_) | Action::Tui(_) | Action::Edit(_) | Action::Backup(_) => {
+                    unreachable!("Handled outside of a transaction")
+                },
+            }?;
+
+            Ok(finish_command(conn, cmd_id)?)
+        }),
+    }.unwrap_or_else(|e| panic!("{e}"));
+}
+
+/// The arguments the program was run with, quoted where needed so that they can be read back.
+fn command_line() -> String {
+    env::args()
+        .skip(1)
+        .map(|arg| if arg.is_empty() || arg.contains(char::is_whitespace) {
+            format!("'{}'", arg.replace('\'', "'\\''"))
+        } else {
+            arg
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,113 @@
+use std::{fmt::{self, Display, Formatter}, str::FromStr};
+
+use clap::Args;
+
+use crate::orm::ticket::Ticket;
+use crate::util::date::{Date, Month, Week};
+
+// Filters for selecting time entries, independent of any invoice.
+#[derive(Debug, Clone, Default, Args)]
+pub struct TimeFilter {
+    /// Only include time starting on or after this date.
+    #[arg(long, short, value_parser = Date::from_str)]
+    pub from: Option<Date>,
+
+    /// Only include time starting on or before this date.
+    #[arg(long, short, value_parser = Date::from_str)]
+    pub to: Option<Date>,
+
+    /// Only include time starting within this ISO week, e.g. 2026-W07.
+    #[arg(long, short, value_parser = Week::from_str)]
+    pub week: Option<Week>,
+
+    /// Only include time with a ticket belonging to this project.
+    #[arg(long, short)]
+    pub project: Option<String>,
+
+    /// Only include time with this ticket.
+    #[arg(long, value_parser = Ticket::from_str)]
+    pub ticket: Option<Ticket>,
+
+    /// Only include time with a description containing this text.
+    #[arg(long)]
+    pub desc: Option<String>,
+
+    /// Only include time with this tag, written with or without its '#'.
+    #[arg(long, value_parser = parse_tag)]
+    pub tag: Option<String>,
+
+    /// Only include time that isn't assigned to an activity.
+    #[arg(long, short)]
+    pub unbilled: bool,
+
+    /// Only include billable time.
+    #[arg(long, conflicts_with = "non_billable")]
+    pub billable: bool,
+
+    /// Only include non-billable time.
+    #[arg(long)]
+    pub non_billable: bool,
+}
+
+impl TimeFilter {
+    pub fn is_empty(&self) -> bool {
+        self.from.is_none()
+            && self.to.is_none()
+            && self.week.is_none()
+            && self.project.is_none()
+            && self.ticket.is_none()
+            && self.desc.is_none()
+            && self.tag.is_none()
+            && !self.unbilled
+            && !self.billable
+            && !self.non_billable
+    }
+}
+
+fn parse_tag(s: &str) -> Result<String, String> {
+    Ok(s.strip_prefix('#').unwrap_or(s).to_owned())
+}
+
+
+/// Picks out an invoice by its number, its month or the number it was issued with.
+#[derive(Debug, Clone)]
+pub enum DocIdentifier {
+    Num(i32),
+    Month(Month),
+    /// The formatted number of an invoice issued under a numbering scheme.
+    Ref(String),
+    /// Anything else, which is only taken as a formatted number if it fits a numbering scheme.
+    Other(String),
+}
+
+impl Default for DocIdentifier {
+    fn default() -> Self {
+        DocIdentifier::Month(Month::default())
+    }
+}
+
+impl Display for DocIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DocIdentifier::Num(n) => write!(f, "{n}"),
+            DocIdentifier::Month(m) => write!(f, "{m}"),
+            DocIdentifier::Ref(r) | DocIdentifier::Other(r) => write!(f, "{r}"),
+        }
+    }
+}
+
+impl FromStr for DocIdentifier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(num) = s.parse() {
+            Ok(DocIdentifier::Num(num))
+        } else if let Ok(date) = s.parse() {
+            Ok(DocIdentifier::Month(date))
+        } else if !s.is_empty() {
+            Ok(DocIdentifier::Other(s.to_owned()))
+        } else {
+            Err("Value doesn't match format of numeric id, month or invoice number".into())
+        }
+    }
+}
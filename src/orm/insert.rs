@@ -1,7 +1,7 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
 
-use crate::orm::schema;
+use crate::orm::{schema, ticket::Ticket};
 
 #[derive(Debug, Insertable)]
 #[diesel(table_name = schema::time)]
@@ -10,4 +10,81 @@ pub struct LoggedTime {
     pub time_end: NaiveDateTime,
     pub time_desc: String,
     pub act_num: Option<i32>,
-}
\ No newline at end of file
+    /// Free-form context that doesn't fit in the description, possibly over several lines.
+    pub time_notes: Option<String>,
+}
+
+/// A time entry that hasn't been inserted yet, along with the tickets and tags it should be
+/// linked to.
+#[derive(Debug)]
+pub struct NewEntry {
+    pub time: LoggedTime,
+    pub tickets: Vec<Ticket>,
+    /// Tag names, without the leading '#'.
+    pub tags: Vec<String>,
+    /// Whether the entry is billable, or `None` to use the default of its projects.
+    pub billable: Option<bool>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::timer)]
+pub struct NewTimer {
+    pub timer_start: NaiveDateTime,
+    pub timer_desc: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::invoice)]
+pub struct NewInvoice {
+    pub inv_num: i32,
+    pub inv_month: NaiveDate,
+    pub recip_id: String,
+    pub inv_created: Option<NaiveDate>,
+    /// The period billed for, left unset when it's the whole month.
+    pub inv_start: Option<NaiveDate>,
+    pub inv_end: Option<NaiveDate>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::invoice_activity)]
+pub struct NewActivity {
+    pub inv_num: i32,
+    pub act_desc: String,
+    pub act_uprice: f64,
+}
+
+/// Changes to an invoiced activity, where `None` leaves a field as it is.
+#[derive(Debug, Default, PartialEq, AsChangeset)]
+#[diesel(table_name = schema::invoice_activity)]
+pub struct ActivityChanges {
+    pub act_desc: Option<String>,
+    pub act_uprice: Option<f64>,
+}
+
+/// Changes to a ticket's details, where `None` leaves a field as it is.
+#[derive(Debug, Default, PartialEq, AsChangeset)]
+#[diesel(table_name = schema::ticket)]
+pub struct TicketChanges {
+    pub tick_title: Option<String>,
+    pub tick_url: Option<String>,
+    pub tick_status: Option<String>,
+    pub tick_estimate: Option<f64>,
+}
+
+/// Changes to a project, where `None` leaves a field as it is.
+#[derive(Debug, Default, PartialEq, AsChangeset)]
+#[diesel(table_name = schema::project)]
+pub struct ProjectChanges {
+    pub proj_name: Option<String>,
+    pub proj_billable: Option<bool>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::budget)]
+pub struct NewBudget {
+    pub proj_key: String,
+    pub tick_num: Option<i32>,
+    pub bud_hours: Option<f64>,
+    pub bud_money: Option<f64>,
+    pub bud_rate: Option<f64>,
+}
@@ -1,3 +1,4 @@
+pub mod filter;
 pub mod insert;
 pub mod model;
 pub mod query;
@@ -1,19 +1,46 @@
 use derive_more::Debug;
 use diesel::prelude::*;
 use diesel::sqlite::Sqlite;
+use serde::Serialize;
 
-use crate::util::date::{Date, DateTime, Month};
+use crate::util::date::{Date, DateTime, Month, Period};
 use crate::orm::ticket::Ticket;
 
 use super::schema;
 
-#[derive(Debug, HasQuery, Identifiable)]
+#[derive(Debug, HasQuery, Identifiable, Insertable, Serialize)]
 #[diesel(table_name = schema::project)]
 #[diesel(primary_key(proj_key))]
 #[diesel(check_for_backend(Sqlite))]
 pub struct Project {
     pub proj_key: String,
     pub proj_name: String,
+    /// Whether time on the project is billable unless logged otherwise.
+    pub proj_billable: bool,
+}
+
+/// Details of a ticket. Time can reference a ticket without any, so this isn't the ticket itself.
+#[derive(Debug, HasQuery, Identifiable, Associations, Insertable, Serialize)]
+#[diesel(belongs_to(Project, foreign_key = proj_key))]
+#[diesel(table_name = schema::ticket)]
+#[diesel(primary_key(proj_key, tick_num))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct TicketDetails {
+    pub proj_key: String,
+    pub tick_num: i32,
+    pub tick_title: String,
+    pub tick_url: Option<String>,
+    pub tick_status: String,
+    pub tick_estimate: Option<f64>,
+}
+
+impl TicketDetails {
+    pub fn ticket(&self) -> Ticket {
+        Ticket {
+            proj_key: self.proj_key.clone(),
+            tick_num: self.tick_num,
+        }
+    }
 }
 
 #[derive(Debug, HasQuery, Identifiable, Associations, Insertable)]
@@ -51,9 +78,72 @@ pub struct Time {
     pub time_desc: String,
     pub time_dur: Option<f64>,
     pub act_num: Option<i32>,
+    pub time_billable: bool,
+    pub time_notes: Option<String>,
+}
+
+#[derive(Debug, HasQuery, Identifiable, Insertable)]
+#[diesel(table_name = schema::tag)]
+#[diesel(primary_key(tag_name))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct Tag {
+    pub tag_name: String,
+}
+
+#[derive(Debug, HasQuery, Identifiable, Associations, Insertable)]
+#[diesel(belongs_to(Time, foreign_key = time_id))]
+#[diesel(table_name = schema::time_tag)]
+#[diesel(primary_key(tag_name, time_id))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct TimeTag {
+    pub tag_name: String,
+    pub time_id: i32,
+}
+
+/// The running timer, of which there is at most one.
+#[derive(Debug, HasQuery)]
+#[diesel(table_name = schema::timer)]
+#[diesel(check_for_backend(Sqlite))]
+pub struct Timer {
+    pub timer_start: DateTime,
+    pub timer_desc: String,
+}
+
+#[derive(Debug, HasQuery, Identifiable, Associations, Insertable)]
+#[diesel(belongs_to(Project, foreign_key = proj_key))]
+#[diesel(table_name = schema::timer_ticket)]
+#[diesel(primary_key(proj_key, tick_num))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct TimerTicket {
+    pub proj_key: String,
+    pub tick_num: i32,
+}
+
+impl From<Ticket> for TimerTicket {
+    fn from(ticket: Ticket) -> Self {
+        TimerTicket {
+            proj_key: ticket.proj_key,
+            tick_num: ticket.tick_num,
+        }
+    }
 }
 
-#[derive(Debug, HasQuery, Identifiable)]
+/// An hour and/or money budget for a project, or for one of its tickets.
+#[derive(Debug, HasQuery, Identifiable, Associations, Serialize)]
+#[diesel(belongs_to(Project, foreign_key = proj_key))]
+#[diesel(table_name = schema::budget)]
+#[diesel(primary_key(bud_id))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct Budget {
+    pub bud_id: i32,
+    pub proj_key: String,
+    pub tick_num: Option<i32>,
+    pub bud_hours: Option<f64>,
+    pub bud_money: Option<f64>,
+    pub bud_rate: Option<f64>,
+}
+
+#[derive(Debug, HasQuery, Identifiable, Insertable, Serialize)]
 #[diesel(table_name = schema::recipient)]
 #[diesel(primary_key(recip_id))]
 #[diesel(check_for_backend(Sqlite))]
@@ -63,6 +153,17 @@ pub struct Recipient {
     pub recip_addr: String,
 }
 
+/// How invoice numbers are formatted, for one recipient or for all without a scheme of their own.
+#[derive(Debug, HasQuery, Identifiable, Serialize)]
+#[diesel(table_name = schema::scheme)]
+#[diesel(primary_key(sch_id))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct Scheme {
+    pub sch_id: i32,
+    pub recip_id: Option<String>,
+    pub sch_format: String,
+}
+
 #[derive(Debug, HasQuery, Identifiable, Associations)]
 #[diesel(belongs_to(Recipient, foreign_key = recip_id))]
 #[diesel(table_name = schema::invoice)]
@@ -73,6 +174,30 @@ pub struct Invoice {
     pub inv_month: Month,
     pub inv_created: Option<Date>,
     pub recip_id: String,
+    pub inv_start: Option<Date>,
+    pub inv_end: Option<Date>,
+    pub inv_ref: Option<String>,
+}
+
+impl Invoice {
+    /// The number the invoice was issued with, its formatted reference if a numbering scheme
+    /// applied.
+    pub fn number(&self) -> String {
+        self.inv_ref.clone().unwrap_or_else(|| self.inv_num.to_string())
+    }
+
+    /// The days billed for, which is the invoice's month unless it has a period of its own. A
+    /// period with one end missing or ending before it starts is an error, rather than the month.
+    pub fn period(&self) -> Result<Period, String> {
+        match (&self.inv_start, &self.inv_end) {
+            (Some(start), Some(end)) => Period::new(**start, **end).map_err(|_| format!(
+                "Invoice {} has a period ending on {end} before it starts on {start}",
+                self.number()
+            )),
+            (None, None) => Ok(Period::from(self.inv_month.clone())),
+            _ => Err(format!("Invoice {} has only one end of its period", self.number())),
+        }
+    }
 }
 
 #[derive(Debug, HasQuery, Identifiable, Associations)]
@@ -85,4 +210,32 @@ pub struct InvoiceActivity {
     pub inv_num: i32,
     pub act_desc: String,
     pub act_uprice: f64,
-}
\ No newline at end of file
+}
+
+/// A run of the CLI or an API request that changed something.
+#[derive(Debug, HasQuery, Identifiable, Serialize)]
+#[diesel(table_name = schema::command)]
+#[diesel(primary_key(cmd_id))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct Command {
+    pub cmd_id: i32,
+    pub cmd_time: DateTime,
+    pub cmd_line: String,
+    /// The command this one undid, if it was an undo.
+    pub cmd_undoes: Option<i32>,
+}
+
+/// A change to a row, as JSON objects of the row before and after.
+#[derive(Debug, HasQuery, Identifiable, Associations, Serialize)]
+#[diesel(belongs_to(Command, foreign_key = cmd_id))]
+#[diesel(table_name = schema::audit)]
+#[diesel(primary_key(aud_id))]
+#[diesel(check_for_backend(Sqlite))]
+pub struct Audit {
+    pub aud_id: i32,
+    pub cmd_id: Option<i32>,
+    pub aud_table: String,
+    pub aud_action: String,
+    pub aud_old: Option<String>,
+    pub aud_new: Option<String>,
+}
@@ -1,12 +1,17 @@
 use std::collections::BTreeSet;
 
+use diesel::dsl::{AsSelect, IntoBoxed, Select};
 use diesel::prelude::*;
 use diesel::query_dsl::methods::LoadQuery;
+use diesel::sqlite::Sqlite;
+use serde::Serialize;
 
-use crate::{cli::args::DocIdentifier, orm::{model::{Invoice, InvoiceActivity, Recipient, TicketTime, Time}, ticket::Ticket}, util::{date::{Date, DateTime, Month}, error::DynResult}};
+use crate::{orm::{filter::{DocIdentifier, TimeFilter}, model::{Invoice, InvoiceActivity, Recipient, TicketDetails, TicketTime, Time, TimeTag}, ticket::Ticket}, util::date::{Date, DateTime, Month, Period}};
+use crate::service::error::{ServiceError, ServiceResult};
+use crate::service::numbering::check_reference;
 use super::schema;
 
-#[derive(Debug, Identifiable, Associations)]
+#[derive(Debug, Identifiable, Associations, Serialize)]
 #[diesel(belongs_to(InvoiceActivity, foreign_key = act_num))]
 #[diesel(table_name = schema::time)]
 #[diesel(primary_key(time_id))]
@@ -18,11 +23,14 @@ pub struct TimeWithTickets {
     pub time_desc: String,
     pub time_dur: Option<f64>,
     pub act_num: Option<i32>,
+    pub time_billable: bool,
+    pub time_notes: Option<String>,
     pub tickets: Vec<Ticket>,
+    pub tags: Vec<String>,
 }
 
-impl From<(Vec<TicketTime>, Time)> for TimeWithTickets {
-    fn from((tickets, time): (Vec<TicketTime>, Time)) -> Self {
+impl From<((Vec<TicketTime>, Vec<TimeTag>), Time)> for TimeWithTickets {
+    fn from(((tickets, tags), time): ((Vec<TicketTime>, Vec<TimeTag>), Time)) -> Self {
         TimeWithTickets {
             time_id: time.time_id,
             time_start: time.time_start,
@@ -30,9 +38,14 @@ impl From<(Vec<TicketTime>, Time)> for TimeWithTickets {
             time_desc: time.time_desc,
             time_dur: time.time_dur,
             act_num: time.act_num,
+            time_billable: time.time_billable,
+            time_notes: time.time_notes,
             tickets: tickets.into_iter()
                 .map(Ticket::from)
                 .collect(),
+            tags: tags.into_iter()
+                .map(|t| t.tag_name)
+                .collect(),
         }
     }
 }
@@ -49,15 +62,103 @@ impl TimeWithTickets {
         let all_tickets: Vec<TicketTime> = TicketTime::belonging_to(&all_times)
             .load(conn)?;
 
+        let all_tags: Vec<TimeTag> = TimeTag::belonging_to(&all_times)
+            .order(schema::time_tag::tag_name)
+            .load(conn)?;
+
         Ok(all_tickets.grouped_by(&all_times)
             .into_iter()
+            .zip(all_tags.grouped_by(&all_times))
             .zip(all_times)
             .map(TimeWithTickets::from)
             .collect())
     }
 }
 
-#[derive(Debug, Identifiable, Associations)]
+pub type BoxedTimeQuery<'a> = IntoBoxed<
+    'a,
+    Select<<Time as HasQuery<Sqlite>>::BaseQuery, AsSelect<Time, Sqlite>>,
+    Sqlite
+>;
+
+impl TimeFilter {
+    pub fn apply<'a>(self, mut query: BoxedTimeQuery<'a>) -> BoxedTimeQuery<'a> {
+        use crate::orm::schema::{ticket_time, time, time_tag};
+
+        if let Some(from) = self.from {
+            query = query.filter(time::time_start.ge(from.start()));
+        }
+        if let Some(to) = self.to {
+            query = query.filter(time::time_start.lt(to.end()));
+        }
+        if let Some(week) = self.week {
+            query = query.filter(time::time_start.ge(week.start()))
+                .filter(time::time_start.lt(week.end()));
+        }
+        if let Some(proj_key) = self.project {
+            query = query.filter(time::time_id.eq_any(
+                ticket_time::table
+                    .filter(ticket_time::proj_key.eq(proj_key))
+                    .select(ticket_time::time_id)
+            ));
+        }
+        if let Some(ticket) = self.ticket {
+            query = query.filter(time::time_id.eq_any(
+                ticket_time::table
+                    .filter(ticket_time::proj_key.eq(ticket.proj_key))
+                    .filter(ticket_time::tick_num.eq(ticket.tick_num))
+                    .select(ticket_time::time_id)
+            ));
+        }
+        if let Some(desc) = self.desc {
+            // Wildcards in the description itself are matched literally.
+            let desc = desc.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            query = query.filter(time::time_desc.like(format!("%{desc}%")).escape('\\'));
+        }
+        if let Some(tag) = self.tag {
+            query = query.filter(time::time_id.eq_any(
+                time_tag::table
+                    .filter(time_tag::tag_name.eq(tag))
+                    .select(time_tag::time_id)
+            ));
+        }
+        if self.unbilled {
+            query = query.filter(time::act_num.is_null());
+        }
+        if self.billable {
+            query = query.filter(time::time_billable.eq(true));
+        }
+        if self.non_billable {
+            query = query.filter(time::time_billable.eq(false));
+        }
+
+        query
+    }
+
+    /// Select all times matching the filter, in chronological order.
+    pub fn query<'a>(self) -> BoxedTimeQuery<'a> {
+        use crate::orm::schema::time;
+
+        self.apply(
+            Time::query()
+                .order(time::time_start)
+                .into_boxed()
+        )
+    }
+}
+
+/// Restrict a time query to entries assigned to the activities of an invoice.
+pub fn in_invoice(query: BoxedTimeQuery<'_>, inv_num: i32) -> BoxedTimeQuery<'_> {
+    use crate::orm::schema::{invoice_activity, time};
+
+    query.filter(time::act_num.eq_any(
+        invoice_activity::table
+            .filter(invoice_activity::inv_num.eq(inv_num))
+            .select(invoice_activity::act_num.nullable())
+    ))
+}
+
+#[derive(Debug, Identifiable, Associations, Serialize)]
 #[diesel(belongs_to(Invoice, foreign_key = inv_num))]
 #[diesel(table_name = schema::invoice)]
 #[diesel(primary_key(act_num))]
@@ -68,19 +169,34 @@ pub struct ActivityWithTickets {
     pub act_desc: String,
     pub act_uprice: f64,
     pub act_dur: f64,
+    pub act_price: f64,
     pub tickets: BTreeSet<Ticket>,
 }
 
 impl From<(Vec<TimeWithTickets>, InvoiceActivity)> for ActivityWithTickets {
     fn from((time_with_tickets, activity): (Vec<TimeWithTickets>, InvoiceActivity)) -> Self {
+        // Non-billable time can't be logged against an activity, but it's never charged for in
+        // case it was made non-billable afterwards.
+        let time_with_tickets: Vec<TimeWithTickets> = time_with_tickets.into_iter()
+            .filter(|t| t.time_billable)
+            .collect();
+
+        let act_dur = time_with_tickets.iter()
+            .flat_map(|t| t.time_dur)
+            .sum();
+
         ActivityWithTickets {
             act_num: activity.act_num,
             inv_num: activity.inv_num,
             act_desc: activity.act_desc,
             act_uprice: activity.act_uprice,
-            act_dur: time_with_tickets.iter()
-                .flat_map(|t| t.time_dur)
-                .sum(),
+            act_dur,
+            // Matches the template, where activities without time are charged as a flat fee.
+            act_price: if act_dur == 0.0 {
+                activity.act_uprice
+            } else {
+                (activity.act_uprice * act_dur * 100.0).round() / 100.0
+            },
             tickets: time_with_tickets.into_iter()
                 .flat_map(|t| t.tickets)
                 .collect()
@@ -110,17 +226,23 @@ impl ActivityWithTickets {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct InvoiceWithActivities {
     pub inv_num: i32,
+    pub inv_ref: Option<String>,
     pub inv_month: Month,
+    pub inv_period: Period,
     pub inv_created: Option<Date>,
+    pub inv_dur: f64,
+    pub inv_total: f64,
     pub recipient: Recipient,
     pub activities: Vec<ActivityWithTickets>,
 }
 
-impl From<(Vec<ActivityWithTickets>, Invoice, Recipient)> for InvoiceWithActivities {
-    fn from((
+impl TryFrom<(Vec<ActivityWithTickets>, Invoice, Recipient)> for InvoiceWithActivities {
+    type Error = String;
+
+    fn try_from((
         mut activities,
         invoice,
         recipient
@@ -128,19 +250,29 @@ impl From<(Vec<ActivityWithTickets>, Invoice, Recipient)> for InvoiceWithActivit
         Vec<ActivityWithTickets>,
         Invoice,
         Recipient
-    )) -> Self {
+    )) -> Result<Self, Self::Error> {
         activities.sort_by_key(|a| a.act_num);
-        InvoiceWithActivities {
+        Ok(InvoiceWithActivities {
             inv_num: invoice.inv_num,
+            inv_ref: invoice.inv_ref.clone(),
+            inv_period: invoice.period()?,
             inv_month: invoice.inv_month,
             inv_created: invoice.inv_created,
+            inv_dur: activities.iter().map(|a| a.act_dur).sum(),
+            inv_total: activities.iter().map(|a| a.act_price).sum(),
             recipient,
             activities,
-        }
+        })
     }
 }
 
 impl InvoiceWithActivities {
+    /// The number the invoice was issued with, its formatted reference if a numbering scheme
+    /// applied.
+    pub fn number(&self) -> String {
+        self.inv_ref.clone().unwrap_or_else(|| self.inv_num.to_string())
+    }
+
     pub fn from_query<'q, Q>(
         query: Q,
         conn: &mut SqliteConnection
@@ -156,22 +288,27 @@ impl InvoiceWithActivities {
             conn
         )?;
 
-        Ok(activities_with_tickets.grouped_by(&all_invoices)
+        // A damaged period fails the query, rather than being read as some other period.
+        activities_with_tickets.grouped_by(&all_invoices)
             .into_iter()
             .zip(all_invoices)
             .zip(recipients)
-            .map(|((a, b), c)| (a, b, c))
-            .map(InvoiceWithActivities::from)
-            .collect())
+            .map(|((a, b), c)| InvoiceWithActivities::try_from((a, b, c))
+                .map_err(|e| diesel::result::Error::DeserializationError(e.into())))
+            .collect()
     }
 
     pub fn select_by_identifier(
         ident: DocIdentifier,
         conn: &mut SqliteConnection
-    ) -> DynResult<InvoiceWithActivities> {
+    ) -> ServiceResult<InvoiceWithActivities> {
         use crate::orm::schema::{invoice, recipient};
 
-        let invoices = match ident {
+        if let DocIdentifier::Other(value) = &ident {
+            check_reference(conn, value)?;
+        }
+
+        let invoices = match ident.clone() {
             DocIdentifier::Num(n) => InvoiceWithActivities::from_query(
                 invoice::table
                     .inner_join(recipient::table)
@@ -186,12 +323,16 @@ impl InvoiceWithActivities {
                     .select((Invoice::as_select(), Recipient::as_select())),
                 conn
             ),
-        }.map_err(|e| format!("Error retrieving invoice from database:\n{e}"))?;
-
-        let [invoice] = <[InvoiceWithActivities; 1]>::try_from(invoices)
-            .map_err(|_| "Identifier failed to uniquely identify an invoice")?;
+            DocIdentifier::Ref(r) | DocIdentifier::Other(r) => InvoiceWithActivities::from_query(
+                invoice::table
+                    .inner_join(recipient::table)
+                    .filter(invoice::inv_ref.eq(r))
+                    .select((Invoice::as_select(), Recipient::as_select())),
+                conn
+            ),
+        }.map_err(ServiceError::database("Error retrieving invoice from database"))?;
 
-        Ok(invoice)
+        unique(ident, invoices)
     }
 }
 
@@ -199,21 +340,45 @@ impl Invoice {
     pub fn select_by_identifier(
         ident: DocIdentifier,
         conn: &mut SqliteConnection
-    ) -> DynResult<Invoice> {
+    ) -> ServiceResult<Invoice> {
         use crate::orm::schema::{invoice};
 
-        let invoices = match ident {
+        if let DocIdentifier::Other(value) = &ident {
+            check_reference(conn, value)?;
+        }
+
+        let invoices = match ident.clone() {
             DocIdentifier::Num(n) => Invoice::query()
                 .filter(invoice::inv_num.eq(n))
                 .load(conn),
             DocIdentifier::Month(m) => Invoice::query()
                 .filter(invoice::inv_month.eq(m))
                 .load(conn),
-        }.map_err(|e| format!("Error retrieving invoice from database:\n{e}"))?;
+            DocIdentifier::Ref(r) | DocIdentifier::Other(r) => Invoice::query()
+                .filter(invoice::inv_ref.eq(r))
+                .load(conn),
+        }.map_err(ServiceError::database("Error retrieving invoice from database"))?;
 
-        let [invoice] = <[Invoice; 1]>::try_from(invoices)
-            .map_err(|_| "Identifier failed to uniquely identify an invoice")?;
+        unique(ident, invoices)
+    }
+}
 
-        Ok(invoice)
+/// The only invoice an identifier matched. A month can have an invoice for each recipient, in which
+/// case the number has to be used instead.
+fn unique<T>(ident: DocIdentifier, mut invoices: Vec<T>) -> ServiceResult<T> {
+    match invoices.len() {
+        0 => Err(ServiceError::NotFound(format!("No invoice matches '{ident}'"))),
+        1 => Ok(invoices.remove(0)),
+        n => Err(ServiceError::Ambiguous(format!(
+            "'{ident}' matches {n} invoices, use an invoice number instead"
+        ))),
     }
-}
\ No newline at end of file
+}
+
+/// A ticket's details along with the hours logged against it, for comparing with the estimate.
+#[derive(Debug, Serialize)]
+pub struct TicketWithTime {
+    #[serde(flatten)]
+    pub details: TicketDetails,
+    pub tick_logged: f64,
+}
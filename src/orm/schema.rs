@@ -1,11 +1,48 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit (aud_id) {
+        aud_id -> Integer,
+        cmd_id -> Nullable<Integer>,
+        aud_table -> Text,
+        aud_action -> Text,
+        aud_old -> Nullable<Text>,
+        aud_new -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    budget (bud_id) {
+        bud_id -> Integer,
+        proj_key -> Text,
+        tick_num -> Nullable<Integer>,
+        bud_hours -> Nullable<Double>,
+        bud_money -> Nullable<Double>,
+        bud_rate -> Nullable<Double>,
+    }
+}
+
+diesel::table! {
+    command (cmd_id) {
+        cmd_id -> Integer,
+        cmd_time -> Timestamp,
+        cmd_line -> Text,
+        cmd_undoes -> Nullable<Integer>,
+        cmd_done -> Bool,
+    }
+}
+
 diesel::table! {
     invoice (inv_num) {
         inv_num -> Integer,
         inv_month -> Date,
         recip_id -> Text,
         inv_created -> Nullable<Date>,
+        inv_start -> Nullable<Date>,
+        inv_end -> Nullable<Date>,
+        inv_ref -> Nullable<Text>,
+        inv_series -> Nullable<Text>,
+        inv_seq -> Nullable<Integer>,
     }
 }
 
@@ -22,6 +59,7 @@ diesel::table! {
     project (proj_key) {
         proj_key -> Text,
         proj_name -> Text,
+        proj_billable -> Bool,
     }
 }
 
@@ -33,6 +71,31 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    scheme (sch_id) {
+        sch_id -> Integer,
+        recip_id -> Nullable<Text>,
+        sch_format -> Text,
+    }
+}
+
+diesel::table! {
+    tag (tag_name) {
+        tag_name -> Text,
+    }
+}
+
+diesel::table! {
+    ticket (proj_key, tick_num) {
+        proj_key -> Text,
+        tick_num -> Integer,
+        tick_title -> Text,
+        tick_url -> Nullable<Text>,
+        tick_status -> Text,
+        tick_estimate -> Nullable<Double>,
+    }
+}
+
 diesel::table! {
     ticket_time (proj_key, tick_num, time_id) {
         proj_key -> Text,
@@ -49,20 +112,60 @@ diesel::table! {
         time_desc -> Text,
         time_dur -> Nullable<Double>,
         act_num -> Nullable<Integer>,
+        time_billable -> Bool,
+        time_notes -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    time_tag (tag_name, time_id) {
+        tag_name -> Text,
+        time_id -> Integer,
+    }
+}
+
+diesel::table! {
+    timer (timer_id) {
+        timer_id -> Integer,
+        timer_start -> Timestamp,
+        timer_desc -> Text,
+    }
+}
+
+diesel::table! {
+    timer_ticket (proj_key, tick_num) {
+        proj_key -> Text,
+        tick_num -> Integer,
     }
 }
 
+diesel::joinable!(audit -> command (cmd_id));
+diesel::joinable!(budget -> project (proj_key));
 diesel::joinable!(invoice -> recipient (recip_id));
 diesel::joinable!(invoice_activity -> invoice (inv_num));
+diesel::joinable!(scheme -> recipient (recip_id));
+diesel::joinable!(ticket -> project (proj_key));
 diesel::joinable!(ticket_time -> project (proj_key));
 diesel::joinable!(ticket_time -> time (time_id));
 diesel::joinable!(time -> invoice_activity (act_num));
+diesel::joinable!(time_tag -> tag (tag_name));
+diesel::joinable!(time_tag -> time (time_id));
+diesel::joinable!(timer_ticket -> project (proj_key));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit,
+    budget,
+    command,
     invoice,
     invoice_activity,
     project,
     recipient,
+    scheme,
+    tag,
+    ticket,
     ticket_time,
     time,
+    time_tag,
+    timer,
+    timer_ticket,
 );
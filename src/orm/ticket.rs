@@ -1,9 +1,10 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use derive_more::{Debug, Display};
+use diesel::prelude::*;
 use serde::{Serialize, Serializer};
 
-use crate::{cli::patterns::{TICKET_PATTERN, TicketPatternCaptures}, orm::model::TicketTime};
+use crate::{cli::patterns::{TICKET_PATTERN, TicketPatternCaptures}, orm::model::{TicketTime, TimerTicket}};
 
 #[derive(Debug, Display, PartialEq, Eq, PartialOrd, Ord, Clone)]
 #[debug("\"{proj_key}-{tick_num}\"")]
@@ -24,6 +25,15 @@ impl From<TicketTime> for Ticket {
     }
 }
 
+impl From<TimerTicket> for Ticket {
+    fn from(value: TimerTicket) -> Self {
+        Ticket {
+            proj_key: value.proj_key,
+            tick_num: value.tick_num
+        }
+    }
+}
+
 impl Serialize for Ticket {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -40,6 +50,24 @@ impl Ticket {
                 .map_err(|e| format!("Error parsing ticket number:\n{e}"))?,
         })
     }
+
+    /// Find every ticket mentioned in some free-form text, in order of appearance.
+    pub fn find_all(text: &str) -> Vec<Ticket> {
+        let mut tickets = vec![];
+        let mut rest = text;
+
+        while let Some(groups) = TICKET_PATTERN.captures(rest) {
+            let end = groups.tick_num.end;
+
+            if let Ok(ticket) = Ticket::try_from_match(groups) {
+                tickets.push(ticket);
+            }
+
+            rest = &rest[end..];
+        }
+
+        tickets
+    }
 }
 
 impl FromStr for Ticket {
@@ -51,4 +79,39 @@ impl FromStr for Ticket {
 
         Ticket::try_from_match(groups)
     }
-}
\ No newline at end of file
+}
+
+/// Titles from the ticket table, for showing tickets as "ABC-12 Fix login timeout" rather than just
+/// their key. The default has no titles, so every ticket is shown by key.
+#[derive(Debug, Default)]
+pub struct TicketTitles(BTreeMap<Ticket, String>);
+
+impl TicketTitles {
+    pub fn load(conn: &mut SqliteConnection) -> QueryResult<TicketTitles> {
+        use crate::orm::schema::ticket;
+
+        let titles: Vec<(String, i32, String)> = ticket::table
+            .select((ticket::proj_key, ticket::tick_num, ticket::tick_title))
+            .load(conn)?;
+
+        Ok(TicketTitles(
+            titles.into_iter()
+                .map(|(proj_key, tick_num, title)| (Ticket { proj_key, tick_num }, title))
+                .collect()
+        ))
+    }
+
+    pub fn label(&self, ticket: &Ticket) -> String {
+        match self.0.get(ticket) {
+            Some(title) => format!("{ticket} {title}"),
+            None => ticket.to_string(),
+        }
+    }
+
+    pub fn join<'a>(&self, tickets: impl IntoIterator<Item = &'a Ticket>) -> String {
+        tickets.into_iter()
+            .map(|t| self.label(t))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
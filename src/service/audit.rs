@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+use chrono::Local;
+use diesel::dsl::{count_star, max, not};
+use diesel::sql_types::Text;
+use diesel::{delete, insert_into, prelude::*, sql_query, update};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::orm::model::{Audit, Command};
+use crate::service::error::{ServiceError, ServiceResult};
+
+/// A command along with how many changes it made, and what undid it if anything did.
+#[derive(Debug, Serialize)]
+pub struct CommandSummary {
+    #[serde(flatten)]
+    pub command: Command,
+    pub changes: i64,
+    pub undone_by: Option<i32>,
+}
+
+/// Start a command, which every change is recorded against until it finishes.
+pub fn start_command(conn: &mut SqliteConnection, cmd_line: &str) -> ServiceResult<i32> {
+    use crate::orm::schema::command;
+
+    insert_into(command::table)
+        .values((
+            command::cmd_time.eq(Local::now().naive_local()),
+            command::cmd_line.eq(cmd_line),
+        ))
+        .returning(command::cmd_id)
+        .get_result(conn)
+        .map_err(ServiceError::database("Error inserting command into database"))
+}
+
+/// Finish a command, removing it again if it didn't change anything so that the history only has
+/// commands worth looking at.
+pub fn finish_command(conn: &mut SqliteConnection, cmd_id: i32) -> ServiceResult<()> {
+    use crate::orm::schema::{audit, command};
+
+    delete(command::table.find(cmd_id))
+        .filter(not(command::cmd_id.nullable().eq_any(audit::table.select(audit::cmd_id))))
+        .execute(conn)
+        .map_err(ServiceError::database("Error removing command from database"))?;
+
+    update(command::table.find(cmd_id))
+        .set(command::cmd_done.eq(true))
+        .execute(conn)
+        .map_err(ServiceError::database("Error updating command in database"))?;
+
+    Ok(())
+}
+
+/// The running command, if there is one.
+fn running_command(conn: &mut SqliteConnection) -> ServiceResult<Option<i32>> {
+    use crate::orm::schema::command;
+
+    command::table
+        .filter(command::cmd_done.eq(false))
+        .select(max(command::cmd_id))
+        .first(conn)
+        .map_err(ServiceError::database("Error retrieving commands from database"))
+}
+
+/// The most recent finished command that changed something and can still be undone, which
+/// excludes undos themselves.
+pub fn last_undoable(conn: &mut SqliteConnection) -> ServiceResult<Command> {
+    use crate::orm::schema::{audit, command};
+
+    let undone: Vec<i32> = command::table
+        .select(command::cmd_undoes.assume_not_null())
+        .filter(command::cmd_undoes.is_not_null())
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving commands from database"))?;
+
+    Command::query()
+        .filter(command::cmd_done.eq(true))
+        .filter(command::cmd_undoes.is_null())
+        .filter(not(command::cmd_id.eq_any(undone)))
+        .filter(command::cmd_id.nullable().eq_any(audit::table.select(audit::cmd_id)))
+        .order(command::cmd_id.desc())
+        .first(conn)
+        .optional()
+        .map_err(ServiceError::database("Error retrieving commands from database"))?
+        .ok_or_else(|| ServiceError::Invalid("There are no changes left to undo".to_owned()))
+}
+
+/// Revert the changes of a command, latest first, as part of the running command. Rows that have
+/// changed since can't be reverted safely, so the whole undo fails instead.
+pub fn undo(conn: &mut SqliteConnection, target: &Command) -> ServiceResult<usize> {
+    use crate::orm::schema::command;
+
+    let changes = select_changes(conn, target.cmd_id)?;
+
+    for change in changes.iter().rev() {
+        revert(conn, change)?;
+    }
+
+    let running = running_command(conn)?;
+
+    update(command::table)
+        .filter(command::cmd_id.nullable().eq(running))
+        .set(command::cmd_undoes.eq(target.cmd_id))
+        .execute(conn)
+        .map_err(ServiceError::database("Error updating command in database"))?;
+
+    Ok(changes.len())
+}
+
+/// The most recent commands that changed something, latest first.
+pub fn select_commands(
+    conn: &mut SqliteConnection,
+    limit: i64
+) -> ServiceResult<Vec<CommandSummary>> {
+    use crate::orm::schema::{audit, command};
+
+    let commands = Command::query()
+        .filter(command::cmd_id.nullable().eq_any(audit::table.select(audit::cmd_id)))
+        .order(command::cmd_id.desc())
+        .limit(limit)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving commands from database"))?;
+
+    let changes: BTreeMap<Option<i32>, i64> = audit::table
+        .group_by(audit::cmd_id)
+        .select((audit::cmd_id, count_star()))
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving changes from database"))?
+        .into_iter()
+        .collect();
+
+    let undos: Vec<(i32, Option<i32>)> = command::table
+        .select((command::cmd_id, command::cmd_undoes))
+        .filter(command::cmd_undoes.is_not_null())
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving commands from database"))?;
+
+    Ok(commands.into_iter()
+        .map(|command| CommandSummary {
+            changes: changes.get(&Some(command.cmd_id)).copied().unwrap_or_default(),
+            undone_by: undos.iter()
+                .find(|(_, undoes)| *undoes == Some(command.cmd_id))
+                .map(|(cmd_id, _)| *cmd_id),
+            command,
+        })
+        .collect())
+}
+
+/// The changes made by a command, in the order they were made.
+pub fn select_changes(conn: &mut SqliteConnection, cmd_id: i32) -> ServiceResult<Vec<Audit>> {
+    use crate::orm::schema::{audit, command};
+
+    let exists: i64 = command::table.find(cmd_id)
+        .count()
+        .get_result(conn)
+        .map_err(ServiceError::database("Error retrieving command from database"))?;
+
+    if exists == 0 {
+        Err(ServiceError::NotFound(format!("No command exists with id {cmd_id}")))?
+    }
+
+    Audit::query()
+        .filter(audit::cmd_id.eq(cmd_id))
+        .order(audit::aud_id)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving changes from database"))
+}
+
+/// The columns identifying a row of an audited table.
+pub fn primary_key(table: &str) -> Option<&'static [&'static str]> {
+    Some(match table {
+        "time" => &["time_id"],
+        "ticket_time" => &["proj_key", "tick_num", "time_id"],
+        "time_tag" => &["tag_name", "time_id"],
+        "invoice_activity" => &["act_num"],
+        "invoice" => &["inv_num"],
+        _ => None?,
+    })
+}
+
+/// Parse a recorded row, which is a JSON object of its columns.
+pub fn parse_row(change: &Audit, row: Option<&str>) -> ServiceResult<Map<String, Value>> {
+    let invalid = || ServiceError::Invalid(
+        format!("Change {} doesn't have a valid row recorded", change.aud_id)
+    );
+
+    let row: Map<String, Value> = serde_json::from_str(row.ok_or_else(invalid)?)
+        .map_err(|_| invalid())?;
+
+    // Column names are written into queries, so they have to be plain identifiers.
+    if !row.keys().all(|k| k.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')) {
+        Err(invalid())?
+    }
+
+    Ok(row)
+}
+
+#[derive(QueryableByName)]
+struct CurrentRow {
+    #[diesel(sql_type = Text)]
+    row: String,
+}
+
+fn revert(conn: &mut SqliteConnection, change: &Audit) -> ServiceResult<()> {
+    let table = &change.aud_table;
+    let key = primary_key(table)
+        .ok_or_else(|| ServiceError::Invalid(format!("Changes to {table} can't be undone")))?;
+
+    // Values are bound as the recorded JSON and extracted in SQL, so that they keep their types.
+    let extract = |column: &str, param: usize| format!("json_extract(?{param}, '$.{column}')");
+    let matches = |param: usize| key.iter()
+        .map(|c| format!("\"{c}\" = {}", extract(c, param)))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    match change.aud_action.as_str() {
+        "insert" => {
+            let new = check_unchanged(conn, change, key)?;
+            sql_query(format!("DELETE FROM \"{table}\" WHERE {}", matches(1)))
+                .bind::<Text, _>(new)
+                .execute(conn)
+        },
+        "update" => {
+            let new = check_unchanged(conn, change, key)?;
+            let old = parse_row(change, change.aud_old.as_deref())?;
+            let set = old.keys()
+                .map(|c| format!("\"{c}\" = {}", extract(c, 1)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            sql_query(format!("UPDATE \"{table}\" SET {set} WHERE {}", matches(2)))
+                .bind::<Text, _>(Value::from(old).to_string())
+                .bind::<Text, _>(new)
+                .execute(conn)
+        },
+        "delete" => {
+            let old = parse_row(change, change.aud_old.as_deref())?;
+            let columns: Vec<&String> = old.keys().collect();
+
+            sql_query(format!(
+                "INSERT INTO \"{table}\" ({}) VALUES ({})",
+                columns.iter().map(|c| format!("\"{c}\"")).collect::<Vec<_>>().join(", "),
+                columns.iter().map(|c| extract(c, 1)).collect::<Vec<_>>().join(", ")
+            ))
+                .bind::<Text, _>(Value::from(old).to_string())
+                .execute(conn)
+        },
+        action => Err(ServiceError::Invalid(format!("Unknown change '{action}'")))?,
+    }.map_err(ServiceError::database("Error reverting change in database"))?;
+
+    Ok(())
+}
+
+/// Check that a row is still as a change left it, returning the recorded row to bind.
+fn check_unchanged(
+    conn: &mut SqliteConnection,
+    change: &Audit,
+    key: &[&str]
+) -> ServiceResult<String> {
+    let table = &change.aud_table;
+    let new = parse_row(change, change.aud_new.as_deref())?;
+
+    let columns = new.keys()
+        .map(|c| format!("'{c}', \"{c}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let matches = key.iter()
+        .map(|c| format!("\"{c}\" = json_extract(?1, '$.{c}')"))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    let current: Option<CurrentRow> = sql_query(format!(
+        "SELECT json_object({columns}) AS row FROM \"{table}\" WHERE {matches}"
+    ))
+        .bind::<Text, _>(Value::from(new.clone()).to_string())
+        .get_result(conn)
+        .optional()
+        .map_err(ServiceError::database("Error retrieving changed row from database"))?;
+
+    let unchanged = current
+        .and_then(|c| serde_json::from_str::<Map<String, Value>>(&c.row).ok())
+        .is_some_and(|c| c == new);
+
+    if !unchanged {
+        let row = key.iter()
+            .map(|c| format!("{c} {}", new.get(*c).unwrap_or(&Value::Null)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Err(ServiceError::Invalid(format!(
+            "The {table} row with {row} has changed since, so it can't be undone"
+        )))?
+    }
+
+    Ok(Value::from(new).to_string())
+}
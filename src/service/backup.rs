@@ -0,0 +1,247 @@
+use std::{fs::{self, File, OpenOptions}, io::{self, ErrorKind}, path::{Path, PathBuf}, process};
+
+use chrono::{Local, NaiveDateTime};
+use diesel::{prelude::*, sql_query, sql_types::Text};
+use serde::Serialize;
+
+use crate::service::error::{ServiceError, ServiceResult};
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H%M%S%.3f";
+
+/// Files SQLite keeps next to a database while it's in use.
+const SIDECAR_SUFFIXES: [&str; 3] = ["-journal", "-wal", "-shm"];
+
+/// Why a backup was taken. Only automatic backups are removed to make room for newer ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    Auto,
+    Manual,
+    Restore,
+}
+
+impl BackupKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            BackupKind::Auto => "auto",
+            BackupKind::Manual => "manual",
+            BackupKind::Restore => "restore",
+        }
+    }
+
+    fn parse(name: &str) -> Option<BackupKind> {
+        Some(match name {
+            "auto" => BackupKind::Auto,
+            "manual" => BackupKind::Manual,
+            "restore" => BackupKind::Restore,
+            _ => None?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Backup {
+    pub name: String,
+    pub kind: BackupKind,
+    pub created: NaiveDateTime,
+    pub size: u64,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// The file behind a database URL, which in-memory databases and ones that don't exist yet lack.
+pub fn database_path(db_url: &str) -> Option<PathBuf> {
+    let path = db_url.strip_prefix("file:").unwrap_or(db_url);
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+
+    Some(PathBuf::from(path)).filter(|p| path != ":memory:" && p.is_file())
+}
+
+/// Backups are kept next to the database, in a directory named after it.
+pub fn backup_dir(database: &Path) -> PathBuf {
+    with_suffix(database, ".backups")
+}
+
+/// Write a consistent copy of the database into the backup directory. This can't happen inside a
+/// transaction.
+pub fn create_backup(
+    conn: &mut SqliteConnection,
+    database: &Path,
+    kind: BackupKind
+) -> ServiceResult<Backup> {
+    let dir = backup_dir(database);
+    fs::create_dir_all(&dir).map_err(|e| ServiceError::File(
+        format!("Error creating backup directory {}:\n{e}", dir.display())
+    ))?;
+
+    let created = Local::now().naive_local();
+    let name = format!("{}-{}.db", created.format(TIME_FORMAT), kind.name());
+    let path = dir.join(&name);
+
+    sql_query("VACUUM INTO ?")
+        .bind::<Text, _>(path.display().to_string())
+        .execute(conn)
+        .map_err(ServiceError::database("Error backing up database"))?;
+
+    let size = fs::metadata(&path)
+        .map_err(|e| ServiceError::File(format!("Error reading backup {}:\n{e}", path.display())))?
+        .len();
+
+    Ok(Backup { name, kind, created, size, path })
+}
+
+/// The backups of a database, oldest first. Files in the directory that aren't named like a
+/// backup are left out.
+pub fn list_backups(database: &Path) -> ServiceResult<Vec<Backup>> {
+    let dir = backup_dir(database);
+
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let dir_error = |e| ServiceError::File(
+        format!("Error reading backup directory {}:\n{e}", dir.display())
+    );
+
+    let mut backups = vec![];
+
+    for entry in fs::read_dir(&dir).map_err(dir_error)? {
+        let entry = entry.map_err(dir_error)?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        let Some((created, kind)) = name.strip_suffix(".db")
+            .and_then(|stem| stem.rsplit_once('-'))
+            .and_then(|(created, kind)| Some((
+                NaiveDateTime::parse_from_str(created, TIME_FORMAT).ok()?,
+                BackupKind::parse(kind)?,
+            ))) else {
+            continue;
+        };
+
+        let size = entry.metadata()
+            .map_err(|e| ServiceError::File(format!("Error reading backup {name}:\n{e}")))?
+            .len();
+
+        backups.push(Backup { path: entry.path(), name, kind, created, size });
+    }
+
+    backups.sort_by(|a, b| a.created.cmp(&b.created).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(backups)
+}
+
+/// Remove all but the latest automatic backups.
+pub fn prune_backups(database: &Path, keep: usize) -> ServiceResult<()> {
+    let backups = list_backups(database)?;
+    let automatic: Vec<&Backup> = backups.iter()
+        .filter(|b| b.kind == BackupKind::Auto)
+        .collect();
+
+    for backup in &automatic[..automatic.len().saturating_sub(keep)] {
+        fs::remove_file(&backup.path).map_err(|e| ServiceError::File(
+            format!("Error removing backup {}:\n{e}", backup.path.display())
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// The backup with a name, or otherwise the path of a database file to restore.
+pub fn find_backup(database: &Path, name: &str) -> ServiceResult<PathBuf> {
+    let path = list_backups(database)?
+        .into_iter()
+        .find(|b| b.name == name)
+        .map_or_else(|| PathBuf::from(name), |b| b.path);
+
+    if !path.is_file() {
+        Err(ServiceError::NotFound(format!("No backup exists named {name}")))?
+    }
+
+    Ok(path)
+}
+
+#[derive(QueryableByName)]
+struct IntegrityCheck {
+    #[diesel(sql_type = Text)]
+    quick_check: String,
+}
+
+/// Make sure a backup is an intact database before anything is replaced with it.
+pub fn check_backup(path: &Path) -> ServiceResult<()> {
+    let mut conn = SqliteConnection::establish(&path.display().to_string())
+        .map_err(|e| ServiceError::Invalid(
+            format!("Error opening backup {}:\n{e}", path.display())
+        ))?;
+
+    let results: Vec<IntegrityCheck> = sql_query("PRAGMA quick_check")
+        .load(&mut conn)
+        .map_err(|e| ServiceError::Invalid(
+            format!("The backup {} isn't a database:\n{e}", path.display())
+        ))?;
+
+    if results.iter().any(|r| r.quick_check != "ok") {
+        Err(ServiceError::Invalid(
+            format!("The backup {} is damaged, so it can't be restored", path.display())
+        ))?
+    }
+
+    Ok(())
+}
+
+/// Replace the database with a backup, returning the backup of the state it replaced. The
+/// connection is closed before the file is replaced.
+///
+/// The backup is copied next to the database and renamed over it, so that the database is never
+/// left half-written. Journal files of the replaced database are removed first, as SQLite would
+/// otherwise apply them to the restored one.
+pub fn restore_backup(
+    mut conn: SqliteConnection,
+    database: &Path,
+    source: &Path
+) -> ServiceResult<Backup> {
+    check_backup(source)?;
+
+    let previous = create_backup(&mut conn, database, BackupKind::Restore)?;
+    drop(conn);
+
+    let temp = with_suffix(database, &format!(".restore-{}", process::id()));
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp)
+        .map_err(|e| ServiceError::File(format!("Error creating {}:\n{e}", temp.display())))?;
+
+    let replaced = File::open(source)
+        .and_then(|mut source| io::copy(&mut source, &mut file))
+        .and_then(|_| file.sync_all())
+        .and_then(|_| remove_sidecars(database))
+        .and_then(|_| fs::rename(&temp, database));
+
+    if let Err(e) = replaced {
+        let _ = fs::remove_file(&temp);
+        Err(ServiceError::File(format!(
+            "Error replacing {} with {}:\n{e}",
+            database.display(),
+            source.display()
+        )))?
+    }
+
+    Ok(previous)
+}
+
+fn remove_sidecars(database: &Path) -> io::Result<()> {
+    for suffix in SIDECAR_SUFFIXES {
+        match fs::remove_file(with_suffix(database, suffix)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(suffix);
+    path.with_file_name(name)
+}
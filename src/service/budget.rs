@@ -0,0 +1,260 @@
+use std::{collections::BTreeMap, fmt::{self, Display, Formatter}, str::FromStr};
+
+use chrono::{Days, NaiveDate};
+use diesel::{delete, insert_into, prelude::*};
+use serde::Serialize;
+
+use crate::orm::filter::TimeFilter;
+use crate::orm::insert::NewBudget;
+use crate::orm::model::Budget;
+use crate::orm::query::TimeWithTickets;
+use crate::orm::ticket::Ticket;
+use crate::service::error::{ServiceError, ServiceResult};
+use crate::service::time::select_times;
+use crate::util::date::Date;
+
+/// Fractions of a budget that logging warns about passing.
+pub const THRESHOLDS: [f64; 2] = [0.8, 1.0];
+
+/// How many days back the burn rate is averaged over.
+const BURN_DAYS: u64 = 28;
+
+/// What a budget applies to: a whole project, or one of its tickets.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BudgetTarget {
+    Project(String),
+    Ticket(Ticket),
+}
+
+impl BudgetTarget {
+    pub fn proj_key(&self) -> &str {
+        match self {
+            BudgetTarget::Project(proj_key) => proj_key,
+            BudgetTarget::Ticket(ticket) => &ticket.proj_key,
+        }
+    }
+
+    pub fn tick_num(&self) -> Option<i32> {
+        match self {
+            BudgetTarget::Project(_) => None,
+            BudgetTarget::Ticket(ticket) => Some(ticket.tick_num),
+        }
+    }
+}
+
+impl FromStr for BudgetTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('-') {
+            Ok(BudgetTarget::Ticket(s.parse()?))
+        } else if !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            Ok(BudgetTarget::Project(s.to_owned()))
+        } else {
+            Err("Value is neither a project key nor a ticket".to_owned())
+        }
+    }
+}
+
+impl Display for BudgetTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetTarget::Project(proj_key) => write!(f, "{proj_key}"),
+            BudgetTarget::Ticket(ticket) => write!(f, "{ticket}"),
+        }
+    }
+}
+
+/// A budget along with how much of it has been spent, as of some day.
+#[derive(Debug, Serialize)]
+pub struct BudgetUsage {
+    #[serde(flatten)]
+    pub budget: Budget,
+    pub spent_hours: f64,
+    /// Only for money budgets, which are spent at the budget's rate.
+    pub spent_money: Option<f64>,
+    /// The largest fraction spent of either the hours or the money.
+    pub used: f64,
+    /// Hours left before either the hours or the money run out.
+    pub remaining_hours: f64,
+    /// Average hours per week over the last four weeks.
+    pub weekly_hours: f64,
+    /// When the budget runs out at the current burn rate, if it hasn't already and is being spent.
+    pub runs_out: Option<Date>,
+}
+
+impl BudgetUsage {
+    fn new(budget: Budget, times: &[&TimeWithTickets], today: NaiveDate) -> BudgetUsage {
+        let recent = today - Days::new(BURN_DAYS - 1);
+
+        // Summing from positive zero, as `sum` starts at negative zero, which prints as "-0".
+        let spent_hours = times.iter()
+            .flat_map(|t| t.time_dur)
+            .fold(0.0, |a, b| a + b);
+        let recent_hours = times.iter()
+            .filter(|t| t.time_start.date() >= recent && t.time_start.date() <= today)
+            .flat_map(|t| t.time_dur)
+            .fold(0.0, |a, b| a + b);
+
+        let spent_money = budget.bud_rate.map(|rate| spent_hours * rate);
+
+        let used = [
+            budget.bud_hours.map(|hours| spent_hours / hours),
+            budget.bud_money.zip(spent_money).map(|(money, spent)| spent / money),
+        ].into_iter().flatten().fold(0.0, f64::max);
+
+        let remaining_hours = [
+            budget.bud_hours.map(|hours| hours - spent_hours),
+            budget.bud_money.zip(budget.bud_rate)
+                .map(|(money, rate)| money / rate - spent_hours),
+        ].into_iter().flatten().fold(f64::INFINITY, f64::min);
+
+        let daily_hours = recent_hours / BURN_DAYS as f64;
+        let runs_out = (remaining_hours > 0.0 && daily_hours > 0.0)
+            .then(|| today + Days::new((remaining_hours / daily_hours).ceil() as u64))
+            .map(Date::from);
+
+        BudgetUsage {
+            budget,
+            spent_hours,
+            spent_money,
+            used,
+            remaining_hours,
+            weekly_hours: daily_hours * 7.0,
+            runs_out,
+        }
+    }
+
+    pub fn target(&self) -> BudgetTarget {
+        target(&self.budget)
+    }
+
+    /// The highest threshold that has been passed since an earlier usage of the same budget.
+    pub fn crossed_since(&self, before: &BudgetUsage) -> Option<f64> {
+        THRESHOLDS.into_iter()
+            .rev()
+            .find(|t| before.used < *t && self.used >= *t)
+    }
+}
+
+/// Set the budget of a project or ticket, replacing any it already has.
+pub fn set_budget(
+    conn: &mut SqliteConnection,
+    target: &BudgetTarget,
+    hours: Option<f64>,
+    money: Option<f64>,
+    rate: Option<f64>
+) -> ServiceResult<()> {
+    use crate::orm::schema::{budget, project};
+
+    if hours.is_none() && money.is_none() {
+        Err(ServiceError::Invalid("A budget needs hours, money or both".to_owned()))?
+    }
+    if money.is_some() && rate.is_none() {
+        Err(ServiceError::Invalid("A money budget needs an hourly rate".to_owned()))?
+    }
+
+    let exists = project::table
+        .find(target.proj_key())
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))?;
+
+    if exists == 0 {
+        Err(ServiceError::Invalid(format!("No project exists with key '{}'", target.proj_key())))?
+    }
+
+    remove_budget(conn, target).or_else(|e| match e {
+        ServiceError::NotFound(_) => Ok(()),
+        e => Err(e),
+    })?;
+
+    insert_into(budget::table)
+        .values(NewBudget {
+            proj_key: target.proj_key().to_owned(),
+            tick_num: target.tick_num(),
+            bud_hours: hours,
+            bud_money: money,
+            bud_rate: rate,
+        })
+        .execute(conn)
+        .map_err(ServiceError::database("Error inserting budget into database"))?;
+
+    Ok(())
+}
+
+/// Remove the budget of a project or ticket. A project's budget is separate from those of its
+/// tickets, so removing it leaves theirs in place.
+pub fn remove_budget(conn: &mut SqliteConnection, target: &BudgetTarget) -> ServiceResult<()> {
+    use crate::orm::schema::budget;
+
+    // A project budget has no ticket, which `eq` can't match, so it's handled separately.
+    let deleted = match target.tick_num() {
+        Some(tick_num) => delete(budget::table)
+            .filter(budget::proj_key.eq(target.proj_key()))
+            .filter(budget::tick_num.eq(tick_num))
+            .execute(conn),
+        None => delete(budget::table)
+            .filter(budget::proj_key.eq(target.proj_key()))
+            .filter(budget::tick_num.is_null())
+            .execute(conn),
+    }.map_err(ServiceError::database("Error deleting budget from database"))?;
+
+    if deleted == 0 {
+        Err(ServiceError::NotFound(format!("{target} has no budget")))?
+    }
+
+    Ok(())
+}
+
+/// Usage of every budget as of a day, or only those that time on some tickets would count
+/// towards, in order of what they're for.
+pub fn budget_usage(
+    conn: &mut SqliteConnection,
+    tickets: Option<&[Ticket]>,
+    today: NaiveDate
+) -> ServiceResult<Vec<BudgetUsage>> {
+    let mut budgets: Vec<Budget> = Budget::query()
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving budgets from database"))?
+        .into_iter()
+        .filter(|b| tickets.is_none_or(|tickets| tickets.iter().any(|t| applies_to(b, t))))
+        .collect();
+    budgets.sort_by_key(target);
+
+    // Time is only loaded once for each project with a budget.
+    let mut times: BTreeMap<String, Vec<TimeWithTickets>> = BTreeMap::new();
+
+    budgets.into_iter()
+        .map(|budget| {
+            if !times.contains_key(&budget.proj_key) {
+                let filter = TimeFilter {
+                    project: Some(budget.proj_key.clone()),
+                    ..Default::default()
+                };
+                times.insert(budget.proj_key.clone(), select_times(conn, filter, None)?);
+            }
+
+            let counted: Vec<&TimeWithTickets> = times[&budget.proj_key].iter()
+                .filter(|time| budget.tick_num.is_none()
+                    || time.tickets.iter().any(|t| applies_to(&budget, t)))
+                .collect();
+
+            Ok(BudgetUsage::new(budget, &counted, today))
+        })
+        .collect()
+}
+
+fn target(budget: &Budget) -> BudgetTarget {
+    match budget.tick_num {
+        Some(tick_num) => BudgetTarget::Ticket(Ticket {
+            proj_key: budget.proj_key.clone(),
+            tick_num,
+        }),
+        None => BudgetTarget::Project(budget.proj_key.clone()),
+    }
+}
+
+fn applies_to(budget: &Budget, ticket: &Ticket) -> bool {
+    budget.proj_key == ticket.proj_key && budget.tick_num.is_none_or(|n| n == ticket.tick_num)
+}
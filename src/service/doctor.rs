@@ -0,0 +1,374 @@
+use std::fmt::{self, Display, Formatter};
+
+use chrono::NaiveDateTime;
+use diesel::dsl::not;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text};
+use diesel::{delete, insert_or_ignore_into, prelude::*, sql_query, update};
+use serde::Serialize;
+
+use crate::orm::model::Invoice;
+use crate::service::error::{ServiceError, ServiceResult};
+
+/// The longest project key the schema allows.
+const MAX_PROJ_KEY_LEN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    Integrity,
+    ForeignKey,
+    Overlap,
+    InvoicePeriod,
+    EmptyInvoice,
+    UnusedProject,
+    ProjectKey,
+}
+
+impl Check {
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::Integrity => "integrity",
+            Check::ForeignKey => "foreign-key",
+            Check::Overlap => "overlap",
+            Check::InvoicePeriod => "invoice-period",
+            Check::EmptyInvoice => "empty-invoice",
+            Check::UnusedProject => "unused-project",
+            Check::ProjectKey => "project-key",
+        }
+    }
+}
+
+/// A change that resolves a problem without losing anything that's still referenced.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Fix {
+    /// Add a missing project, named after its key.
+    AddProject { proj_key: String },
+    AddTag { tag_name: String },
+    /// Remove a row linking to time that no longer exists.
+    DeleteRow { table: String, rowid: i64 },
+    /// Unbill time whose activity no longer exists.
+    ClearActivity { time_id: i32 },
+    /// Remove an invoice that has no activities.
+    DeleteInvoice { inv_num: i32 },
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::AddProject { proj_key } => write!(f, "add project {proj_key}"),
+            Fix::AddTag { tag_name } => write!(f, "add tag #{tag_name}"),
+            Fix::DeleteRow { table, rowid } => write!(f, "delete {table} row {rowid}"),
+            Fix::ClearActivity { time_id } => write!(f, "unbill time {time_id}"),
+            Fix::DeleteInvoice { inv_num } => write!(f, "delete invoice {inv_num}"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    pub check: Check,
+    pub description: String,
+    pub fix: Option<Fix>,
+}
+
+impl Problem {
+    fn new(check: Check, description: String, fix: Option<Fix>) -> Problem {
+        Problem { check, description, fix }
+    }
+}
+
+/// Look for everything that's inconsistent in the database. Foreign keys are only enforced by
+/// sessions that turn them on, so other tools may have left rows that break them.
+pub fn diagnose(conn: &mut SqliteConnection) -> ServiceResult<Vec<Problem>> {
+    let mut problems = integrity_problems(conn)?;
+    problems.extend(foreign_key_problems(conn)?);
+    problems.extend(overlap_problems(conn)?);
+    problems.extend(invoice_period_problems(conn)?);
+    problems.extend(empty_invoice_problems(conn)?);
+    problems.extend(project_problems(conn)?);
+
+    Ok(problems)
+}
+
+pub fn apply_fix(conn: &mut SqliteConnection, fix: &Fix) -> ServiceResult<()> {
+    use crate::orm::schema::{invoice, project, tag, time};
+
+    // The same fix can be offered for several rows, so fixes have to be repeatable.
+    match fix {
+        Fix::AddProject { proj_key } => insert_or_ignore_into(project::table)
+            .values((project::proj_key.eq(proj_key), project::proj_name.eq(proj_key)))
+            .execute(conn)
+            .map_err(ServiceError::database("Error inserting project into database"))?,
+        Fix::AddTag { tag_name } => insert_or_ignore_into(tag::table)
+            .values(tag::tag_name.eq(tag_name))
+            .execute(conn)
+            .map_err(ServiceError::database("Error inserting tag into database"))?,
+        Fix::DeleteRow { table, rowid } => sql_query(
+            format!("DELETE FROM \"{table}\" WHERE rowid = ?1")
+        )
+            .bind::<BigInt, _>(rowid)
+            .execute(conn)
+            .map_err(ServiceError::database("Error deleting row from database"))?,
+        Fix::ClearActivity { time_id } => update(time::table.find(time_id))
+            .set(time::act_num.eq(None::<i32>))
+            .execute(conn)
+            .map_err(ServiceError::database("Error updating time in database"))?,
+        // Activities still reference the invoice, so it can't be deleted once it has any.
+        Fix::DeleteInvoice { inv_num } => delete(invoice::table.find(inv_num))
+            .execute(conn)
+            .map_err(ServiceError::database("Error deleting invoice from database"))?,
+    };
+
+    Ok(())
+}
+
+#[derive(QueryableByName)]
+struct IntegrityCheck {
+    #[diesel(sql_type = Text)]
+    integrity_check: String,
+}
+
+fn integrity_problems(conn: &mut SqliteConnection) -> ServiceResult<Vec<Problem>> {
+    let results: Vec<IntegrityCheck> = sql_query("PRAGMA integrity_check")
+        .load(conn)
+        .map_err(ServiceError::database("Error checking database integrity"))?;
+
+    // Corruption can't be repaired from here, only by restoring a backup.
+    Ok(results.into_iter()
+        .filter(|r| r.integrity_check != "ok")
+        .map(|r| Problem::new(Check::Integrity, r.integrity_check, None))
+        .collect())
+}
+
+#[derive(QueryableByName)]
+struct ForeignKeyViolation {
+    #[diesel(sql_type = Text)]
+    table: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    rowid: Option<i64>,
+    #[diesel(sql_type = Text)]
+    parent: String,
+    #[diesel(sql_type = Integer)]
+    fkid: i32,
+}
+
+#[derive(QueryableByName)]
+struct ForeignKeyColumn {
+    #[diesel(sql_type = Text)]
+    column: String,
+}
+
+#[derive(QueryableByName)]
+struct ForeignKeyValue {
+    #[diesel(sql_type = Nullable<Text>)]
+    value: Option<String>,
+}
+
+fn foreign_key_problems(conn: &mut SqliteConnection) -> ServiceResult<Vec<Problem>> {
+    let violations: Vec<ForeignKeyViolation> = sql_query("PRAGMA foreign_key_check")
+        .load(conn)
+        .map_err(ServiceError::database("Error checking foreign keys"))?;
+
+    // Links to missing time are deleted, so nothing else they reference needs adding.
+    let deleted: Vec<(String, Option<i64>)> = violations.iter()
+        .filter(|v| is_time_link(&v.table, &v.parent))
+        .map(|v| (v.table.clone(), v.rowid))
+        .collect();
+
+    let mut problems = vec![];
+
+    for ForeignKeyViolation { table, rowid, parent, fkid } in violations {
+        let Some(rowid) = rowid else {
+            problems.push(Problem::new(
+                Check::ForeignKey,
+                format!("A {table} row references a missing {parent}"),
+                None
+            ));
+            continue;
+        };
+
+        let ForeignKeyColumn { column } = sql_query(
+            "SELECT \"from\" AS \"column\" FROM pragma_foreign_key_list(?1) WHERE id = ?2"
+        )
+            .bind::<Text, _>(&table)
+            .bind::<Integer, _>(fkid)
+            .get_result(conn)
+            .map_err(ServiceError::database("Error retrieving foreign key from database"))?;
+
+        // Table and column names come from SQLite itself, so they're safe to write into queries.
+        let ForeignKeyValue { value } = sql_query(format!(
+            "SELECT CAST(\"{column}\" AS TEXT) AS value FROM \"{table}\" WHERE rowid = ?1"
+        ))
+            .bind::<BigInt, _>(rowid)
+            .get_result(conn)
+            .map_err(ServiceError::database("Error retrieving row from database"))?;
+
+        let value = value.unwrap_or_default();
+        let fix = match (table.as_str(), parent.as_str()) {
+            _ if is_time_link(&table, &parent) => {
+                Some(Fix::DeleteRow { table: table.clone(), rowid })
+            },
+            _ if deleted.contains(&(table.clone(), Some(rowid))) => None,
+            (_, "project") => Some(Fix::AddProject { proj_key: value.clone() }),
+            (_, "tag") => Some(Fix::AddTag { tag_name: value.clone() }),
+            ("time", "invoice_activity") => i32::try_from(rowid).ok()
+                .map(|time_id| Fix::ClearActivity { time_id }),
+            _ => None,
+        };
+
+        problems.push(Problem::new(
+            Check::ForeignKey,
+            format!("The {table} row {rowid} has {column} {value}, which isn't in {parent}"),
+            fix
+        ));
+    }
+
+    Ok(problems)
+}
+
+/// Whether a table only links time to something else, so its rows mean nothing without the time.
+fn is_time_link(table: &str, parent: &str) -> bool {
+    matches!(table, "ticket_time" | "time_tag") && parent == "time"
+}
+
+fn overlap_problems(conn: &mut SqliteConnection) -> ServiceResult<Vec<Problem>> {
+    use crate::orm::schema::time;
+
+    let times: Vec<(i32, NaiveDateTime, NaiveDateTime)> = time::table
+        .select((time::time_id, time::time_start, time::time_end))
+        .order((time::time_start, time::time_id))
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving time from database"))?;
+
+    let mut problems = vec![];
+
+    // Sorted by start, each entry can only overlap the ones starting before it ends.
+    for (i, (time_id, _, end)) in times.iter().enumerate() {
+        for (other_id, other_start, other_end) in &times[i + 1..] {
+            if other_start >= end {
+                break;
+            }
+
+            problems.push(Problem::new(
+                Check::Overlap,
+                format!(
+                    "Time {time_id} and {other_id} overlap from {other_start} to {}",
+                    end.min(other_end)
+                ),
+                None
+            ));
+        }
+    }
+
+    Ok(problems)
+}
+
+fn invoice_period_problems(conn: &mut SqliteConnection) -> ServiceResult<Vec<Problem>> {
+    use crate::orm::schema::{invoice, invoice_activity, time};
+
+    let invoices: Vec<Invoice> = Invoice::query()
+        .order(invoice::inv_num)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving invoices from database"))?;
+
+    // Which days a damaged period was meant to cover is anyone's guess, so it's only reported.
+    let mut problems: Vec<Problem> = invoices.iter()
+        .filter_map(|invoice| invoice.period().err())
+        .map(|description| Problem::new(Check::InvoicePeriod, description, None))
+        .collect();
+
+    let billed: Vec<(i32, NaiveDateTime, Invoice)> = time::table
+        .inner_join(invoice_activity::table.inner_join(invoice::table))
+        .select((time::time_id, time::time_start, Invoice::as_select()))
+        .order(time::time_id)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving billed time from database"))?;
+
+    // Moving time to another invoice would change what was billed, so there's no safe fix.
+    problems.extend(billed.into_iter()
+        .filter_map(|(time_id, start, invoice)| {
+            let period = invoice.period().ok()?;
+
+            (!period.contains(start)).then(|| Problem::new(
+                Check::InvoicePeriod,
+                format!(
+                    "Time {time_id} from {} is outside the period {period} of invoice {}",
+                    start.date(),
+                    invoice.inv_num
+                ),
+                None
+            ))
+        }));
+
+    Ok(problems)
+}
+
+/// Invoices without any activities. Activities without time are flat fees, so an invoice with
+/// only those still bills something.
+fn empty_invoice_problems(conn: &mut SqliteConnection) -> ServiceResult<Vec<Problem>> {
+    use crate::orm::schema::{invoice, invoice_activity};
+
+    let billed = invoice_activity::table.select(invoice_activity::inv_num);
+    let empty: Vec<(i32, Option<String>)> = invoice::table
+        .filter(not(invoice::inv_num.eq_any(billed)))
+        .select((invoice::inv_num, invoice::inv_ref))
+        .order(invoice::inv_num)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving invoices from database"))?;
+
+    // Deleting an invoice issued under a numbering scheme would leave a gap in its series.
+    Ok(empty.into_iter()
+        .map(|(inv_num, inv_ref)| match inv_ref {
+            Some(inv_ref) => Problem::new(
+                Check::EmptyInvoice,
+                format!("Invoice {inv_ref} has no activities"),
+                None
+            ),
+            None => Problem::new(
+                Check::EmptyInvoice,
+                format!("Invoice {inv_num} has no activities"),
+                Some(Fix::DeleteInvoice { inv_num })
+            ),
+        })
+        .collect())
+}
+
+fn project_problems(conn: &mut SqliteConnection) -> ServiceResult<Vec<Problem>> {
+    use crate::orm::schema::{budget, project, ticket, ticket_time, timer_ticket};
+
+    let unused: Vec<String> = project::table
+        .filter(not(project::proj_key.eq_any(ticket_time::table.select(ticket_time::proj_key))))
+        .filter(not(project::proj_key.eq_any(ticket::table.select(ticket::proj_key))))
+        .filter(not(project::proj_key.eq_any(budget::table.select(budget::proj_key))))
+        .filter(not(project::proj_key.eq_any(timer_ticket::table.select(timer_ticket::proj_key))))
+        .select(project::proj_key)
+        .order(project::proj_key)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))?;
+
+    let keys: Vec<String> = project::table
+        .select(project::proj_key)
+        .order(project::proj_key)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))?;
+
+    // A project may just not have been used yet, so it's only reported.
+    let unused = unused.into_iter()
+        .map(|proj_key| Problem::new(
+            Check::UnusedProject,
+            format!("Project {proj_key} has no time, tickets or budgets"),
+            None
+        ));
+
+    // Renaming a key would have to touch every row referencing it, so that's left to the user.
+    let long = keys.into_iter()
+        .filter(|k| k.chars().count() > MAX_PROJ_KEY_LEN)
+        .map(|proj_key| Problem::new(
+            Check::ProjectKey,
+            format!("Project key {proj_key} is longer than {MAX_PROJ_KEY_LEN} characters"),
+            None
+        ));
+
+    Ok(unused.chain(long).collect())
+}
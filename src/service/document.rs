@@ -0,0 +1,147 @@
+use csv::{QuoteStyle, WriterBuilder};
+use diesel::prelude::*;
+use typst::{Library, LibraryExt};
+use typst_pdf::PdfOptions;
+
+use crate::orm::filter::{DocIdentifier, TimeFilter};
+use crate::csv::convert::{CsvDetailedTime, CsvTime};
+use crate::orm::model::Invoice;
+use crate::orm::query::{InvoiceWithActivities, TimeWithTickets};
+use crate::orm::ticket::TicketTitles;
+use crate::service::error::{ServiceError, ServiceResult};
+use crate::service::time::select_times;
+use crate::typst::error::DisplayErrors;
+use crate::util::date::Date;
+use crate::typst::{convert::IntoTypst, world::MinimalWorld};
+
+/// A rendered document, along with the name it's saved under unless told otherwise.
+#[derive(Debug)]
+pub struct Document {
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+    /// Problems with the contents that don't stop the document from being rendered.
+    pub warnings: Vec<String>,
+}
+
+/// Render the invoice matching an identifier to PDF bytes, optionally with ticket titles.
+pub fn render_invoice(
+    conn: &mut SqliteConnection,
+    ident: DocIdentifier,
+    titles: bool
+) -> ServiceResult<Document> {
+    let invoice = InvoiceWithActivities::select_by_identifier(ident, conn)?;
+    let file_name = format!("{}-tax-invoice-{}.pdf", invoice.inv_month, invoice.number());
+
+    Ok(Document {
+        file_name,
+        bytes: invoice_pdf(invoice, &ticket_titles(conn, titles)?)?,
+        warnings: vec![],
+    })
+}
+
+/// Render an invoice to PDF bytes, labelling tickets with the given titles.
+pub fn invoice_pdf(
+    invoice: InvoiceWithActivities,
+    titles: &TicketTitles
+) -> ServiceResult<Vec<u8>> {
+    let lib = Library::builder()
+        .with_inputs((invoice, titles).into_typst())
+        .build();
+
+    let world = MinimalWorld::new("../", include_str!("../../res/template.typ"), lib);
+
+    let document = typst::compile(&world)
+        .output
+        .map_err(|e| ServiceError::Render(
+            format!("Error compiling typst template:\n{}", DisplayErrors(e))
+        ))?;
+
+    typst_pdf::pdf(&document, &PdfOptions::default())
+        .map_err(|e| ServiceError::Render(format!("Error exporting PDF:\n{}", DisplayErrors(e))))
+}
+
+/// Write the times matching a filter as a timesheet to CSV bytes, optionally only those billed on
+/// the invoice matching an identifier.
+pub fn render_timesheet(
+    conn: &mut SqliteConnection,
+    ident: Option<DocIdentifier>,
+    filter: TimeFilter,
+    titles: bool,
+    detailed: bool
+) -> ServiceResult<Document> {
+    let invoice = ident.map(|i| Invoice::select_by_identifier(i, conn))
+        .transpose()?;
+
+    let times = select_times(conn, filter, invoice.as_ref().map(|i| i.inv_num))?;
+    let mut warnings = vec![];
+
+    // Billed time is checked against the period when it's assigned, but not time from before that
+    // was checked, or that another program assigned.
+    if let Some(invoice) = &invoice {
+        let period = invoice.period().map_err(ServiceError::Invalid)?;
+        let outside = times.iter().filter(|t| !period.contains(*t.time_start)).count();
+
+        if outside > 0 {
+            warnings.push(format!(
+                "{outside} of the entries on invoice {} are outside its period {period}, run \
+                    doctor to list them",
+                invoice.number()
+            ));
+        }
+    }
+
+    let file_name = match &invoice {
+        Some(invoice) => format!("{}-timesheet-{}.csv", invoice.inv_month, invoice.number()),
+        None => format!("timesheet-{}.csv", Date::now()),
+    };
+
+    Ok(Document {
+        file_name,
+        bytes: timesheet_csv(times, &ticket_titles(conn, titles)?, detailed)?,
+        warnings,
+    })
+}
+
+/// Titles for labelling tickets in documents, or none when they should be shown by key.
+pub fn ticket_titles(conn: &mut SqliteConnection, titles: bool) -> ServiceResult<TicketTitles> {
+    if titles {
+        TicketTitles::load(conn)
+            .map_err(ServiceError::database("Error retrieving ticket titles from database"))
+    } else {
+        Ok(TicketTitles::default())
+    }
+}
+
+/// Write time entries as a timesheet to CSV bytes, labelling tickets with the given titles. A
+/// detailed timesheet also has the notes of each entry.
+pub fn timesheet_csv(
+    times: Vec<TimeWithTickets>,
+    titles: &TicketTitles,
+    detailed: bool
+) -> ServiceResult<Vec<u8>> {
+    let error = |e| ServiceError::Render(format!("Error writing time entry to timesheet:\n{e}"));
+
+    let mut writer = WriterBuilder::new()
+        .quote_style(QuoteStyle::Always)
+        .has_headers(false)
+        .from_writer(vec![]);
+
+    // Just manually write the headers so that they are pretty.
+    let headers = ["Start", "End", "Duration", "Tickets", "Description", "Notes"];
+    writer.write_record(if detailed { &headers[..] } else { &headers[..5] })
+        .map_err(error)?;
+
+    // Timesheets back up invoices, so time that's never charged for is left out.
+    for time in times.into_iter().filter(|t| t.time_billable) {
+        let tickets = titles.join(&time.tickets);
+
+        if detailed {
+            writer.serialize(CsvDetailedTime { tickets, ..CsvDetailedTime::from(time) })
+        } else {
+            writer.serialize(CsvTime { tickets, ..CsvTime::from(time) })
+        }.map_err(error)?;
+    }
+
+    writer.into_inner()
+        .map_err(|e| ServiceError::Render(format!("Error writing timesheet:\n{e}")))
+}
@@ -0,0 +1,52 @@
+use std::{error::Error, fmt::{self, Display, Formatter}};
+
+/// Everything that can go wrong in the service layer, so that callers can react to the kind of
+/// failure rather than just reporting it.
+#[derive(Debug)]
+pub enum ServiceError {
+    /// A referenced record doesn't exist.
+    NotFound(String),
+    /// An identifier matched more than one record.
+    Ambiguous(String),
+    /// Input that breaks a rule, which the caller can fix.
+    Invalid(String),
+    /// A document couldn't be produced.
+    Render(String),
+    /// A file couldn't be read or written.
+    File(String),
+    Database {
+        context: &'static str,
+        source: diesel::result::Error,
+    },
+}
+
+pub type ServiceResult<T> = Result<T, ServiceError>;
+
+impl ServiceError {
+    /// Wrap a database error with what was being attempted, for use with `map_err`.
+    pub fn database(context: &'static str) -> impl FnOnce(diesel::result::Error) -> ServiceError {
+        move |source| ServiceError::Database { context, source }
+    }
+}
+
+impl Display for ServiceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::NotFound(message)
+                | ServiceError::Ambiguous(message)
+                | ServiceError::Invalid(message)
+                | ServiceError::Render(message)
+                | ServiceError::File(message) => write!(f, "{message}"),
+            ServiceError::Database { context, source } => write!(f, "{context}:\n{source}"),
+        }
+    }
+}
+
+impl Error for ServiceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ServiceError::Database { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use clap::ValueEnum;
+use diesel::{insert_into, prelude::*, update};
+
+use crate::orm::insert::{ActivityChanges, NewActivity, NewInvoice};
+use crate::orm::model::{Invoice, InvoiceActivity, Project, Recipient, Time};
+use crate::orm::query::TimeWithTickets;
+use crate::orm::ticket::Ticket;
+use crate::service::error::{ServiceError, ServiceResult};
+use crate::service::numbering::next_reference;
+use crate::util::date::{Month, Period};
+
+/// How unbilled time is grouped into the activities of an invoice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupRule {
+    Project,
+    Ticket,
+    #[value(alias = "description")]
+    Desc,
+}
+
+
+/// An activity that hasn't been inserted yet, along with the time entries that will be assigned to
+/// it.
+#[derive(Debug)]
+pub struct DraftActivity {
+    pub desc: String,
+    pub times: Vec<TimeWithTickets>,
+}
+
+impl DraftActivity {
+    pub fn dur(&self) -> f64 {
+        self.times.iter()
+            .flat_map(|t| t.time_dur)
+            .sum()
+    }
+}
+
+/// Group the unbilled time of a period into the activities an invoice for it would have.
+pub fn draft_activities(
+    conn: &mut SqliteConnection,
+    period: &Period,
+    recip_id: &str,
+    rule: GroupRule
+) -> ServiceResult<Vec<DraftActivity>> {
+    use crate::orm::schema::{recipient, time};
+
+    Recipient::query()
+        .filter(recipient::recip_id.eq(recip_id))
+        .first(conn)
+        .optional()
+        .map_err(ServiceError::database("Error retrieving recipient from database"))?
+        .ok_or_else(|| {
+            ServiceError::NotFound(format!("No recipient exists with id '{recip_id}'"))
+        })?;
+
+    let times = TimeWithTickets::from_query(
+        Time::query()
+            .filter(time::act_num.is_null())
+            .filter(time::time_billable.eq(true))
+            .filter(time::time_start.ge(period.start()))
+            .filter(time::time_start.lt(period.end()))
+            .order(time::time_start),
+        conn
+    ).map_err(ServiceError::database("Error retrieving unbilled times from database"))?;
+
+    if times.is_empty() {
+        Err(ServiceError::Invalid(format!("No unbilled time entries found for {period}")))?
+    }
+
+    let projects: HashMap<String, String> = Project::query()
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))?
+        .into_iter()
+        .map(|p| (p.proj_key, p.proj_name))
+        .collect();
+
+    Ok(group_times(times, rule, &projects))
+}
+
+/// The number following the highest existing invoice number.
+pub fn next_invoice_num(conn: &mut SqliteConnection) -> ServiceResult<i32> {
+    use crate::orm::schema::invoice;
+
+    Ok(
+        invoice::table
+            .select(diesel::dsl::max(invoice::inv_num))
+            .first::<Option<i32>>(conn)
+            .map_err(ServiceError::database("Error retrieving invoice numbers from database"))?
+            .map_or(1, |n| n + 1)
+    )
+}
+
+/// An invoice for a period, which goes by the period's first month. Only periods other than a whole
+/// month are stored with the invoice.
+pub fn new_invoice(inv_num: i32, period: &Period, recip_id: String) -> NewInvoice {
+    NewInvoice {
+        inv_num,
+        inv_month: *Month::containing(period.first()),
+        recip_id,
+        inv_created: None,
+        inv_start: period.month().is_none().then(|| period.first()),
+        inv_end: period.month().is_none().then(|| period.last()),
+    }
+}
+
+/// Insert an invoice with an activity for each draft, assigning the drafted times to them. The
+/// invoice is numbered under the recipient's numbering scheme, if there is one.
+pub fn create_invoice(
+    conn: &mut SqliteConnection,
+    invoice: NewInvoice,
+    uprice: f64,
+    drafts: Vec<DraftActivity>
+) -> ServiceResult<Invoice> {
+    use crate::orm::schema::{invoice, invoice_activity, time};
+
+    let reference = next_reference(conn, &invoice.recip_id, &Month::containing(invoice.inv_month))?;
+
+    let created = insert_into(invoice::table)
+        .values((
+            invoice,
+            invoice::inv_ref.eq(reference.as_ref().map(|r| &r.inv_ref)),
+            invoice::inv_series.eq(reference.as_ref().map(|r| &r.inv_series)),
+            invoice::inv_seq.eq(reference.as_ref().map(|r| r.inv_seq)),
+        ))
+        .returning(Invoice::as_returning())
+        .get_result(conn)
+        .map_err(ServiceError::database("Error inserting invoice into database"))?;
+    let inv_num = created.inv_num;
+
+    for draft in drafts {
+        let act_num: i32 = NewActivity {
+            inv_num,
+            act_desc: draft.desc,
+            act_uprice: uprice,
+        }.insert_into(invoice_activity::table)
+            .returning(invoice_activity::act_num)
+            .get_result(conn)
+            .map_err(ServiceError::database("Error inserting activity into database"))?;
+
+        update(time::table)
+            .filter(time::time_id.eq_any(draft.times.iter().map(|t| t.time_id)))
+            .set(time::act_num.eq(act_num))
+            .execute(conn)
+            .map_err(ServiceError::database("Error assigning times to activity"))?;
+    }
+
+    Ok(created)
+}
+
+/// Change the description or unit price of an invoiced activity.
+pub fn edit_activity(
+    conn: &mut SqliteConnection,
+    act_num: i32,
+    changes: ActivityChanges
+) -> ServiceResult<InvoiceActivity> {
+    use crate::orm::schema::invoice_activity;
+
+    if changes == ActivityChanges::default() {
+        Err(ServiceError::Invalid("Nothing to change".to_owned()))?
+    }
+
+    update(invoice_activity::table.find(act_num))
+        .set(changes)
+        .returning(InvoiceActivity::as_returning())
+        .get_result(conn)
+        .optional()
+        .map_err(ServiceError::database("Error updating activity in database"))?
+        .ok_or_else(|| ServiceError::NotFound(format!("No activity exists with number {act_num}")))
+}
+
+fn group_times(
+    times: Vec<TimeWithTickets>,
+    rule: GroupRule,
+    projects: &HashMap<String, String>
+) -> Vec<DraftActivity> {
+    // Grouping keys are sorted so that the resulting activity numbers are stable between runs.
+    let mut groups: BTreeMap<String, DraftActivity> = BTreeMap::new();
+
+    for time in times {
+        let (key, desc) = match rule {
+            GroupRule::Project => match time.tickets.iter().min() {
+                Some(ticket) => (
+                    ticket.proj_key.clone(),
+                    projects.get(&ticket.proj_key)
+                        .cloned()
+                        .unwrap_or_else(|| ticket.proj_key.clone())
+                ),
+                // Entries without tickets can't be attributed to a project, so they are grouped by
+                // description instead. The leading character sorts them after any ticket keys.
+                None => (format!("~{}", time.time_desc), time.time_desc.clone()),
+            },
+            GroupRule::Ticket if time.tickets.is_empty() => (
+                format!("~{}", time.time_desc),
+                time.time_desc.clone()
+            ),
+            GroupRule::Ticket => (
+                time.tickets.iter()
+                    .collect::<BTreeSet<&Ticket>>()
+                    .into_iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                time.time_desc.clone()
+            ),
+            GroupRule::Desc => (time.time_desc.clone(), time.time_desc.clone()),
+        };
+
+        groups.entry(key)
+            .or_insert_with(|| DraftActivity { desc, times: vec![] })
+            .times
+            .push(time);
+    }
+
+    groups.into_values().collect()
+}
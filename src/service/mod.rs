@@ -0,0 +1,13 @@
+pub mod audit;
+pub mod backup;
+pub mod budget;
+pub mod doctor;
+pub mod document;
+pub mod error;
+pub mod invoice;
+pub mod numbering;
+pub mod project;
+pub mod recipient;
+pub mod ticket;
+pub mod time;
+pub mod timer;
@@ -0,0 +1,286 @@
+use std::{fmt::{self, Display, Formatter}, ops::RangeInclusive, str::FromStr};
+
+use chrono::Datelike;
+use diesel::{delete, insert_into, prelude::*};
+
+use crate::orm::model::Scheme;
+use crate::service::error::{ServiceError, ServiceResult};
+use crate::util::date::Month;
+
+/// Part of an invoice number format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Text(String),
+    Year,
+    Month,
+    Recip,
+    /// The sequence number, zero-padded to a width.
+    Seq(usize),
+}
+
+/// A format for invoice numbers such as `INV-{year}-{seq:4}`, which has the placeholders `{year}`
+/// and `{month}` of the invoice's month, `{recip}` for the recipient's id and `{seq}` for the
+/// sequence number, optionally zero-padded with `{seq:N}`. The sequence restarts whenever the rest
+/// of the number changes, so a format with the year in it is numbered from 1 each year.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    tokens: Vec<Token>,
+}
+
+impl NumberFormat {
+    /// The series an invoice is numbered in, which is the number with the sequence left out.
+    pub fn series(&self, month: &Month, recip_id: &str) -> String {
+        self.render(month, recip_id, None)
+    }
+
+    pub fn number(&self, month: &Month, recip_id: &str, seq: i32) -> String {
+        self.render(month, recip_id, Some(seq))
+    }
+
+    /// Whether a value could be a number in this format, for any month and recipient.
+    pub fn matches(&self, value: &str) -> bool {
+        matches_tokens(&self.tokens, value)
+    }
+
+    fn render(&self, month: &Month, recip_id: &str, seq: Option<i32>) -> String {
+        self.tokens.iter()
+            .map(|token| match token {
+                Token::Text(text) => text.clone(),
+                Token::Year => month.year().to_string(),
+                Token::Month => format!("{:02}", month.month()),
+                Token::Recip => recip_id.to_owned(),
+                Token::Seq(width) => seq.map_or_else(
+                    || "{seq}".to_owned(),
+                    |seq| format!("{seq:0width$}")
+                ),
+            })
+            .collect()
+    }
+}
+
+fn matches_tokens(tokens: &[Token], value: &str) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return value.is_empty();
+    };
+
+    match token {
+        Token::Text(text) => value.strip_prefix(text.as_str())
+            .is_some_and(|value| matches_tokens(rest, value)),
+        Token::Year => digits_then(value, 4..=4, rest),
+        Token::Month => digits_then(value, 2..=2, rest),
+        // Sequence numbers outgrow their padding.
+        Token::Seq(width) => digits_then(value, (*width).max(1)..=value.len(), rest),
+        Token::Recip => (1..=value.len())
+            .filter(|&len| value.is_char_boundary(len))
+            .any(|len| matches_tokens(rest, &value[len..])),
+    }
+}
+
+/// Whether a value starts with a number of digits in a range, followed by the rest of a format.
+fn digits_then(value: &str, lens: RangeInclusive<usize>, rest: &[Token]) -> bool {
+    let digits = value.bytes().take_while(u8::is_ascii_digit).count();
+    lens.take_while(|&len| len <= digits).any(|len| matches_tokens(rest, &value[len..]))
+}
+
+impl FromStr for NumberFormat {
+    type Err = ServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| ServiceError::Invalid(format!(
+            "Invalid invoice number format '{s}', {reason}"
+        ));
+
+        let mut tokens = vec![];
+        let mut rest = s;
+
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..].find('}')
+                .ok_or_else(|| invalid("a placeholder isn't closed"))? + open;
+
+            if open > 0 {
+                tokens.push(Token::Text(rest[..open].to_owned()));
+            }
+
+            tokens.push(match &rest[open + 1..close] {
+                "year" => Token::Year,
+                "month" => Token::Month,
+                "recip" => Token::Recip,
+                "seq" => Token::Seq(0),
+                other => match other.strip_prefix("seq:").and_then(|w| w.parse().ok()) {
+                    Some(width) if width <= 10 => Token::Seq(width),
+                    _ => Err(invalid(&format!("unknown placeholder {{{other}}}")))?,
+                },
+            });
+
+            rest = &rest[close + 1..];
+        }
+
+        if !rest.is_empty() {
+            tokens.push(Token::Text(rest.to_owned()));
+        }
+
+        if tokens.iter().filter(|t| matches!(t, Token::Seq(_))).count() != 1 {
+            Err(invalid("it needs exactly one {seq}"))?
+        }
+
+        // Numbers name the generated files.
+        if tokens.iter().any(|t| matches!(t, Token::Text(text) if text.contains(['/', '\\']))) {
+            Err(invalid("numbers can't contain slashes"))?
+        }
+
+        let format = NumberFormat { tokens };
+
+        // Invoices are identified by number, month or formatted number, in that order, so a
+        // number like `2026-01` would always be taken for a month.
+        let sample = format.number(&Month::current(), "ACME", 1);
+        if sample.parse::<i32>().is_ok() || sample.parse::<Month>().is_ok() {
+            Err(invalid(&format!("numbers like {sample} can't be told apart from invoice ids")))?
+        }
+
+        Ok(format)
+    }
+}
+
+impl Display for NumberFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            match token {
+                Token::Text(text) => write!(f, "{text}")?,
+                Token::Year => write!(f, "{{year}}")?,
+                Token::Month => write!(f, "{{month}}")?,
+                Token::Recip => write!(f, "{{recip}}")?,
+                Token::Seq(0) => write!(f, "{{seq}}")?,
+                Token::Seq(width) => write!(f, "{{seq:{width}}}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The formatted number an invoice is issued with, and where it falls in its series.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub inv_ref: String,
+    pub inv_series: String,
+    pub inv_seq: i32,
+}
+
+/// Number invoices for a recipient with a format, or all invoices for recipients without a scheme
+/// of their own, replacing any scheme there already is.
+pub fn set_scheme(
+    conn: &mut SqliteConnection,
+    recip_id: Option<&str>,
+    format: &NumberFormat
+) -> ServiceResult<()> {
+    use crate::orm::schema::{recipient, scheme};
+
+    if let Some(recip_id) = recip_id {
+        let exists = recipient::table
+            .find(recip_id)
+            .count()
+            .get_result::<i64>(conn)
+            .map_err(ServiceError::database("Error retrieving recipients from database"))?;
+
+        if exists == 0 {
+            Err(ServiceError::Invalid(format!("No recipient exists with id '{recip_id}'")))?
+        }
+    }
+
+    remove_scheme(conn, recip_id).or_else(|e| match e {
+        ServiceError::NotFound(_) => Ok(()),
+        e => Err(e),
+    })?;
+
+    insert_into(scheme::table)
+        .values((scheme::recip_id.eq(recip_id), scheme::sch_format.eq(format.to_string())))
+        .execute(conn)
+        .map_err(ServiceError::database("Error inserting numbering scheme into database"))?;
+
+    Ok(())
+}
+
+pub fn remove_scheme(conn: &mut SqliteConnection, recip_id: Option<&str>) -> ServiceResult<()> {
+    use crate::orm::schema::scheme;
+
+    // The global scheme has no recipient, which `eq` can't match, so it's handled separately.
+    let deleted = match recip_id {
+        Some(recip_id) => delete(scheme::table)
+            .filter(scheme::recip_id.eq(recip_id))
+            .execute(conn),
+        None => delete(scheme::table)
+            .filter(scheme::recip_id.is_null())
+            .execute(conn),
+    }.map_err(ServiceError::database("Error deleting numbering scheme from database"))?;
+
+    if deleted == 0 {
+        Err(ServiceError::NotFound(match recip_id {
+            Some(recip_id) => format!("Recipient '{recip_id}' has no numbering scheme"),
+            None => "There is no global numbering scheme".to_owned(),
+        }))?
+    }
+
+    Ok(())
+}
+
+/// Every numbering scheme, the global one first.
+pub fn select_schemes(conn: &mut SqliteConnection) -> ServiceResult<Vec<Scheme>> {
+    use crate::orm::schema::scheme;
+
+    Scheme::query()
+        .order((scheme::recip_id.is_not_null(), scheme::recip_id))
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving numbering schemes from database"))
+}
+
+/// Make sure a value that isn't an invoice id or month fits a numbering scheme, before it's looked
+/// up as a formatted number. Otherwise it's more likely a mistyped id or month.
+pub fn check_reference(conn: &mut SqliteConnection, value: &str) -> ServiceResult<()> {
+    for scheme in select_schemes(conn)? {
+        if scheme.sch_format.parse::<NumberFormat>()?.matches(value) {
+            return Ok(());
+        }
+    }
+
+    Err(ServiceError::Invalid(format!(
+        "'{value}' isn't an invoice id, a month or a number of any numbering scheme"
+    )))
+}
+
+/// The number the next invoice for a recipient and month gets, under the recipient's scheme or
+/// else the global one. Without either, invoices only have their plain number. Sequences continue
+/// from the highest number issued in the series, so there are no gaps as long as invoices aren't
+/// deleted.
+pub fn next_reference(
+    conn: &mut SqliteConnection,
+    recip_id: &str,
+    month: &Month
+) -> ServiceResult<Option<Reference>> {
+    use crate::orm::schema::{invoice, scheme};
+
+    let mut schemes = Scheme::query()
+        .filter(scheme::recip_id.eq(recip_id).or(scheme::recip_id.is_null()))
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving numbering schemes from database"))?;
+    schemes.sort_by_key(|s| s.recip_id.is_none());
+
+    let Some(scheme) = schemes.into_iter().next() else {
+        return Ok(None);
+    };
+
+    let format: NumberFormat = scheme.sch_format.parse()?;
+    let inv_series = format.series(month, recip_id);
+
+    let inv_seq = invoice::table
+        .filter(invoice::inv_series.eq(&inv_series))
+        .select(diesel::dsl::max(invoice::inv_seq))
+        .first::<Option<i32>>(conn)
+        .map_err(ServiceError::database("Error retrieving invoice numbers from database"))?
+        .map_or(1, |n| n + 1);
+
+    Ok(Some(Reference {
+        inv_ref: format.number(month, recip_id, inv_seq),
+        inv_series,
+        inv_seq,
+    }))
+}
@@ -0,0 +1,74 @@
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::{insert_into, prelude::*, update};
+
+use crate::orm::insert::ProjectChanges;
+use crate::orm::model::Project;
+use crate::orm::ticket::Ticket;
+use crate::service::error::{ServiceError, ServiceResult};
+
+/// Add a project, whose key must not be taken yet.
+pub fn add_project(conn: &mut SqliteConnection, project: Project) -> ServiceResult<()> {
+    use crate::orm::schema::project;
+
+    let key = project.proj_key.clone();
+
+    insert_into(project::table)
+        .values(project)
+        .execute(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ServiceError::Invalid(format!("Project {key} already exists, edit it instead"))
+            },
+            e => ServiceError::database("Error inserting project into database")(e),
+        })?;
+
+    Ok(())
+}
+
+/// Change the name or billability of an existing project. Time already logged keeps its own
+/// billability.
+pub fn edit_project(
+    conn: &mut SqliteConnection,
+    proj_key: &str,
+    changes: ProjectChanges
+) -> ServiceResult<Project> {
+    use crate::orm::schema::project;
+
+    if changes == ProjectChanges::default() {
+        Err(ServiceError::Invalid("Nothing to change".to_owned()))?
+    }
+
+    update(project::table.find(proj_key))
+        .set(changes)
+        .returning(Project::as_returning())
+        .get_result(conn)
+        .optional()
+        .map_err(ServiceError::database("Error updating project in database"))?
+        .ok_or_else(|| ServiceError::NotFound(format!("No project exists with key '{proj_key}'")))
+}
+
+pub fn select_projects(conn: &mut SqliteConnection) -> ServiceResult<Vec<Project>> {
+    use crate::orm::schema::project;
+
+    Project::query()
+        .order(project::proj_key)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))
+}
+
+/// Keep only the tickets of projects that exist. Text like a branch name `release-2` looks like a
+/// ticket too, so tickets found in free-form text are checked against the projects before use.
+pub fn known_tickets(
+    conn: &mut SqliteConnection,
+    tickets: Vec<Ticket>
+) -> ServiceResult<Vec<Ticket>> {
+    use crate::orm::schema::project;
+
+    let known: Vec<String> = project::table
+        .filter(project::proj_key.eq_any(tickets.iter().map(|t| &t.proj_key)))
+        .select(project::proj_key)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))?;
+
+    Ok(tickets.into_iter().filter(|t| known.contains(&t.proj_key)).collect())
+}
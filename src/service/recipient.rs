@@ -0,0 +1,33 @@
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::{insert_into, prelude::*};
+
+use crate::orm::model::Recipient;
+use crate::service::error::{ServiceError, ServiceResult};
+
+/// Add a recipient, whose id must not be taken yet.
+pub fn add_recipient(conn: &mut SqliteConnection, recipient: Recipient) -> ServiceResult<()> {
+    use crate::orm::schema::recipient;
+
+    let id = recipient.recip_id.clone();
+
+    insert_into(recipient::table)
+        .values(recipient)
+        .execute(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ServiceError::Invalid(format!("Recipient '{id}' already exists"))
+            },
+            e => ServiceError::database("Error inserting recipient into database")(e),
+        })?;
+
+    Ok(())
+}
+
+pub fn select_recipients(conn: &mut SqliteConnection) -> ServiceResult<Vec<Recipient>> {
+    use crate::orm::schema::recipient;
+
+    Recipient::query()
+        .order(recipient::recip_id)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving recipients from database"))
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use diesel::dsl::sum;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::{insert_into, prelude::*, update};
+
+use crate::orm::insert::TicketChanges;
+use crate::orm::model::TicketDetails;
+use crate::orm::query::TicketWithTime;
+use crate::orm::ticket::Ticket;
+use crate::service::error::{ServiceError, ServiceResult};
+use crate::service::time::check_projects;
+
+/// Add details for a ticket, which must not have any yet.
+pub fn add_ticket(conn: &mut SqliteConnection, details: TicketDetails) -> ServiceResult<()> {
+    use crate::orm::schema::ticket;
+
+    let key = details.ticket();
+    check_projects(conn, std::slice::from_ref(&key))?;
+
+    insert_into(ticket::table)
+        .values(details)
+        .execute(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                ServiceError::Invalid(format!("Ticket {key} already exists, edit it instead"))
+            },
+            e => ServiceError::database("Error inserting ticket into database")(e),
+        })?;
+
+    Ok(())
+}
+
+/// Change some of the details of an existing ticket.
+pub fn edit_ticket(
+    conn: &mut SqliteConnection,
+    key: &Ticket,
+    changes: TicketChanges
+) -> ServiceResult<TicketDetails> {
+    use crate::orm::schema::ticket;
+
+    if changes == TicketChanges::default() {
+        Err(ServiceError::Invalid("Nothing to change".to_owned()))?
+    }
+
+    update(ticket::table)
+        .filter(ticket::proj_key.eq(&key.proj_key))
+        .filter(ticket::tick_num.eq(key.tick_num))
+        .set(changes)
+        .returning(TicketDetails::as_returning())
+        .get_result(conn)
+        .optional()
+        .map_err(ServiceError::database("Error updating ticket in database"))?
+        .ok_or_else(|| ServiceError::NotFound(format!("No ticket {key} exists, add it first")))
+}
+
+/// Select tickets with the hours logged against them, optionally only from one project or with
+/// one status.
+pub fn select_tickets(
+    conn: &mut SqliteConnection,
+    proj_key: Option<String>,
+    status: Option<String>
+) -> ServiceResult<Vec<TicketWithTime>> {
+    use crate::orm::schema::{ticket, ticket_time, time};
+
+    let mut query = TicketDetails::query()
+        .order((ticket::proj_key, ticket::tick_num))
+        .into_boxed();
+
+    if let Some(proj_key) = proj_key {
+        query = query.filter(ticket::proj_key.eq(proj_key));
+    }
+    if let Some(status) = status {
+        query = query.filter(ticket::tick_status.eq(status));
+    }
+
+    let tickets = query.load(conn)
+        .map_err(ServiceError::database("Error retrieving tickets from database"))?;
+
+    let logged: BTreeMap<(String, i32), f64> = ticket_time::table
+        .inner_join(time::table)
+        .group_by((ticket_time::proj_key, ticket_time::tick_num))
+        .select((ticket_time::proj_key, ticket_time::tick_num, sum(time::time_dur)))
+        .load::<(String, i32, Option<f64>)>(conn)
+        .map_err(ServiceError::database("Error retrieving logged time from database"))?
+        .into_iter()
+        .map(|(proj_key, tick_num, dur)| ((proj_key, tick_num), dur.unwrap_or_default()))
+        .collect();
+
+    Ok(tickets.into_iter()
+        .map(|details| TicketWithTime {
+            tick_logged: logged.get(&(details.proj_key.clone(), details.tick_num))
+                .copied()
+                .unwrap_or_default(),
+            details,
+        })
+        .collect())
+}
@@ -0,0 +1,273 @@
+use std::{collections::BTreeSet, iter};
+
+use diesel::{delete, insert_into, insert_or_ignore_into, prelude::*, update};
+
+use crate::orm::filter::TimeFilter;
+use crate::orm::insert::NewEntry;
+use crate::orm::model::{Invoice, Tag, TicketTime, Time, TimeTag};
+use crate::orm::query::{TimeWithTickets, in_invoice};
+use crate::orm::ticket::Ticket;
+use crate::service::error::{ServiceError, ServiceResult};
+
+/// Insert a time entry and its ticket relations. Anything that creates time should go through
+/// here so that it is validated the same way as `log`.
+pub fn log_time(conn: &mut SqliteConnection, entry: NewEntry) -> ServiceResult<Time> {
+    use crate::orm::schema::time;
+
+    let billable = validate_entry(conn, &entry)?;
+
+    let time = insert_into(time::table)
+        .values((entry.time, time::time_billable.eq(billable)))
+        .returning(Time::as_returning())
+        .get_result(conn)
+        .map_err(ServiceError::database("Error inserting time into database"))?;
+
+    insert_tickets(conn, time.time_id, entry.tickets)?;
+    insert_tags(conn, time.time_id, entry.tags)?;
+
+    Ok(time)
+}
+
+/// Replace an existing time entry and its ticket and tag relations, with the same validation as
+/// `log`.
+pub fn update_time(
+    conn: &mut SqliteConnection,
+    time_id: i32,
+    entry: NewEntry
+) -> ServiceResult<Time> {
+    use crate::orm::schema::{ticket_time, time, time_tag};
+
+    let billable = validate_entry(conn, &entry)?;
+
+    let time = update(time::table.find(time_id))
+        .set((
+            time::time_start.eq(entry.time.time_start),
+            time::time_end.eq(entry.time.time_end),
+            time::time_desc.eq(entry.time.time_desc),
+            time::act_num.eq(entry.time.act_num),
+            time::time_billable.eq(billable),
+            time::time_notes.eq(entry.time.time_notes),
+        ))
+        .returning(Time::as_returning())
+        .get_result(conn)
+        .optional()
+        .map_err(ServiceError::database("Error updating time in database"))?
+        .ok_or_else(|| not_found(time_id))?;
+
+    delete(ticket_time::table.filter(ticket_time::time_id.eq(time_id)))
+        .execute(conn)
+        .map_err(ServiceError::database("Error removing ticket-time relations from database"))?;
+
+    insert_tickets(conn, time_id, entry.tickets)?;
+
+    delete(time_tag::table.filter(time_tag::time_id.eq(time_id)))
+        .execute(conn)
+        .map_err(ServiceError::database("Error removing time tags from database"))?;
+
+    insert_tags(conn, time_id, entry.tags)?;
+
+    Ok(time)
+}
+
+/// Delete a time entry along with its ticket and tag relations.
+pub fn delete_time(conn: &mut SqliteConnection, time_id: i32) -> ServiceResult<()> {
+    use crate::orm::schema::{ticket_time, time, time_tag};
+
+    delete(ticket_time::table.filter(ticket_time::time_id.eq(time_id)))
+        .execute(conn)
+        .map_err(ServiceError::database("Error removing ticket-time relations from database"))?;
+
+    delete(time_tag::table.filter(time_tag::time_id.eq(time_id)))
+        .execute(conn)
+        .map_err(ServiceError::database("Error removing time tags from database"))?;
+
+    let deleted = delete(time::table.find(time_id))
+        .execute(conn)
+        .map_err(ServiceError::database("Error deleting time from database"))?;
+
+    if deleted == 0 {
+        Err(not_found(time_id))?
+    }
+
+    Ok(())
+}
+
+pub fn select_time(conn: &mut SqliteConnection, time_id: i32) -> ServiceResult<TimeWithTickets> {
+    use crate::orm::schema::time;
+
+    TimeWithTickets::from_query(Time::query().filter(time::time_id.eq(time_id)), conn)
+        .map_err(ServiceError::database("Error retrieving time from database"))?
+        .pop()
+        .ok_or_else(|| not_found(time_id))
+}
+
+/// Select the times matching a filter, optionally only those billed on an invoice.
+pub fn select_times(
+    conn: &mut SqliteConnection,
+    filter: TimeFilter,
+    inv_num: Option<i32>
+) -> ServiceResult<Vec<TimeWithTickets>> {
+    let mut query = filter.query();
+
+    if let Some(inv_num) = inv_num {
+        query = in_invoice(query, inv_num);
+    }
+
+    TimeWithTickets::from_query(query, conn)
+        .map_err(ServiceError::database("Error retrieving times from database"))
+}
+
+fn not_found(time_id: i32) -> ServiceError {
+    ServiceError::NotFound(format!("No time entry exists with id {time_id}"))
+}
+
+/// Validate an entry, returning whether it's billable.
+fn validate_entry(conn: &mut SqliteConnection, entry: &NewEntry) -> ServiceResult<bool> {
+    if entry.time.time_end <= entry.time.time_start {
+        Err(ServiceError::Invalid(format!(
+            "Time entry '{}' must end after it starts",
+            entry.time.time_desc
+        )))?
+    }
+
+    if let Some(tag) = entry.tags.iter().find(|t| !is_tag_name(t)) {
+        Err(ServiceError::Invalid(format!(
+            "Tag '{tag}' must be a word made of letters, digits, '_' or '-'"
+        )))?
+    }
+
+    check_projects(conn, &entry.tickets)?;
+
+    let billable = match entry.billable {
+        Some(billable) => billable,
+        None => billable_by_default(conn, &entry.tickets)?,
+    };
+
+    if !billable && let Some(act_num) = entry.time.act_num {
+        Err(ServiceError::Invalid(format!(
+            "Time entry '{}' is non-billable, so it can't be assigned to activity {act_num}",
+            entry.time.time_desc
+        )))?
+    }
+
+    if let Some(act_num) = entry.time.act_num {
+        check_period(conn, act_num, entry)?;
+    }
+
+    Ok(billable)
+}
+
+/// Check that an entry falls within the period of the invoice its activity is on, so that no
+/// invoice bills time from outside what it covers.
+fn check_period(conn: &mut SqliteConnection, act_num: i32, entry: &NewEntry) -> ServiceResult<()> {
+    use crate::orm::schema::{invoice, invoice_activity};
+
+    let invoice = Invoice::query()
+        .filter(invoice::inv_num.eq_any(
+            invoice_activity::table
+                .filter(invoice_activity::act_num.eq(act_num))
+                .select(invoice_activity::inv_num)
+        ))
+        .first(conn)
+        .optional()
+        .map_err(ServiceError::database("Error retrieving invoice from database"))?
+        .ok_or_else(|| {
+            ServiceError::NotFound(format!("No activity exists with number {act_num}"))
+        })?;
+
+    let period = invoice.period().map_err(ServiceError::Invalid)?;
+
+    if !period.contains(entry.time.time_start) {
+        Err(ServiceError::Invalid(format!(
+            "Time entry '{}' on {} is outside the period {period} of invoice {}, so it can't be \
+                assigned to activity {act_num}",
+            entry.time.time_desc,
+            entry.time.time_start.date(),
+            invoice.number()
+        )))?
+    }
+
+    Ok(())
+}
+
+/// Whether time with these tickets is billable when not stated, which is only when none of their
+/// projects are non-billable.
+pub fn billable_by_default(conn: &mut SqliteConnection, tickets: &[Ticket]) -> ServiceResult<bool> {
+    use crate::orm::schema::project;
+
+    let non_billable: i64 = project::table
+        .filter(project::proj_key.eq_any(tickets.iter().map(|t| &t.proj_key)))
+        .filter(project::proj_billable.eq(false))
+        .count()
+        .get_result(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))?;
+
+    Ok(non_billable == 0)
+}
+
+fn is_tag_name(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Check that the project of every ticket exists.
+pub fn check_projects(conn: &mut SqliteConnection, tickets: &[Ticket]) -> ServiceResult<()> {
+    use crate::orm::schema::project;
+
+    let proj_keys: BTreeSet<&String> = tickets.iter()
+        .map(|t| &t.proj_key)
+        .collect();
+
+    let existing: Vec<String> = project::table
+        .filter(project::proj_key.eq_any(&proj_keys))
+        .select(project::proj_key)
+        .load(conn)
+        .map_err(ServiceError::database("Error retrieving projects from database"))?;
+
+    if let Some(missing) = proj_keys.iter().find(|k| !existing.contains(k)) {
+        Err(ServiceError::Invalid(format!("No project exists with key '{missing}'")))?
+    }
+
+    Ok(())
+}
+
+fn insert_tickets(
+    conn: &mut SqliteConnection,
+    time_id: i32,
+    tickets: Vec<Ticket>
+) -> ServiceResult<()> {
+    use crate::orm::schema::ticket_time;
+
+    // Duplicates would violate the primary key, but a ticket listed twice isn't really an error.
+    let tickets: Vec<TicketTime> = tickets.into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .zip(iter::repeat(time_id))
+        .map(TicketTime::from)
+        .collect();
+
+    insert_into(ticket_time::table)
+        .values(tickets)
+        .execute(conn)
+        .map_err(ServiceError::database("Error inserting ticket-time relations into database"))?;
+
+    Ok(())
+}
+
+fn insert_tags(conn: &mut SqliteConnection, time_id: i32, tags: Vec<String>) -> ServiceResult<()> {
+    use crate::orm::schema::{tag, time_tag};
+
+    let tags: BTreeSet<String> = tags.into_iter().collect();
+
+    // Tags don't need to be declared beforehand, so any new ones are created here.
+    insert_or_ignore_into(tag::table)
+        .values(tags.iter().map(|t| Tag { tag_name: t.clone() }).collect::<Vec<_>>())
+        .execute(conn)
+        .map_err(ServiceError::database("Error inserting tags into database"))?;
+
+    insert_into(time_tag::table)
+        .values(tags.into_iter().map(|tag_name| TimeTag { tag_name, time_id }).collect::<Vec<_>>())
+        .execute(conn)
+        .map_err(ServiceError::database("Error inserting time tags into database"))?;
+
+    Ok(())
+}
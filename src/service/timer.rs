@@ -0,0 +1,137 @@
+use chrono::NaiveDateTime;
+use diesel::{delete, insert_or_ignore_into, prelude::*};
+
+use crate::orm::insert::{LoggedTime, NewEntry, NewTimer};
+use crate::orm::model::{Time, Timer, TimerTicket};
+use crate::orm::ticket::Ticket;
+use crate::service::error::{ServiceError, ServiceResult};
+use crate::service::time::{check_projects, log_time};
+
+pub fn running_timer(conn: &mut SqliteConnection) -> ServiceResult<Option<Timer>> {
+    Timer::query()
+        .first(conn)
+        .optional()
+        .map_err(ServiceError::database("Error retrieving timer from database"))
+}
+
+/// Tickets attached to the running timer, or waiting for the next one when none is running.
+pub fn timer_tickets(conn: &mut SqliteConnection) -> ServiceResult<Vec<Ticket>> {
+    Ok(
+        TimerTicket::query()
+            .load(conn)
+            .map_err(ServiceError::database("Error retrieving timer tickets from database"))?
+            .into_iter()
+            .map(Ticket::from)
+            .collect()
+    )
+}
+
+/// Attach tickets to the running timer, or to the next one started if none is running.
+pub fn attach_tickets(conn: &mut SqliteConnection, tickets: Vec<Ticket>) -> ServiceResult<()> {
+    use crate::orm::schema::timer_ticket;
+
+    check_projects(conn, &tickets)?;
+
+    // Tickets that are already attached are fine as they are.
+    insert_or_ignore_into(timer_ticket::table)
+        .values(tickets.into_iter().map(TimerTicket::from).collect::<Vec<_>>())
+        .execute(conn)
+        .map_err(ServiceError::database("Error inserting timer tickets into database"))?;
+
+    Ok(())
+}
+
+/// Start a timer, along with any tickets that were attached while no timer was running.
+pub fn start_timer(
+    conn: &mut SqliteConnection,
+    description: String,
+    tickets: Vec<Ticket>,
+    at: NaiveDateTime
+) -> ServiceResult<()> {
+    use crate::orm::schema::timer;
+
+    if let Some(running) = running_timer(conn)? {
+        Err(ServiceError::Invalid(format!(
+            "A timer is already running since {}: {}",
+            running.timer_start,
+            running.timer_desc
+        )))?
+    }
+
+    attach_tickets(conn, tickets)?;
+
+    NewTimer { timer_start: at, timer_desc: description }
+        .insert_into(timer::table)
+        .execute(conn)
+        .map_err(ServiceError::database("Error inserting timer into database"))?;
+
+    Ok(())
+}
+
+/// Stop the running timer and log it as an entry ending at `at`.
+pub fn stop_timer(conn: &mut SqliteConnection, at: NaiveDateTime) -> ServiceResult<Time> {
+    let timer = running_timer(conn)?
+        .ok_or_else(|| ServiceError::NotFound("No timer is running".to_owned()))?;
+
+    if at <= *timer.timer_start {
+        Err(ServiceError::Invalid(
+            "The timer has been running for less than a minute, so there is nothing to log"
+                .to_owned()
+        ))?
+    }
+
+    let tickets = timer_tickets(conn)?;
+
+    clear_timer(conn)?;
+
+    log_time(conn, NewEntry {
+        time: LoggedTime {
+            time_start: *timer.timer_start,
+            time_end: at,
+            time_desc: timer.timer_desc,
+            act_num: None,
+            time_notes: None,
+        },
+        tickets,
+        tags: vec![],
+        billable: None,
+    })
+}
+
+/// Remove the running timer and its tickets without logging anything.
+pub fn clear_timer(conn: &mut SqliteConnection) -> ServiceResult<()> {
+    use crate::orm::schema::{timer, timer_ticket};
+
+    delete(timer_ticket::table)
+        .execute(conn)
+        .map_err(ServiceError::database("Error removing timer tickets from database"))?;
+
+    delete(timer::table)
+        .execute(conn)
+        .map_err(ServiceError::database("Error removing timer from database"))?;
+
+    Ok(())
+}
+
+/// Log the running timer up to `at` and continue with a new timer for `tickets` from then on,
+/// returning the logged entry. A timer that only just started is restarted with the new
+/// tickets instead, without logging anything.
+pub fn split_timer(
+    conn: &mut SqliteConnection,
+    tickets: Vec<Ticket>,
+    at: NaiveDateTime
+) -> ServiceResult<Option<Time>> {
+    let timer = running_timer(conn)?
+        .ok_or_else(|| ServiceError::NotFound("No timer is running".to_owned()))?;
+
+    let logged = if *timer.timer_start < at {
+        Some(stop_timer(conn, at)?)
+    } else {
+        clear_timer(conn)?;
+        None
+    };
+
+    start_timer(conn, timer.timer_desc, tickets, at)?;
+
+    Ok(logged)
+}
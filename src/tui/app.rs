@@ -0,0 +1,302 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, NaiveDate, NaiveTime};
+
+use crate::orm::filter::TimeFilter;
+use crate::orm::insert::{LoggedTime, NewEntry};
+use crate::orm::query::{InvoiceWithActivities, TimeWithTickets};
+use crate::orm::ticket::Ticket;
+use crate::util::date::{Date, Month, Week};
+
+/// Keys the interface responds to, independent of the terminal backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Esc,
+    Backspace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+/// The part of an entry being edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Description,
+    Range,
+    Tickets,
+    Activity,
+}
+
+impl Field {
+    pub fn label(self) -> &'static str {
+        match self {
+            Field::Description => "Description",
+            Field::Range => "Range (HH:MM-HH:MM)",
+            Field::Tickets => "Tickets",
+            Field::Activity => "Activity (blank to unassign)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Browse,
+    Edit { field: Field, input: String },
+}
+
+/// Work for whoever is running the interface, since the state machine doesn't touch the database.
+#[derive(Debug)]
+pub enum Effect {
+    /// Load the entries for `App::filter` and the invoices for `App::month` into `App::load`.
+    Reload,
+    /// Replace an entry, then reload.
+    Save { time_id: i32, entry: NewEntry },
+    Quit,
+}
+
+#[derive(Debug)]
+pub struct App {
+    pub period: Period,
+    pub date: NaiveDate,
+    pub times: Vec<TimeWithTickets>,
+    pub invoices: Vec<InvoiceWithActivities>,
+    pub selected: usize,
+    pub mode: Mode,
+    pub message: Option<String>,
+}
+
+impl App {
+    pub fn new(date: NaiveDate) -> App {
+        App {
+            period: Period::Week,
+            date,
+            times: vec![],
+            invoices: vec![],
+            selected: 0,
+            mode: Mode::Browse,
+            message: None,
+        }
+    }
+
+    /// The first and last day shown.
+    pub fn days(&self) -> (NaiveDate, NaiveDate) {
+        match self.period {
+            Period::Day => (self.date, self.date),
+            Period::Week => {
+                let week = Week::containing(self.date);
+                (*week, *week + Days::new(6))
+            },
+        }
+    }
+
+    /// Selects the entries that are shown.
+    pub fn filter(&self) -> TimeFilter {
+        let (from, to) = self.days();
+
+        TimeFilter {
+            from: Some(Date::from(from)),
+            to: Some(Date::from(to)),
+            ..Default::default()
+        }
+    }
+
+    /// The month whose invoices are totalled alongside the entries.
+    pub fn month(&self) -> Month {
+        Month::containing(self.date)
+    }
+
+    pub fn load(&mut self, times: Vec<TimeWithTickets>, invoices: Vec<InvoiceWithActivities>) {
+        self.times = times;
+        self.invoices = invoices;
+        self.selected = self.selected.min(self.times.len().saturating_sub(1));
+    }
+
+    pub fn selected_time(&self) -> Option<&TimeWithTickets> {
+        self.times.get(self.selected)
+    }
+
+    pub fn handle(&mut self, key: Key) -> Option<Effect> {
+        match &self.mode {
+            Mode::Browse => self.browse(key),
+            Mode::Edit { .. } => self.edit(key),
+        }
+    }
+
+    fn browse(&mut self, key: Key) -> Option<Effect> {
+        self.message = None;
+
+        match key {
+            Key::Char('q') | Key::Esc => return Some(Effect::Quit),
+            Key::Char('j') | Key::Down => {
+                self.selected = (self.selected + 1).min(self.times.len().saturating_sub(1));
+            },
+            Key::Char('k') | Key::Up => self.selected = self.selected.saturating_sub(1),
+            Key::Char('h') | Key::Left => return self.move_date(-1),
+            Key::Char('l') | Key::Right => return self.move_date(1),
+            Key::Char('t') => {
+                self.date = *Date::now();
+                return Some(self.change_period());
+            },
+            Key::Char('v') => {
+                self.period = match self.period {
+                    Period::Day => Period::Week,
+                    Period::Week => Period::Day,
+                };
+                return Some(self.change_period());
+            },
+            Key::Char('e') => self.start_edit(Field::Description),
+            Key::Char('r') => self.start_edit(Field::Range),
+            Key::Char('T') => self.start_edit(Field::Tickets),
+            Key::Char('a') => self.start_edit(Field::Activity),
+            _ => {},
+        }
+
+        None
+    }
+
+    fn move_date(&mut self, steps: i64) -> Option<Effect> {
+        let days = match self.period {
+            Period::Day => steps,
+            Period::Week => steps * 7,
+        };
+
+        self.date = if days < 0 {
+            self.date - Days::new(days.unsigned_abs())
+        } else {
+            self.date + Days::new(days as u64)
+        };
+
+        Some(self.change_period())
+    }
+
+    fn change_period(&mut self) -> Effect {
+        self.selected = 0;
+        Effect::Reload
+    }
+
+    fn start_edit(&mut self, field: Field) {
+        let Some(time) = self.selected_time() else {
+            self.message = Some("No entry selected".to_owned());
+            return;
+        };
+
+        let input = match field {
+            Field::Description => time.time_desc.clone(),
+            Field::Range => format!(
+                "{}-{}",
+                time.time_start.format("%H:%M"),
+                time.time_end.format("%H:%M")
+            ),
+            Field::Tickets => time.tickets.iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            Field::Activity => time.act_num.map(|a| a.to_string()).unwrap_or_default(),
+        };
+
+        self.mode = Mode::Edit { field, input };
+    }
+
+    fn edit(&mut self, key: Key) -> Option<Effect> {
+        let Mode::Edit { field, input } = &mut self.mode else {
+            return None;
+        };
+
+        match key {
+            Key::Esc => self.mode = Mode::Browse,
+            Key::Char(c) => input.push(c),
+            Key::Backspace => {
+                input.pop();
+            },
+            Key::Enter => {
+                let (field, input) = (*field, input.clone());
+
+                match self.edited_entry(field, &input) {
+                    Ok(entry) => {
+                        self.mode = Mode::Browse;
+                        let time_id = self.selected_time()?.time_id;
+                        return Some(Effect::Save { time_id, entry });
+                    },
+                    // Stay in the editor so the input can be fixed.
+                    Err(e) => self.message = Some(e),
+                }
+            },
+            _ => {},
+        }
+
+        None
+    }
+
+    /// The selected entry with one field replaced by parsed input.
+    fn edited_entry(&self, field: Field, input: &str) -> Result<NewEntry, String> {
+        let time = self.selected_time().ok_or("No entry selected")?;
+
+        let mut entry = NewEntry {
+            time: LoggedTime {
+                time_start: *time.time_start,
+                time_end: *time.time_end,
+                time_desc: time.time_desc.clone(),
+                act_num: time.act_num,
+                time_notes: time.time_notes.clone(),
+            },
+            tickets: time.tickets.clone(),
+            tags: time.tags.clone(),
+            billable: Some(time.time_billable),
+        };
+
+        match field {
+            Field::Description => entry.time.time_desc = input.trim().to_owned(),
+            Field::Range => {
+                let (start, end) = input.split_once('-')
+                    .ok_or("Range doesn't match the format HH:MM-HH:MM")?;
+                let date = time.time_start.date();
+                entry.time.time_start = date.and_time(parse_time(start)?);
+                entry.time.time_end = date.and_time(parse_time(end)?);
+            },
+            Field::Tickets => {
+                entry.tickets = input.split([' ', ','])
+                    .filter(|t| !t.is_empty())
+                    .map(Ticket::from_str)
+                    .collect::<Result<_, _>>()?;
+            },
+            Field::Activity => {
+                entry.time.act_num = match input.trim() {
+                    "" => None,
+                    act_num => Some(
+                        act_num.parse()
+                            .map_err(|_| format!("'{act_num}' isn't an activity number"))?
+                    ),
+                };
+            },
+        }
+
+        Ok(entry)
+    }
+
+    /// Hours logged in the shown period.
+    pub fn total_hours(&self) -> f64 {
+        self.times.iter().flat_map(|t| t.time_dur).sum()
+    }
+
+    /// A heading for the shown period.
+    pub fn title(&self) -> String {
+        match self.period {
+            Period::Day => format!("{} {}", self.date.weekday(), Date::from(self.date)),
+            Period::Week => Week::containing(self.date).to_string(),
+        }
+    }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .map_err(|_| format!("'{}' isn't a time in the format HH:MM", s.trim()))
+}
@@ -0,0 +1,124 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+
+use crate::tui::app::{App, Mode, Period};
+
+const HELP: &str = "j/k select  h/l previous/next  v day/week  t today  \
+    e description  r range  T tickets  a activity  q quit";
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, footer] = Layout::vertical([Constraint::Min(0), Constraint::Length(3)])
+        .areas(frame.area());
+    let [entries, invoices] = Layout::horizontal([Constraint::Min(0), Constraint::Length(40)])
+        .areas(main);
+
+    draw_entries(frame, app, entries);
+    draw_invoices(frame, app, invoices);
+    draw_footer(frame, app, footer);
+}
+
+fn draw_entries(frame: &mut Frame, app: &App, area: Rect) {
+    let mut previous_day = None;
+
+    let rows = app.times.iter().map(|time| {
+        let day = time.time_start.date();
+        // Only label the first entry of each day, so that the week reads as a grid of days.
+        let label = match previous_day {
+            Some(previous) if previous == day => String::new(),
+            _ => day.format("%a %d").to_string(),
+        };
+        previous_day = Some(day);
+
+        Row::new([
+            label,
+            format!("{}-{}", time.time_start.format("%H:%M"), time.time_end.format("%H:%M")),
+            format!("{:.1}", time.time_dur.unwrap_or_default()),
+            time.tickets.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", "),
+            time.time_desc.clone(),
+            time.act_num.map(|a| a.to_string()).unwrap_or_default(),
+        ])
+    });
+
+    let day_width = match app.period {
+        Period::Day => 0,
+        Period::Week => 6,
+    };
+
+    let table = Table::new(rows, [
+        Constraint::Length(day_width),
+        Constraint::Length(11),
+        Constraint::Length(5),
+        Constraint::Length(16),
+        Constraint::Min(10),
+        Constraint::Length(8),
+    ])
+        .header(
+            Row::new(["Day", "Time", "Hours", "Tickets", "Description", "Activity"])
+                .style(Style::new().add_modifier(Modifier::BOLD))
+        )
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(format!(" {} ({:.1}h) ", app.title(), app.total_hours())));
+
+    let mut state = TableState::default()
+        .with_selected((!app.times.is_empty()).then_some(app.selected));
+
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_invoices(frame: &mut Frame, app: &App, area: Rect) {
+    let mut lines = vec![];
+
+    for invoice in &app.invoices {
+        lines.push(Line::styled(
+            format!("#{} {}", invoice.number(), invoice.recipient.recip_name),
+            Style::new().add_modifier(Modifier::BOLD)
+        ));
+
+        for activity in &invoice.activities {
+            lines.push(Line::from(format!(
+                "{:>4} {:<18.18} {:>5.1}h {:>8.2}",
+                activity.act_num,
+                activity.act_desc,
+                activity.act_dur,
+                activity.act_price
+            )));
+        }
+
+        lines.push(Line::from(format!(
+            "{:>4} {:<18} {:>5.1}h {:>8.2}",
+            "",
+            "Total",
+            invoice.inv_dur,
+            invoice.inv_total
+        )));
+        lines.push(Line::default());
+    }
+
+    if app.invoices.is_empty() {
+        lines.push(Line::from("No invoices this month"));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(format!(" Invoices {} ", app.month()))),
+        area
+    );
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let text = match (&app.mode, &app.message) {
+        (Mode::Edit { field, input }, message) => {
+            let line = format!("{}: {input}_", field.label());
+            match message {
+                Some(message) => format!("{line}  ({message})"),
+                None => line,
+            }
+        },
+        (Mode::Browse, Some(message)) => message.clone(),
+        (Mode::Browse, None) => HELP.to_owned(),
+    };
+
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
@@ -1,7 +1,7 @@
 use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
 use typst::foundations::{Array, Datetime, Dict, IntoValue, Str, Value};
 
-use crate::orm::{model::Recipient, query::{ActivityWithTickets, InvoiceWithActivities}, ticket::Ticket};
+use crate::orm::{model::Recipient, query::{ActivityWithTickets, InvoiceWithActivities}, ticket::{Ticket, TicketTitles}};
 
 // IntoValue exists, but the result is always a Value (enum) and the orphan rule prevents me from
 // implementing it for chrono types.
@@ -34,12 +34,23 @@ impl IntoTypst for ActivityWithTickets {
     type Output = Dict;
 
     fn into_typst(self) -> Self::Output {
+        (self, &TicketTitles::default()).into_typst()
+    }
+}
+
+// Tickets are labelled with their titles, when there are any.
+impl IntoTypst for (ActivityWithTickets, &TicketTitles) {
+    type Output = Dict;
+
+    fn into_typst(self) -> Self::Output {
+        let (activity, titles) = self;
+
         [
-            (Str::from("desc"), Str::from(self.act_desc).into_value()),
-            (Str::from("uprice"), self.act_uprice.into_value()),
-            (Str::from("dur"), self.act_dur.into_value()),
-            (Str::from("tickets"), self.tickets.into_iter()
-                .map(|t| Value::Str(t.into_typst()))
+            (Str::from("desc"), Str::from(activity.act_desc).into_value()),
+            (Str::from("uprice"), activity.act_uprice.into_value()),
+            (Str::from("dur"), activity.act_dur.into_value()),
+            (Str::from("tickets"), activity.tickets.iter()
+                .map(|t| Value::Str(Str::from(titles.label(t))))
                 .collect::<Array>()
                 .into_value()
             )
@@ -51,16 +62,31 @@ impl IntoTypst for InvoiceWithActivities {
     type Output = Dict;
 
     fn into_typst(self) -> Self::Output {
+        (self, &TicketTitles::default()).into_typst()
+    }
+}
+
+impl IntoTypst for (InvoiceWithActivities, &TicketTitles) {
+    type Output = Dict;
+
+    fn into_typst(self) -> Self::Output {
+        let (invoice, titles) = self;
+
         [
-            (Str::from("num"), self.inv_num.into_value()),
-            (Str::from("month"), self.inv_month.into_typst().into_value()),
-            (Str::from("created"), self.inv_created.unwrap_or_default()
+            (Str::from("num"), invoice.inv_num.into_value()),
+            (Str::from("number"), Str::from(invoice.number()).into_value()),
+            (Str::from("month"), invoice.inv_month.into_typst().into_value()),
+            (Str::from("period"), [
+                (Str::from("start"), invoice.inv_period.first().into_typst().into_value()),
+                (Str::from("end"), invoice.inv_period.last().into_typst().into_value()),
+            ].into_iter().collect::<Dict>().into_value()),
+            (Str::from("created"), invoice.inv_created.unwrap_or_default()
                 .into_typst()
                 .into_value()
             ),
-            (Str::from("recipient"), self.recipient.into_typst().into_value()),
-            (Str::from("activities"), self.activities.into_iter()
-                .map(|t| t.into_typst().into_value())
+            (Str::from("recipient"), invoice.recipient.into_typst().into_value()),
+            (Str::from("activities"), invoice.activities.into_iter()
+                .map(|a| (a, titles).into_typst().into_value())
                 .collect::<Array>()
                 .into_value()
             )
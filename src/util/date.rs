@@ -1,17 +1,35 @@
 use std::{fmt::{self, Display, Formatter}, ops::Deref, str::FromStr};
 
-use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, Timelike};
+use chrono::{Datelike, Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
 use diesel::{Queryable, backend::Backend, deserialize::{self, FromSql}, expression::AsExpression, sqlite::Sqlite};
 use diesel::sql_types::{Date as SqlDate, Timestamp};
+use serde::{Serialize, Serializer};
 
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Month(NaiveDate);
 
 impl Month {
     pub fn current() -> Month {
-        let now = Local::now().date_naive();
-        Month(NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap())
+        Month::containing(Local::now().date_naive())
+    }
+
+    pub fn containing(date: NaiveDate) -> Month {
+        Month(NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap())
+    }
+
+    pub fn succ(&self) -> Month {
+        Month(self.0 + Months::new(1))
+    }
+
+    /// The first instant of the month, for comparison against timestamps.
+    pub fn start(&self) -> NaiveDateTime {
+        self.0.and_time(NaiveTime::MIN)
+    }
+
+    /// The first instant of the following month, exclusive.
+    pub fn end(&self) -> NaiveDateTime {
+        self.succ().start()
     }
 }
 
@@ -51,6 +69,14 @@ impl Display for Month {
     }
 }
 
+impl Serialize for Month {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 impl AsExpression<SqlDate> for Month {
     type Expression = <NaiveDate as AsExpression<SqlDate>>::Expression;
 
@@ -73,6 +99,94 @@ impl FromSql<SqlDate, Sqlite> for Month {
     }
 }
 
+/// The days an invoice bills for, from the first to the last inclusive. Most invoices cover a
+/// single month, but any run of days can be billed, such as a fortnight or a quarter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Period {
+    first: NaiveDate,
+    last: NaiveDate,
+}
+
+impl Period {
+    pub fn new(first: NaiveDate, last: NaiveDate) -> Result<Period, String> {
+        if last < first {
+            Err(format!("Period can't end on {last} before it starts on {first}"))?
+        }
+
+        Ok(Period { first, last })
+    }
+
+    pub fn first(&self) -> NaiveDate {
+        self.first
+    }
+
+    pub fn last(&self) -> NaiveDate {
+        self.last
+    }
+
+    /// The first instant of the period, for comparison against timestamps.
+    pub fn start(&self) -> NaiveDateTime {
+        self.first.and_time(NaiveTime::MIN)
+    }
+
+    /// The first instant after the period, exclusive.
+    pub fn end(&self) -> NaiveDateTime {
+        (self.last + Days::new(1)).and_time(NaiveTime::MIN)
+    }
+
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        self.start() <= at && at < self.end()
+    }
+
+    /// The month the period covers exactly, if it's a single whole month.
+    pub fn month(&self) -> Option<Month> {
+        let month = Month::containing(self.first);
+        (Period::from(month.clone()) == *self).then_some(month)
+    }
+}
+
+impl From<Month> for Period {
+    fn from(value: Month) -> Self {
+        Period { first: value.0, last: value.succ().0 - Days::new(1) }
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    /// Either end can be a date or a month, where a month starts on its first day and ends on its
+    /// last. A single month or date is a period of its own.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, last) = s.split_once("..").unwrap_or((s, s));
+
+        let parse = |s: &str| match s.trim().parse::<Date>() {
+            Ok(date) => Ok(Period { first: date.0, last: date.0 }),
+            Err(_) => s.trim().parse::<Month>()
+                .map(Period::from)
+                .map_err(|_| format!("'{s}' isn't a date (YYYY-MM-DD) or month (YYYY-MM)")),
+        };
+
+        Period::new(parse(first)?.first, parse(last)?.last)
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.month() {
+            Some(month) => write!(f, "{month}"),
+            None => write!(f, "{}..{}", Date(self.first), Date(self.last)),
+        }
+    }
+}
+
+impl Serialize for Period {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Date(NaiveDate);
 
@@ -80,6 +194,22 @@ impl Date {
     pub fn now() -> Date {
         Date(Local::now().date_naive())
     }
+
+    /// The first instant of the day, for comparison against timestamps.
+    pub fn start(&self) -> NaiveDateTime {
+        self.0.and_time(NaiveTime::MIN)
+    }
+
+    /// The first instant of the following day, exclusive.
+    pub fn end(&self) -> NaiveDateTime {
+        (self.0 + Days::new(1)).and_time(NaiveTime::MIN)
+    }
+}
+
+impl From<NaiveDate> for Date {
+    fn from(value: NaiveDate) -> Self {
+        Date(value)
+    }
 }
 
 impl Default for Date {
@@ -119,6 +249,14 @@ impl Display for Date {
     }
 }
 
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer {
+        self.0.serialize(serializer)
+    }
+}
+
 impl AsExpression<SqlDate> for Date {
     type Expression = <NaiveDate as AsExpression<SqlDate>>::Expression;
 
@@ -141,6 +279,64 @@ impl FromSql<SqlDate, Sqlite> for Date {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Week(NaiveDate);
+
+impl Week {
+    pub fn containing(date: NaiveDate) -> Week {
+        Week(date.week(Weekday::Mon).first_day())
+    }
+
+    /// The first instant of the week (Monday), for comparison against timestamps.
+    pub fn start(&self) -> NaiveDateTime {
+        self.0.and_time(NaiveTime::MIN)
+    }
+
+    /// The first instant of the following week, exclusive.
+    pub fn end(&self) -> NaiveDateTime {
+        (self.0 + Days::new(7)).and_time(NaiveTime::MIN)
+    }
+}
+
+impl Deref for Week {
+    type Target = NaiveDate;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromStr for Week {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (year, week) = s.split_once("-W")
+            .ok_or("Week doesn't match the format YYYY-Www")?;
+
+        let year = year.parse()
+            .map_err(|e| format!("Error parsing week year:\n{e}"))?;
+        let week = week.parse()
+            .map_err(|e| format!("Error parsing week number:\n{e}"))?;
+
+        Ok(Week(
+            NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+                .ok_or("Week number is out of range")?
+        ))
+    }
+}
+
+impl Display for Week {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let week = self.0.iso_week();
+        write!(
+            f,
+            "{:0>4}-W{:0>2}",
+            week.year(),
+            week.week()
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, AsExpression)]
 #[diesel(sql_type = SqlDate)]
 pub struct DateTime(NaiveDateTime);
@@ -153,6 +349,24 @@ impl Deref for DateTime {
     }
 }
 
+impl From<NaiveDateTime> for DateTime {
+    fn from(value: NaiveDateTime) -> Self {
+        DateTime(value)
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Accept the format that DateTime is displayed with, as well as ISO 8601.
+        ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"].iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(s.trim(), f).ok())
+            .map(DateTime)
+            .ok_or_else(|| format!("Error parsing date and time: '{s}'"))
+    }
+}
+
 impl Display for DateTime {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
@@ -167,6 +381,14 @@ impl Display for DateTime {
     }
 }
 
+impl Serialize for DateTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer {
+        self.0.serialize(serializer)
+    }
+}
+
 impl Queryable<Timestamp, Sqlite> for DateTime {
     type Row = NaiveDateTime;
 
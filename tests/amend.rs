@@ -0,0 +1,76 @@
+mod common;
+
+use clap::Parser;
+use time_tracker::cli::amend::amend;
+use time_tracker::cli::args::{Action, AmendArgs, CliArgs};
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::time::select_time;
+
+fn amend_args(args: &[&str]) -> AmendArgs {
+    let args = CliArgs::try_parse_from(["time-tracker", "amend"].iter().chain(args)).unwrap();
+    match args.action {
+        Action::Amend(amend_args) => amend_args,
+        action => panic!("Parsed as {action:?}"),
+    }
+}
+
+#[test]
+fn amend_changes_the_given_properties() {
+    let mut conn = common::seeded();
+
+    amend(&mut conn, amend_args(&["-t", "2", "2026-08-05", "ABC-20", "Fix login loop"])).unwrap();
+    let time = select_time(&mut conn, 2).unwrap();
+    assert_eq!(time.time_start.to_string(), "2026-08-05 13:00");
+    assert_eq!(time.time_desc, "Fix login loop");
+    assert_eq!(time.tickets.len(), 3);
+
+    // Without an id, the latest entry is amended.
+    amend(&mut conn, amend_args(&["10+2"])).unwrap();
+    let time = select_time(&mut conn, 5).unwrap();
+    assert_eq!(time.time_start.to_string(), "2026-09-02 10:00");
+    assert_eq!(time.time_dur, Some(2.0));
+}
+
+#[test]
+fn amend_deletes_entries() {
+    let mut conn = common::seeded();
+
+    amend(&mut conn, amend_args(&["-t", "2", "-d"])).unwrap();
+    assert!(matches!(select_time(&mut conn, 2), Err(ServiceError::NotFound(_))));
+
+    assert!(CliArgs::try_parse_from(["time-tracker", "amend", "-d", "Planning"]).is_err());
+}
+
+#[test]
+fn amend_adds_and_removes_tags() {
+    let mut conn = common::seeded();
+
+    amend(&mut conn, amend_args(&["-t", "2", "#review", "#urgent", "ABC-20"])).unwrap();
+    let time = select_time(&mut conn, 2).unwrap();
+    assert_eq!(time.tags, vec!["review", "urgent"]);
+    assert_eq!(time.tickets.len(), 3);
+
+    // Without an id, the latest entry is amended.
+    amend(&mut conn, amend_args(&["#urgent", "10+2"])).unwrap();
+    amend(&mut conn, amend_args(&["-#urgent", "Planned the next sprint"])).unwrap();
+    let time = select_time(&mut conn, 5).unwrap();
+    assert!(time.tags.is_empty());
+    assert_eq!(time.time_dur, Some(2.0));
+    assert_eq!(time.time_desc, "Planned the next sprint");
+}
+
+#[test]
+fn amend_removes_tickets() {
+    let mut conn = common::seeded();
+
+    amend(&mut conn, amend_args(&["-t", "2", "-ABC-12", "ABC-20"])).unwrap();
+    let tickets: Vec<String> = select_time(&mut conn, 2).unwrap()
+        .tickets
+        .iter()
+        .map(|t| t.to_string())
+        .collect();
+    assert_eq!(tickets, ["ABC-14", "ABC-20"]);
+
+    // Only a whole ticket is removed, anything else is taken as a description or rejected.
+    assert!(CliArgs::try_parse_from(["time-tracker", "amend", "-ABC-12x"]).is_err());
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use serde_json::{Value, json};
+use time_tracker::api::handler::{Request, handle};
+
+fn database() -> SqliteConnection {
+    let mut conn = common::database();
+    conn.batch_execute("INSERT INTO project (proj_key, proj_name) VALUES ('ABC', 'Alpha')").unwrap();
+    conn
+}
+
+fn request(method: &str, url: &str, body: Value) -> Request {
+    Request {
+        method: method.to_owned(),
+        url: url.to_owned(),
+        authorization: Some("Bearer secret".to_owned()),
+        body: if body.is_null() { String::new() } else { body.to_string() },
+    }
+}
+
+fn send(conn: &mut SqliteConnection, request: Request) -> (u16, Value) {
+    let response = handle(conn, Some("secret"), &request);
+    let body = if response.body.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&response.body).unwrap()
+    };
+    (response.status, body)
+}
+
+#[test]
+fn requests_need_the_token() {
+    let mut conn = database();
+
+    let mut anonymous = request("GET", "/projects", Value::Null);
+    anonymous.authorization = None;
+    assert_eq!(send(&mut conn, anonymous).0, 401);
+
+    let mut wrong = request("GET", "/projects", Value::Null);
+    wrong.authorization = Some("Bearer guess".to_owned());
+    assert_eq!(send(&mut conn, wrong).0, 401);
+
+    let (status, projects) = send(&mut conn, request("GET", "/projects", Value::Null));
+    assert_eq!(status, 200);
+    assert_eq!(
+        projects,
+        json!([{ "proj_key": "ABC", "proj_name": "Alpha", "proj_billable": true }])
+    );
+}
+
+#[test]
+fn time_entries_can_be_created_edited_and_deleted() {
+    let mut conn = database();
+
+    let (status, created) = send(&mut conn, request("POST", "/times", json!({
+        "start": "2026-09-02 09:00",
+        "end": "2026-09-02 10:30",
+        "desc": "Fix login",
+        "tickets": ["ABC-12"],
+    })));
+    assert_eq!(status, 201);
+    assert_eq!(created["time_dur"], json!(1.5));
+    assert_eq!(created["tickets"], json!(["ABC-12"]));
+
+    let url = format!("/times/{}", created["time_id"]);
+
+    let (status, updated) = send(&mut conn, request("PUT", &url, json!({
+        "start": "2026-09-02 09:00",
+        "end": "2026-09-02 11:00",
+        "desc": "Fix login timeout",
+    })));
+    assert_eq!(status, 200);
+    assert_eq!(updated["time_desc"], json!("Fix login timeout"));
+    assert_eq!(updated["tickets"], json!([]));
+
+    let (status, found) = send(&mut conn, request("GET", "/times?desc=login+timeout", Value::Null));
+    assert_eq!(status, 200);
+    assert_eq!(found.as_array().unwrap().len(), 1);
+
+    assert_eq!(send(&mut conn, request("DELETE", &url, Value::Null)).0, 204);
+    assert_eq!(send(&mut conn, request("GET", &url, Value::Null)).0, 404);
+    assert_eq!(send(&mut conn, request("DELETE", &url, Value::Null)).0, 404);
+}
+
+#[test]
+fn writes_are_validated_like_the_cli() {
+    let mut conn = database();
+
+    let (status, error) = send(&mut conn, request("POST", "/times", json!({
+        "start": "2026-09-02 09:00",
+        "end": "2026-09-02 10:00",
+        "desc": "Unknown project",
+        "tickets": ["XYZ-1"],
+    })));
+    assert_eq!(status, 400);
+    assert_eq!(error["error"], json!("No project exists with key 'XYZ'"));
+
+    let (status, _) = send(&mut conn, request("POST", "/times", json!({
+        "start": "2026-09-02 10:00",
+        "end": "2026-09-02 09:00",
+        "desc": "Backwards",
+    })));
+    assert_eq!(status, 400);
+
+    // Nothing from the failed requests was left behind.
+    let (_, times) = send(&mut conn, request("GET", "/times", Value::Null));
+    assert_eq!(times, json!([]));
+}
+
+#[test]
+fn unknown_routes_and_methods_are_rejected() {
+    let mut conn = database();
+
+    assert_eq!(send(&mut conn, request("GET", "/nothing", Value::Null)).0, 404);
+    assert_eq!(send(&mut conn, request("PATCH", "/times/1", Value::Null)).0, 405);
+    assert_eq!(send(&mut conn, request("GET", "/times?colour=red", Value::Null)).0, 400);
+}
+
+#[test]
+fn timesheet_is_returned_as_csv() {
+    let mut conn = database();
+
+    send(&mut conn, request("POST", "/times", json!({
+        "start": "2026-09-02 09:00",
+        "end": "2026-09-02 10:00",
+        "desc": "Standup",
+    })));
+
+    let response = handle(&mut conn, Some("secret"), &request("GET", "/timesheet", Value::Null));
+
+    assert_eq!(response.content_type, "text/csv");
+    assert_eq!(
+        String::from_utf8(response.body).unwrap(),
+        "\"Start\",\"End\",\"Duration\",\"Tickets\",\"Description\"\n\
+        \"2026-09-02 09:00\",\"2026-09-02 10:00\",\"1.0\",\"\",\"Standup\"\n"
+    );
+}
+
+#[test]
+fn projects_recipients_and_invoices_can_be_written() {
+    let mut conn = database();
+
+    let (status, project) = send(&mut conn, request("POST", "/projects", json!({
+        "key": "INT",
+        "name": "Internal",
+        "billable": false,
+    })));
+    assert_eq!(status, 201);
+    assert_eq!(project["proj_billable"], json!(false));
+    assert_eq!(send(&mut conn, request("POST", "/projects", json!({
+        "key": "INT",
+        "name": "Again",
+    }))).0, 400);
+
+    let (status, project) = send(&mut conn, request("PATCH", "/projects/ABC", json!({
+        "name": "Alphabet",
+    })));
+    assert_eq!((status, &project["proj_name"]), (200, &json!("Alphabet")));
+    assert_eq!(send(&mut conn, request("PATCH", "/projects/NOPE", json!({
+        "billable": false,
+    }))).0, 404);
+
+    let (status, _) = send(&mut conn, request("POST", "/recipients", json!({
+        "id": "ACME",
+        "name": "Acme Ltd",
+        "address": "Desert",
+    })));
+    assert_eq!(status, 201);
+
+    send(&mut conn, request("POST", "/times", json!({
+        "start": "2026-09-02 09:00",
+        "end": "2026-09-02 10:30",
+        "desc": "Fix login",
+        "tickets": ["ABC-12"],
+    })));
+
+    let (status, invoice) = send(&mut conn, request("POST", "/invoices", json!({
+        "period": "2026-09",
+        "recipient": "ACME",
+        "uprice": 80.0,
+    })));
+    assert_eq!(status, 201);
+    assert_eq!(invoice["inv_total"], json!(120.0));
+
+    // The time is billed now, so there's nothing left for another invoice.
+    assert_eq!(send(&mut conn, request("POST", "/invoices", json!({
+        "period": "2026-09",
+        "recipient": "ACME",
+        "uprice": 80.0,
+    }))).0, 400);
+
+    let url = format!("/activities/{}", invoice["activities"][0]["act_num"]);
+    let (status, activity) = send(&mut conn, request("PATCH", &url, json!({ "uprice": 90.0 })));
+    assert_eq!(status, 200);
+    assert_eq!(activity["act_price"], json!(135.0));
+    assert_eq!(send(&mut conn, request("PATCH", &url, json!({}))).0, 400);
+}
@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use time_tracker::cli::args::{EntryLabel, TimeProperty};
+
+#[test]
+fn time_properties_are_recognised_by_shape() {
+    assert!(matches!(TimeProperty::from_str("12"), Ok(TimeProperty::Activity(12))));
+    assert!(matches!(
+        TimeProperty::from_str("ABC-12"),
+        Ok(TimeProperty::Ticket(t)) if t.to_string() == "ABC-12"
+    ));
+    assert!(matches!(
+        TimeProperty::from_str("-ABC-12"),
+        Ok(TimeProperty::Untick(t)) if t.to_string() == "ABC-12"
+    ));
+    assert!(matches!(
+        TimeProperty::from_str("2026-09-02"),
+        Ok(TimeProperty::Date(d)) if d.to_string() == "2026-09-02"
+    ));
+    assert!(matches!(TimeProperty::from_str("9+1.5"), Ok(TimeProperty::Time(_))));
+    assert!(matches!(
+        TimeProperty::from_str("Fix login"),
+        Ok(TimeProperty::Desc(d)) if d == "Fix login"
+    ));
+}
+
+#[test]
+fn single_words_are_not_descriptions() {
+    assert!(TimeProperty::from_str("login").is_err());
+}
+
+#[test]
+fn tickets_in_descriptions_are_not_tickets() {
+    assert!(matches!(
+        TimeProperty::from_str("Fix ABC-12 again"),
+        Ok(TimeProperty::Desc(d)) if d == "Fix ABC-12 again"
+    ));
+}
+
+#[test]
+fn tags_are_added_and_removed_with_a_hash() {
+    assert!(matches!(TimeProperty::from_str("#review"), Ok(TimeProperty::Tag(t)) if t == "review"));
+    assert!(matches!(
+        TimeProperty::from_str("-#review"),
+        Ok(TimeProperty::Untag(t)) if t == "review"
+    ));
+    assert!(matches!(TimeProperty::from_str("#12"), Ok(TimeProperty::Tag(t)) if t == "12"));
+}
+
+#[test]
+fn log_labels_are_split_into_tickets_and_tags() {
+    let labels = ["ABC-12", "#meeting", "XYZ-3"].into_iter()
+        .map(EntryLabel::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let (tickets, tags) = EntryLabel::split(labels);
+    assert_eq!(tickets.iter().map(|t| t.to_string()).collect::<Vec<_>>(), ["ABC-12", "XYZ-3"]);
+    assert_eq!(tags, ["meeting"]);
+
+    assert!(EntryLabel::from_str("-#meeting").is_err());
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use chrono::NaiveDate;
+use diesel::connection::SimpleConnection;
+use diesel::SqliteConnection;
+use time_tracker::orm::insert::NewEntry;
+use time_tracker::service::audit::{
+    finish_command, last_undoable, select_changes, select_commands, start_command, undo
+};
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::time::{delete_time, log_time, select_time, update_time};
+
+fn entry(desc: &str) -> NewEntry {
+    let date = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+    let tickets = vec![common::ticket("ABC", 12)];
+    NewEntry { tags: vec!["review".to_owned()], ..common::entry(date, 9, 10, desc, tickets) }
+}
+
+/// Run something as a command, the way `main` does.
+fn command<T>(
+    conn: &mut SqliteConnection,
+    cmd_line: &str,
+    run: impl FnOnce(&mut SqliteConnection) -> T
+) -> T {
+    let cmd_id = start_command(conn, cmd_line).unwrap();
+    let result = run(conn);
+    finish_command(conn, cmd_id).unwrap();
+    result
+}
+
+fn undo_last(conn: &mut SqliteConnection) -> Result<usize, ServiceError> {
+    command(conn, "undo", |conn| {
+        let target = last_undoable(conn)?;
+        undo(conn, &target)
+    })
+}
+
+#[test]
+fn changes_are_recorded_against_their_command() {
+    let mut conn = common::seeded();
+
+    command(&mut conn, "list time", |_| ());
+    let time = command(&mut conn, "log", |conn| log_time(conn, entry("Review")).unwrap());
+
+    // Commands that didn't change anything aren't kept.
+    let commands = select_commands(&mut conn, 10).unwrap();
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].command.cmd_line, "log");
+    assert_eq!(commands[0].changes, 3);
+
+    let changes = select_changes(&mut conn, commands[0].command.cmd_id).unwrap();
+    let tables: Vec<(&str, &str)> = changes.iter()
+        .map(|c| (c.aud_table.as_str(), c.aud_action.as_str()))
+        .collect();
+    assert_eq!(tables, [("time", "insert"), ("ticket_time", "insert"), ("time_tag", "insert")]);
+    let new = changes[0].aud_new.as_deref().unwrap();
+    assert!(new.contains(&format!("\"time_id\":{}", time.time_id)));
+}
+
+#[test]
+fn undo_reverts_the_latest_commands_in_turn() {
+    let mut conn = common::seeded();
+
+    let time = command(&mut conn, "log", |conn| log_time(conn, entry("Review")).unwrap());
+    command(&mut conn, "amend", |conn| update_time(conn, time.time_id, entry("Reviewed")).unwrap());
+    command(&mut conn, "delete", |conn| delete_time(conn, 3).unwrap());
+
+    // The deleted entry comes back with its tickets.
+    undo_last(&mut conn).unwrap();
+    let restored = select_time(&mut conn, 3).unwrap();
+    assert_eq!(restored.time_desc, "Monthly report");
+    assert_eq!(restored.act_num, Some(3));
+    assert_eq!(restored.tickets, vec![common::ticket("XYZ", 3)]);
+
+    // Undos are skipped, so undoing again goes further back rather than redoing.
+    undo_last(&mut conn).unwrap();
+    assert_eq!(select_time(&mut conn, time.time_id).unwrap().time_desc, "Review");
+
+    undo_last(&mut conn).unwrap();
+    assert!(matches!(select_time(&mut conn, time.time_id), Err(ServiceError::NotFound(_))));
+
+    assert!(matches!(undo_last(&mut conn), Err(ServiceError::Invalid(_))));
+
+    let commands = select_commands(&mut conn, 10).unwrap();
+    assert_eq!(commands[0].command.cmd_undoes, Some(commands[5].command.cmd_id));
+    assert_eq!(commands[5].undone_by, Some(commands[0].command.cmd_id));
+}
+
+#[test]
+fn rows_changed_since_are_not_undone() {
+    let mut conn = common::seeded();
+
+    command(&mut conn, "amend", |conn| update_time(conn, 5, entry("Planning")).unwrap());
+    conn.batch_execute("UPDATE time SET time_desc = 'Changed elsewhere' WHERE time_id = 5")
+        .unwrap();
+
+    assert!(matches!(undo_last(&mut conn), Err(ServiceError::Invalid(_))));
+}
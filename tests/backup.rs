@@ -0,0 +1,117 @@
+mod common;
+
+use std::{env, fs, path::PathBuf, process, thread, time::Duration};
+
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use time_tracker::cli::args::BackupRestoreArgs;
+use time_tracker::cli::backup::{auto_backup, restore};
+use time_tracker::service::backup::{BackupKind, backup_dir, create_backup, database_path, list_backups};
+
+/// A seeded database written to a file, removed again with its backups when the test finishes.
+struct DatabaseFile(PathBuf);
+
+impl DatabaseFile {
+    fn new(name: &str) -> DatabaseFile {
+        let path = env::temp_dir().join(format!("time-tracker-{name}-{}.db", process::id()));
+        let file = DatabaseFile(path);
+        file.remove();
+
+        common::seeded()
+            .batch_execute(&format!("VACUUM INTO '{}'", file.url()))
+            .unwrap();
+
+        file
+    }
+
+    fn url(&self) -> String {
+        self.0.display().to_string()
+    }
+
+    fn connect(&self) -> SqliteConnection {
+        SqliteConnection::establish(&self.url()).unwrap()
+    }
+
+    fn remove(&self) {
+        let _ = fs::remove_file(&self.0);
+        let _ = fs::remove_dir_all(backup_dir(&self.0));
+    }
+}
+
+impl Drop for DatabaseFile {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+fn count_times(conn: &mut SqliteConnection) -> i64 {
+    use time_tracker::orm::schema::time;
+
+    time::table.count().get_result(conn).unwrap()
+}
+
+#[test]
+fn only_the_latest_automatic_backups_are_kept() {
+    let database = DatabaseFile::new("backup-prune");
+    let mut conn = database.connect();
+
+    create_backup(&mut conn, &database.0, BackupKind::Manual).unwrap();
+
+    for _ in 0..4 {
+        // Backups are named by the millisecond.
+        thread::sleep(Duration::from_millis(2));
+        auto_backup(&mut conn, &database.url(), 2).unwrap();
+    }
+
+    let backups = list_backups(&database.0).unwrap();
+    let kinds: Vec<BackupKind> = backups.iter().map(|b| b.kind).collect();
+    assert_eq!(kinds, [BackupKind::Manual, BackupKind::Auto, BackupKind::Auto]);
+    assert!(backups.windows(2).all(|b| b[0].created < b[1].created));
+
+    let mut backup = SqliteConnection::establish(&backups[2].path.display().to_string()).unwrap();
+    assert_eq!(count_times(&mut backup), count_times(&mut conn));
+}
+
+#[test]
+fn backups_can_be_turned_off() {
+    let database = DatabaseFile::new("backup-off");
+    let mut conn = database.connect();
+
+    auto_backup(&mut conn, &database.url(), 0).unwrap();
+    assert!(!backup_dir(&database.0).exists());
+
+    // In-memory databases have nothing to back up.
+    assert_eq!(database_path(":memory:"), None);
+    auto_backup(&mut common::seeded(), ":memory:", 10).unwrap();
+}
+
+#[test]
+fn restoring_keeps_the_replaced_database() {
+    let database = DatabaseFile::new("backup-restore");
+    let mut conn = database.connect();
+
+    let before = count_times(&mut conn);
+    let backup = create_backup(&mut conn, &database.0, BackupKind::Manual).unwrap();
+    conn.batch_execute("DELETE FROM time WHERE act_num IS NULL").unwrap();
+    let after = count_times(&mut conn);
+    drop(conn);
+
+    // A journal left over from the replaced database mustn't be applied to the restored one.
+    let wal = PathBuf::from(format!("{}-wal", database.url()));
+    fs::write(&wal, "stale").unwrap();
+
+    restore(&database.url(), BackupRestoreArgs { backup: backup.name, yes: true }).unwrap();
+    assert!(!wal.exists());
+    assert_eq!(count_times(&mut database.connect()), before);
+
+    let backups = list_backups(&database.0).unwrap();
+    let replaced = backups.iter().find(|b| b.kind == BackupKind::Restore).unwrap();
+    let mut replaced = SqliteConnection::establish(&replaced.path.display().to_string()).unwrap();
+    assert_eq!(count_times(&mut replaced), after);
+    assert_ne!(before, after);
+
+    // Only intact databases can be restored.
+    fs::write(&backups[0].path, "not a database").unwrap();
+    let args = BackupRestoreArgs { backup: backups[0].name.clone(), yes: true };
+    assert!(restore(&database.url(), args).is_err());
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use chrono::NaiveDate;
+use time_tracker::cli::args::{GroupRule, TimeFilter};
+use time_tracker::orm::insert::{NewEntry, ProjectChanges};
+use time_tracker::service::document::render_timesheet;
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::invoice::draft_activities;
+use time_tracker::service::project::edit_project;
+use time_tracker::service::time::{log_time, select_times};
+use time_tracker::util::date::Period;
+
+fn entry(day: u32, desc: &str, proj_key: &str, billable: Option<bool>) -> NewEntry {
+    let date = NaiveDate::from_ymd_opt(2026, 9, day).unwrap();
+    NewEntry { billable, ..common::entry(date, 9, 11, desc, vec![common::ticket(proj_key, 1)]) }
+}
+
+#[test]
+fn entries_default_to_their_projects() {
+    let mut conn = common::seeded();
+
+    assert!(log_time(&mut conn, entry(7, "Fix export", "ABC", None)).unwrap().time_billable);
+    assert!(!log_time(&mut conn, entry(7, "Team meeting", "INT", None)).unwrap().time_billable);
+
+    // The default can be overridden either way.
+    assert!(log_time(&mut conn, entry(8, "Onboarding", "INT", Some(true))).unwrap().time_billable);
+    assert!(!log_time(&mut conn, entry(8, "Rework", "ABC", Some(false))).unwrap().time_billable);
+
+    // Changing a project's default only affects time logged afterwards.
+    edit_project(&mut conn, "ABC", ProjectChanges {
+        proj_billable: Some(false),
+        ..Default::default()
+    }).unwrap();
+    assert!(!log_time(&mut conn, entry(9, "Goodwill fix", "ABC", None)).unwrap().time_billable);
+
+    let filter = TimeFilter { non_billable: true, ..Default::default() };
+    let descs: Vec<String> = select_times(&mut conn, filter, None).unwrap()
+        .into_iter()
+        .map(|t| t.time_desc)
+        .collect();
+    assert_eq!(descs, ["Team meeting", "Rework", "Goodwill fix"]);
+}
+
+#[test]
+fn non_billable_time_is_never_invoiced() {
+    let mut conn = common::seeded();
+
+    log_time(&mut conn, entry(7, "Team meeting", "INT", None)).unwrap();
+
+    let activities = draft_activities(
+        &mut conn,
+        &"2026-09".parse::<Period>().unwrap(),
+        "ACME",
+        GroupRule::Project
+    ).unwrap();
+    let descs: Vec<&str> = activities.iter()
+        .flat_map(|a| a.times.iter().map(|t| t.time_desc.as_str()))
+        .collect();
+    assert_eq!(descs, ["Plan next sprint"]);
+
+    let mut billed = entry(7, "Team meeting", "INT", None);
+    billed.time.act_num = Some(4);
+    assert!(matches!(log_time(&mut conn, billed), Err(ServiceError::Invalid(_))));
+}
+
+#[test]
+fn timesheets_leave_out_non_billable_time() {
+    let mut conn = common::seeded();
+
+    log_time(&mut conn, entry(7, "Team meeting", "INT", None)).unwrap();
+
+    let filter = TimeFilter { unbilled: true, ..Default::default() };
+    let csv = render_timesheet(&mut conn, None, filter, false, false).unwrap();
+
+    let csv = String::from_utf8(csv.bytes).unwrap();
+    assert!(csv.contains("Plan next sprint"));
+    assert!(!csv.contains("Team meeting"));
+}
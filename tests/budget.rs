@@ -0,0 +1,103 @@
+mod common;
+
+use chrono::NaiveDate;
+use diesel::connection::SimpleConnection;
+use time_tracker::cli::args::BudgetTarget;
+use time_tracker::service::budget::{budget_usage, remove_budget, set_budget};
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::time::log_time;
+
+fn target(s: &str) -> BudgetTarget {
+    s.parse().unwrap()
+}
+
+fn day(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+}
+
+#[test]
+fn usage_is_computed_from_logged_time() {
+    let mut conn = common::seeded();
+
+    set_budget(&mut conn, &target("ABC"), Some(4.0), None, None).unwrap();
+    set_budget(&mut conn, &target("ABC-12"), None, Some(300.0), Some(100.0)).unwrap();
+
+    let usage = budget_usage(&mut conn, None, day(9, 10)).unwrap();
+    assert_eq!(usage.len(), 2);
+
+    // Time with several tickets of the project only counts once.
+    let project = &usage[0];
+    assert_eq!(project.target(), target("ABC"));
+    assert_eq!(project.spent_hours, 3.0);
+    assert_eq!(project.used, 0.75);
+    assert_eq!(project.remaining_hours, 1.0);
+
+    // Only half an hour was logged in the last four weeks, so the last hour takes eight weeks.
+    assert_eq!(project.weekly_hours, 0.125);
+    assert_eq!(project.runs_out.as_ref().map(|d| **d), Some(day(11, 5)));
+
+    let ticket = &usage[1];
+    assert_eq!(ticket.target(), target("ABC-12"));
+    assert_eq!(ticket.spent_money, Some(300.0));
+    assert_eq!(ticket.used, 1.0);
+    assert!(ticket.runs_out.is_none());
+}
+
+#[test]
+fn logging_reports_crossed_thresholds() {
+    let mut conn = common::seeded();
+
+    set_budget(&mut conn, &target("ABC"), Some(4.0), None, None).unwrap();
+    set_budget(&mut conn, &target("XYZ"), Some(10.0), None, None).unwrap();
+
+    let tickets = vec![common::ticket("ABC", 14)];
+    let before = budget_usage(&mut conn, Some(&tickets), day(9, 10)).unwrap();
+    assert_eq!(before.len(), 1);
+
+    let mut entry = common::entry(day(9, 10), 9, 10, "Redirect tests", tickets.clone());
+    entry.time.time_end = day(9, 10).and_hms_opt(9, 30, 0).unwrap();
+    log_time(&mut conn, entry).unwrap();
+
+    let after = budget_usage(&mut conn, Some(&tickets), day(9, 10)).unwrap();
+    assert_eq!(after[0].crossed_since(&before[0]), Some(0.8));
+    assert_eq!(after[0].crossed_since(&after[0]), None);
+}
+
+#[test]
+fn budgets_are_replaced_and_removed_by_target() {
+    let mut conn = common::seeded();
+
+    set_budget(&mut conn, &target("ABC"), Some(4.0), None, None).unwrap();
+    set_budget(&mut conn, &target("ABC"), Some(8.0), None, None).unwrap();
+
+    let usage = budget_usage(&mut conn, None, day(9, 10)).unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].budget.bud_hours, Some(8.0));
+
+    // Only one budget per target fits in the table, whichever way it's inserted.
+    set_budget(&mut conn, &target("ABC-12"), Some(2.0), None, None).unwrap();
+    for duplicate in [
+        "INSERT INTO budget (proj_key, bud_hours) VALUES ('ABC', 1.0)",
+        "INSERT INTO budget (proj_key, tick_num, bud_hours) VALUES ('ABC', 12, 1.0)",
+    ] {
+        assert!(conn.batch_execute(duplicate).is_err());
+    }
+
+    remove_budget(&mut conn, &target("ABC")).unwrap();
+    assert_eq!(budget_usage(&mut conn, None, day(9, 10)).unwrap().len(), 1);
+    assert!(matches!(
+        remove_budget(&mut conn, &target("ABC")),
+        Err(ServiceError::NotFound(_))
+    ));
+}
+
+#[test]
+fn invalid_budgets_are_rejected() {
+    let mut conn = common::seeded();
+
+    let error = set_budget(&mut conn, &target("ABC"), None, Some(100.0), None).unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+
+    let error = set_budget(&mut conn, &target("NOPE"), Some(1.0), None, None).unwrap_err();
+    assert_eq!(error.to_string(), "No project exists with key 'NOPE'");
+}
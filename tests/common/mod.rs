@@ -0,0 +1,98 @@
+// Each test crate only uses some of these helpers.
+#![allow(dead_code)]
+
+use std::{env, fs, path::{Path, PathBuf}, process::{self, Command}};
+
+use chrono::NaiveDate;
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use time_tracker::orm::insert::{LoggedTime, NewEntry};
+use time_tracker::orm::ticket::Ticket;
+
+/// An in-memory database with all migrations applied, but no data.
+pub fn database() -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+
+    let mut migrations: Vec<_> = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.is_dir())
+        .collect();
+    migrations.sort();
+
+    conn.batch_execute("PRAGMA foreign_keys = ON").unwrap();
+    for migration in migrations {
+        conn.batch_execute(&fs::read_to_string(migration.join("up.sql")).unwrap()).unwrap();
+    }
+
+    conn
+}
+
+/// An in-memory database filled with `tests/fixtures/seed.sql`.
+pub fn seeded() -> SqliteConnection {
+    let mut conn = database();
+    conn.batch_execute(include_str!("../fixtures/seed.sql")).unwrap();
+    conn
+}
+
+pub fn ticket(proj_key: &str, tick_num: i32) -> Ticket {
+    Ticket { proj_key: proj_key.to_owned(), tick_num }
+}
+
+/// An entry on `day` from `start` to `end` o'clock, without tags or an activity.
+pub fn entry(day: NaiveDate, start: u32, end: u32, desc: &str, tickets: Vec<Ticket>) -> NewEntry {
+    NewEntry {
+        time: LoggedTime {
+            time_start: day.and_hms_opt(start, 0, 0).unwrap(),
+            time_end: day.and_hms_opt(end, 0, 0).unwrap(),
+            time_desc: desc.to_owned(),
+            act_num: None,
+            time_notes: None,
+        },
+        tickets,
+        tags: vec![],
+        billable: None,
+    }
+}
+
+/// A throwaway repository, removed again when the test finishes.
+pub struct Repo(PathBuf);
+
+impl Repo {
+    pub fn new(name: &str) -> Repo {
+        let path = env::temp_dir().join(format!("time-tracker-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        let repo = Repo(path);
+        repo.git(&["init", "-q", "-b", "main"], None);
+        repo.git(&["config", "user.email", "dev@example.com"], None);
+        repo.git(&["config", "user.name", "Dev"], None);
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn git(&self, args: &[&str], date: Option<&str>) {
+        let mut command = Command::new("git");
+        command.arg("-C").arg(&self.0).args(args);
+
+        if let Some(date) = date {
+            command.env("GIT_AUTHOR_DATE", date).env("GIT_COMMITTER_DATE", date);
+        }
+
+        assert!(command.status().unwrap().success(), "git {args:?} failed");
+    }
+
+    pub fn commit(&self, date: &str, message: &str) {
+        self.git(&["commit", "-q", "--allow-empty", "-m", message], Some(date));
+    }
+}
+
+impl Drop for Repo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
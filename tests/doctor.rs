@@ -0,0 +1,68 @@
+mod common;
+
+use diesel::connection::SimpleConnection;
+use time_tracker::service::doctor::{Check, Fix, apply_fix, diagnose};
+
+#[test]
+fn dangling_rows_are_fixed_without_losing_linked_time() {
+    let mut conn = common::seeded();
+
+    conn.batch_execute("
+        PRAGMA foreign_keys = OFF;
+        INSERT INTO ticket_time VALUES ('NEW', 1, 5);
+        INSERT INTO time_tag VALUES ('ghost', 99);
+        UPDATE time SET act_num = 99 WHERE time_id = 5;
+        INSERT INTO invoice VALUES (4, '2026-09-01', 'ACME', NULL, NULL, NULL, NULL, NULL, NULL);
+        PRAGMA foreign_keys = ON;
+        INSERT INTO invoice VALUES (5, '2026-09-01', 'ACME', NULL, NULL, NULL, NULL, NULL, NULL);
+        INSERT INTO invoice_activity VALUES (10, 5, 'Setup fee', 250);
+    ").unwrap();
+
+    let problems = diagnose(&mut conn).unwrap();
+    let fixes: Vec<Fix> = problems.iter().filter_map(|p| p.fix.clone()).collect();
+    assert_eq!(fixes, [
+        Fix::DeleteRow { table: "time_tag".to_owned(), rowid: 1 },
+        Fix::AddProject { proj_key: "NEW".to_owned() },
+        Fix::ClearActivity { time_id: 5 },
+        Fix::DeleteInvoice { inv_num: 4 },
+    ]);
+
+    // The tag of the deleted row isn't worth adding.
+    assert!(problems.iter().any(|p| p.check == Check::ForeignKey && p.fix.is_none()));
+
+    for fix in &fixes {
+        apply_fix(&mut conn, fix).unwrap();
+    }
+
+    // Only the unused project is left, since it may yet be used.
+    let left: Vec<Check> = diagnose(&mut conn).unwrap().into_iter().map(|p| p.check).collect();
+    assert_eq!(left, [Check::UnusedProject]);
+}
+
+#[test]
+fn problems_without_a_safe_fix_are_only_reported() {
+    let mut conn = common::seeded();
+
+    conn.batch_execute("
+        INSERT INTO project VALUES ('TOOLONG', 'Long key', TRUE);
+        INSERT INTO ticket_time VALUES ('TOOLONG', 1, 5);
+        INSERT INTO time (time_id, time_start, time_end, time_desc) VALUES
+            (6, '2026-09-02 09:30:00', '2026-09-02 11:00:00', 'Standup');
+        UPDATE time SET time_start = '2026-10-01 09:00:00', time_end = '2026-10-01 09:30:00'
+            WHERE time_id = 4;
+    ").unwrap();
+
+    let problems: Vec<(Check, String)> = diagnose(&mut conn).unwrap()
+        .into_iter()
+        .filter(|p| p.fix.is_none())
+        .map(|p| (p.check, p.description))
+        .collect();
+
+    let expected = [
+        (Check::Overlap, "Time 5 and 6 overlap from 2026-09-02 09:30:00 to 2026-09-02 10:00:00"),
+        (Check::InvoicePeriod, "Time 4 from 2026-10-01 is outside the period 2026-09 of invoice 3"),
+        (Check::UnusedProject, "Project INT has no time, tickets or budgets"),
+        (Check::ProjectKey, "Project key TOOLONG is longer than 5 characters"),
+    ].map(|(check, description)| (check, description.to_owned()));
+    assert_eq!(problems, expected);
+}
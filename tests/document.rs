@@ -0,0 +1,127 @@
+mod common;
+
+use diesel::connection::SimpleConnection;
+use time_tracker::cli::args::{DocIdentifier, TimeFilter};
+use time_tracker::orm::query::InvoiceWithActivities;
+use time_tracker::orm::ticket::TicketTitles;
+use time_tracker::service::document::render_timesheet;
+use time_tracker::typst::convert::IntoTypst;
+use typst::foundations::{Array, Datetime, Dict, IntoValue, Value};
+
+fn field(dict: &Dict, key: &str) -> Value {
+    dict.get(key).unwrap().clone()
+}
+
+fn dict(value: Value) -> Dict {
+    match value {
+        Value::Dict(dict) => dict,
+        other => panic!("Expected a dictionary, got {other:?}"),
+    }
+}
+
+#[test]
+fn timesheet_is_written_as_quoted_csv() {
+    let mut conn = common::seeded();
+
+    let invoice = Some(DocIdentifier::Num(1));
+    let csv = render_timesheet(&mut conn, invoice, TimeFilter::default(), false, false).unwrap();
+
+    assert_eq!(csv.file_name, "2026-08-timesheet-1.csv");
+    assert!(csv.warnings.is_empty());
+    assert_eq!(String::from_utf8(csv.bytes).unwrap(), concat!(
+        "\"Start\",\"End\",\"Duration\",\"Tickets\",\"Description\"\n",
+        "\"2026-08-03 09:00\",\"2026-08-03 10:30\",\"1.5\",\"ABC-12\",\"Fix login timeout\"\n",
+        "\"2026-08-04 13:00\",\"2026-08-04 14:00\",\"1.0\",\"ABC-12, ABC-14\",",
+        "\"Fix login redirect\"\n",
+    ));
+}
+
+#[test]
+fn detailed_timesheets_have_notes() {
+    let mut conn = common::seeded();
+
+    conn.batch_execute(
+        "UPDATE time SET time_notes = 'Raised the session limit.\nAdded a test.' WHERE time_id = 1"
+    ).unwrap();
+
+    let invoice = Some(DocIdentifier::Num(1));
+    let csv = render_timesheet(&mut conn, invoice, TimeFilter::default(), false, true).unwrap();
+
+    assert_eq!(String::from_utf8(csv.bytes).unwrap(), concat!(
+        "\"Start\",\"End\",\"Duration\",\"Tickets\",\"Description\",\"Notes\"\n",
+        "\"2026-08-03 09:00\",\"2026-08-03 10:30\",\"1.5\",\"ABC-12\",\"Fix login timeout\",",
+        "\"Raised the session limit.\nAdded a test.\"\n",
+        "\"2026-08-04 13:00\",\"2026-08-04 14:00\",\"1.0\",\"ABC-12, ABC-14\",",
+        "\"Fix login redirect\",\"\"\n",
+    ));
+}
+
+#[test]
+fn invoices_become_typst_inputs() {
+    let mut conn = common::seeded();
+
+    let invoice = InvoiceWithActivities::select_by_identifier(DocIdentifier::Num(1), &mut conn)
+        .unwrap()
+        .into_typst();
+
+    assert_eq!(field(&invoice, "num"), 1.into_value());
+    assert_eq!(field(&invoice, "number"), "1".into_value());
+    assert_eq!(
+        field(&invoice, "month"),
+        Datetime::from_ymd(2026, 8, 1).unwrap().into_value()
+    );
+    assert_eq!(
+        field(&invoice, "created"),
+        Datetime::from_ymd(2026, 9, 1).unwrap().into_value()
+    );
+
+    // Invoices without a period of their own cover their month.
+    let period = dict(field(&invoice, "period"));
+    assert_eq!(field(&period, "start"), Datetime::from_ymd(2026, 8, 1).unwrap().into_value());
+    assert_eq!(field(&period, "end"), Datetime::from_ymd(2026, 8, 31).unwrap().into_value());
+
+    // Escaped newlines in the address are turned into real ones.
+    let recipient = dict(field(&invoice, "recipient"));
+    assert_eq!(field(&recipient, "name"), "Acme Ltd".into_value());
+    assert_eq!(field(&recipient, "addr"), "1 Road Runner Way\nDesert".into_value());
+
+    let Value::Array(activities) = field(&invoice, "activities") else {
+        panic!("Expected activities to be an array");
+    };
+    assert_eq!(activities.len(), 2);
+
+    let login = dict(activities.at(0, None).unwrap());
+    assert_eq!(field(&login, "desc"), "Login fixes".into_value());
+    assert_eq!(field(&login, "uprice"), 80.0.into_value());
+    assert_eq!(field(&login, "dur"), 2.5.into_value());
+    assert_eq!(
+        field(&login, "tickets"),
+        ["ABC-12", "ABC-14"].into_iter().map(IntoValue::into_value).collect::<Array>().into_value()
+    );
+}
+
+#[test]
+fn tickets_can_be_labelled_with_titles() {
+    let mut conn = common::seeded();
+
+    let invoice = Some(DocIdentifier::Num(1));
+    let csv = render_timesheet(&mut conn, invoice, TimeFilter::default(), true, false).unwrap();
+    assert!(String::from_utf8(csv.bytes).unwrap()
+        .contains("\"ABC-12 Fix login timeout, ABC-14\",\"Fix login redirect\""));
+
+    let titles = TicketTitles::load(&mut conn).unwrap();
+    let invoice = InvoiceWithActivities::select_by_identifier(DocIdentifier::Num(1), &mut conn)
+        .unwrap();
+    let invoice = (invoice, &titles).into_typst();
+
+    let Value::Array(activities) = field(&invoice, "activities") else {
+        panic!("Expected activities to be an array");
+    };
+    assert_eq!(
+        field(&dict(activities.at(0, None).unwrap()), "tickets"),
+        ["ABC-12 Fix login timeout", "ABC-14"].into_iter()
+            .map(IntoValue::into_value)
+            .collect::<Array>()
+            .into_value()
+    );
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::{env, fs, path::PathBuf, process};
+
+use chrono::{NaiveDate, TimeDelta};
+use common::ticket;
+use time_tracker::cli::args::TimeFilter;
+use time_tracker::cli::edit::apply_edit;
+use time_tracker::csv::edit::{read_edit_file, write_edit_file};
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::time::{log_time, select_time, select_times};
+
+/// Write `contents` to a temporary edit file, which is removed again when dropped.
+struct EditFile(PathBuf);
+
+impl EditFile {
+    fn new(name: &str, contents: &[u8]) -> EditFile {
+        let path = env::temp_dir().join(format!("time-tracker-{name}-{}.csv", process::id()));
+        fs::write(&path, contents).unwrap();
+        EditFile(path)
+    }
+}
+
+impl Drop for EditFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn untouched_entries_read_back_unchanged() {
+    let mut conn = common::seeded();
+
+    // Imported entries often have seconds, which have to survive the round trip.
+    let mut entry = common::entry(
+        NaiveDate::from_ymd_opt(2026, 10, 5).unwrap(), 9, 10, "Imported", vec![]
+    );
+    entry.time.time_start += TimeDelta::seconds(42);
+    log_time(&mut conn, entry).unwrap();
+
+    let times = select_times(&mut conn, TimeFilter::default(), None).unwrap();
+    let mut contents = vec![];
+    write_edit_file(&mut contents, &times).unwrap();
+
+    let file = EditFile::new("untouched", &contents);
+    let edited = read_edit_file(&file.0).unwrap();
+
+    assert_eq!(edited.len(), times.len());
+    for (row, time) in edited.iter().zip(&times) {
+        assert_eq!(row.time_id, Some(time.time_id));
+        assert_eq!(row.entry.time.time_start, *time.time_start);
+        assert_eq!(row.entry.time.time_end, *time.time_end);
+        assert_eq!(row.entry.time.time_desc, time.time_desc);
+        assert_eq!(row.entry.tickets, time.tickets);
+    }
+}
+
+fn read_error(name: &str, contents: &str) -> String {
+    let file = EditFile::new(name, contents.as_bytes());
+    read_edit_file(&file.0).unwrap_err().to_string()
+}
+
+#[test]
+fn errors_point_at_the_offending_line() {
+    let header = "# A comment\nId,Start,End,Activity,Tickets,Tags,Billable,Description,Notes\n";
+
+    let error = read_error("bad-id", &format!(
+        "{header}1,2026-09-01 09:00,2026-09-01 10:00,,,,yes,Fine,\n\
+        x,2026-09-01 09:00,2026-09-01 10:00,,,,yes,Broken,\n"
+    ));
+    assert!(error.starts_with("Error on line 4 of edit file:\nError parsing id"), "{error}");
+    assert!(error.ends_with("> x,2026-09-01 09:00,2026-09-01 10:00,,,,yes,Broken,"), "{error}");
+
+    let error = read_error("bad-billable", &format!(
+        "{header},2026-09-01 09:00,2026-09-01 10:00,,,,maybe,New,\n"
+    ));
+    assert!(error.contains("Billable must be 'yes' or 'no', not 'maybe'"), "{error}");
+
+    let error = read_error("bad-time", &format!(
+        "{header},2026-09-01 9am,2026-09-01 10:00,,ABC-1,,,New,\n"
+    ));
+    assert!(error.starts_with("Error on line 3 of edit file:\nError parsing date"), "{error}");
+
+    // Moving a column would put values into the wrong fields.
+    let error = read_error("moved-column", "\
+        Id,End,Start,Activity,Tickets,Tags,Billable,Description,Notes\n\
+        1,2026-09-01 10:00,2026-09-01 09:00,,,,yes,Fine,\n");
+    assert!(error.starts_with("Error on line 1 of edit file:\nThe columns must be"), "{error}");
+    assert!(error.ends_with("> Id,End,Start,Activity,Tickets,Tags,Billable,Description,Notes"));
+}
+
+#[test]
+fn edited_files_insert_update_and_delete_entries() {
+    let mut conn = common::seeded();
+
+    let times = select_times(&mut conn, TimeFilter::default(), Some(1)).unwrap();
+    let mut contents = vec![];
+    write_edit_file(&mut contents, &times).unwrap();
+
+    // Change the first entry, remove the second and add another.
+    let contents: String = String::from_utf8(contents).unwrap()
+        .lines()
+        .filter(|line| !line.starts_with("2,"))
+        .map(|line| line.replace("Fix login timeout", "Fix login timeout for good") + "\n")
+        .chain([",2026-08-05 09:00,2026-08-05 10:00,1,ABC-12,#review,,Retest login,\n".into()])
+        .collect();
+
+    let file = EditFile::new("applied", contents.as_bytes());
+    apply_edit(&mut conn, times, read_edit_file(&file.0).unwrap()).unwrap();
+
+    assert_eq!(select_time(&mut conn, 1).unwrap().time_desc, "Fix login timeout for good");
+    assert!(matches!(select_time(&mut conn, 2), Err(ServiceError::NotFound(_))));
+
+    let times = select_times(&mut conn, TimeFilter::default(), Some(1)).unwrap();
+    let inserted = times.last().unwrap();
+    assert_eq!(times.len(), 2);
+    assert_eq!(inserted.time_desc, "Retest login");
+    assert_eq!(inserted.tickets, [ticket("ABC", 12)]);
+    assert_eq!(inserted.tags, ["review"]);
+}
+
+#[test]
+fn edited_files_only_change_selected_entries() {
+    let mut conn = common::seeded();
+
+    let times = select_times(&mut conn, TimeFilter::default(), Some(1)).unwrap();
+    let file = EditFile::new("unselected", b"\
+        Id,Start,End,Activity,Tickets,Tags,Billable,Description,Notes\n\
+        1,2026-08-03 09:00,2026-08-03 10:30,1,ABC-12,,yes,Fix login timeout,\n\
+        3,2026-08-10 09:00,2026-08-10 12:00,3,XYZ-3,,yes,Monthly report,\n");
+
+    let error = apply_edit(&mut conn, times, read_edit_file(&file.0).unwrap()).unwrap_err();
+    assert!(error.to_string().starts_with("Error on line 3 of edit file:\nEntry 3 wasn't"));
+}
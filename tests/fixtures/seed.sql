@@ -0,0 +1,36 @@
+-- A small but complete database: two billable projects and an internal one, one recipient and
+-- three invoices, two of which share a month so that month identifiers are ambiguous.
+INSERT INTO project VALUES
+    ('ABC', 'Alpha', TRUE),
+    ('XYZ', 'Xylophone', TRUE),
+    ('INT', 'Internal', FALSE);
+
+INSERT INTO recipient VALUES ('ACME', 'Acme Ltd', '1 Road Runner Way\nDesert');
+
+INSERT INTO invoice VALUES
+    (1, '2026-08-01', 'ACME', '2026-09-01', NULL, NULL, NULL, NULL, NULL),
+    (2, '2026-08-01', 'ACME', '2026-09-02', NULL, NULL, NULL, NULL, NULL),
+    (3, '2026-09-01', 'ACME', NULL, NULL, NULL, NULL, NULL, NULL);
+
+INSERT INTO invoice_activity VALUES
+    (1, 1, 'Login fixes', 80.0),
+    (2, 1, 'Hosting', 25.0),
+    (3, 2, 'Reporting', 80.0),
+    (4, 3, 'Login fixes', 90.0);
+
+INSERT INTO time (time_id, time_start, time_end, time_desc, act_num) VALUES
+    (1, '2026-08-03 09:00:00', '2026-08-03 10:30:00', 'Fix login timeout', 1),
+    (2, '2026-08-04 13:00:00', '2026-08-04 14:00:00', 'Fix login redirect', 1),
+    (3, '2026-08-10 09:00:00', '2026-08-10 11:00:00', 'Monthly report', 3),
+    (4, '2026-09-01 09:00:00', '2026-09-01 09:30:00', 'Review login changes', 4),
+    (5, '2026-09-02 09:00:00', '2026-09-02 10:00:00', 'Plan next sprint', NULL);
+
+INSERT INTO ticket_time VALUES
+    ('ABC', 12, 1),
+    ('ABC', 12, 2),
+    ('ABC', 14, 2),
+    ('XYZ', 3, 3),
+    ('ABC', 12, 4);
+
+-- Only some tickets have details.
+INSERT INTO ticket VALUES ('ABC', 12, 'Fix login timeout', 'https://example.com/ABC-12', 'done', 3.0);
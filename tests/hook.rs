@@ -0,0 +1,67 @@
+mod common;
+
+use std::fs;
+
+use common::Repo;
+use time_tracker::git::hook::{install, post_checkout_script};
+
+fn command() -> Vec<String> {
+    ["time-tracker", "--database", "/tmp/it's.db", "hook", "checkout"]
+        .map(str::to_owned)
+        .to_vec()
+}
+
+#[test]
+fn post_checkout_hook_passes_branch() {
+    let repo = Repo::new("hook-install");
+
+    let path = install(repo.path(), "post-checkout", &post_checkout_script(&command()), false)
+        .unwrap();
+    let script = fs::read_to_string(&path).unwrap();
+
+    assert!(path.ends_with(".git/hooks/post-checkout"));
+    assert!(script.starts_with("#!/bin/sh\n"));
+    // Arguments are quoted for the shell, even when they contain quotes themselves.
+    assert!(script.contains(r#"'/tmp/it'\''s.db' 'hook' 'checkout' "$branch""#));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o111, 0o111);
+    }
+}
+
+#[test]
+fn foreign_hooks_are_only_replaced_when_forced() {
+    let repo = Repo::new("hook-force");
+    let script = post_checkout_script(&command());
+
+    let path = install(repo.path(), "post-checkout", &script, false).unwrap();
+    // Reinstalling over our own hook is fine.
+    install(repo.path(), "post-checkout", &script, false).unwrap();
+
+    fs::write(&path, "#!/bin/sh\necho custom\n").unwrap();
+
+    assert!(install(repo.path(), "post-checkout", &script, false).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "#!/bin/sh\necho custom\n");
+
+    install(repo.path(), "post-checkout", &script, true).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), script);
+}
+
+#[test]
+fn hook_runs_on_branch_checkout() {
+    let repo = Repo::new("hook-run");
+    repo.commit("2026-09-02T09:00:00", "Initial commit");
+
+    let command = ["sh", "-c", r#"echo "$0" >> .git/branches.txt"#].map(str::to_owned).to_vec();
+    install(repo.path(), "post-checkout", &post_checkout_script(&command), false).unwrap();
+
+    repo.git(&["checkout", "-q", "-b", "feature/ABC-123-foo"], None);
+    // A detached head isn't on any branch.
+    repo.git(&["checkout", "-q", "--detach"], None);
+    repo.git(&["checkout", "-q", "main"], None);
+
+    let branches = fs::read_to_string(repo.path().join(".git/branches.txt")).unwrap();
+    assert_eq!(branches, "feature/ABC-123-foo\nmain\n");
+}
@@ -0,0 +1,310 @@
+mod common;
+
+use std::{env, fs, path::{Path, PathBuf}, process};
+
+use chrono::{NaiveDate, TimeDelta};
+use diesel::prelude::*;
+use common::ticket;
+use clap::Parser;
+use time_tracker::cli::args::{Action, CliArgs, ColumnMapping, ImportOptions, TicketMapping, TimeFilter};
+use time_tracker::cli::import::{import, import_entries};
+use time_tracker::csv::import::read_timesheet;
+use time_tracker::csv::tracker::{self, CLOCKIFY, CLOCKIFY_DAY_FIRST, TOGGL};
+use time_tracker::ical::convert::escape;
+use time_tracker::ical::import::{read_calendar, unescape};
+use time_tracker::json::timewarrior;
+use time_tracker::orm::ticket::TicketTitles;
+use time_tracker::service::document::timesheet_csv;
+use time_tracker::service::project::known_tickets;
+use time_tracker::service::time::select_times;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+#[test]
+fn timewarrior_skips_open_intervals() {
+    let entries = timewarrior::read_export(&fixture("timewarrior.json"), &[], Ok).unwrap();
+
+    assert_eq!(entries.len(), 2);
+
+    let (source, review) = &entries[0];
+    assert_eq!(source, "interval @3");
+    assert_eq!(review.time.time_desc, "Reviewed login changes");
+    assert_eq!(review.tickets, vec![ticket("ABC", 12)]);
+    assert_eq!(review.time.time_end - review.time.time_start, TimeDelta::minutes(90));
+
+    // Without an annotation, the non-ticket tags describe the entry.
+    let (_, meeting) = &entries[1];
+    assert_eq!(meeting.time.time_desc, "meeting planning");
+    assert!(meeting.tickets.is_empty());
+}
+
+#[test]
+fn toggl_collects_tickets_from_description_and_tags() {
+    let entries = tracker::read_export(&fixture("toggl.csv"), &TOGGL, &[], Ok).unwrap();
+
+    assert_eq!(entries.len(), 2);
+
+    let (source, fix) = &entries[0];
+    assert_eq!(source, "line 2");
+    assert_eq!(fix.time.time_desc, "Fix login timeout ABC-12");
+    assert_eq!(fix.tickets, vec![ticket("ABC", 12), ticket("ABC", 14)]);
+    assert_eq!(
+        fix.time.time_start,
+        NaiveDate::from_ymd_opt(2026, 9, 2).unwrap().and_hms_opt(9, 0, 0).unwrap()
+    );
+    assert_eq!(fix.time.time_end - fix.time.time_start, TimeDelta::minutes(135));
+
+    // Entries without a description fall back to the task.
+    assert_eq!(entries[1].1.time.time_desc, "Standup");
+}
+
+#[test]
+fn mapped_projects_and_tags_are_imported_with_their_tickets() {
+    use time_tracker::orm::schema::{project, ticket_time};
+
+    let mappings: Vec<TicketMapping> = ["website=WEB-1", "urgent=ABC-99"].into_iter()
+        .map(|m| m.parse().unwrap())
+        .collect();
+    let entries = tracker::read_export(&fixture("toggl.csv"), &TOGGL, &mappings, Ok).unwrap();
+
+    let mut conn = common::database();
+    import_entries(&mut conn, entries, ImportOptions {
+        dry_run: false,
+        create_projects: true,
+        allow_overlaps: false,
+    }).unwrap();
+
+    let mut linked: Vec<(String, i32, i32)> = ticket_time::table
+        .select((ticket_time::proj_key, ticket_time::tick_num, ticket_time::time_id))
+        .load(&mut conn)
+        .unwrap();
+    linked.sort();
+    assert_eq!(linked, [
+        ("ABC".to_owned(), 12, 1),
+        ("ABC".to_owned(), 14, 1),
+        ("ABC".to_owned(), 99, 1),
+        ("WEB".to_owned(), 1, 1),
+        ("WEB".to_owned(), 1, 2),
+    ]);
+
+    let projects: Vec<String> = project::table.select(project::proj_key).load(&mut conn).unwrap();
+    assert_eq!(projects, ["ABC", "WEB"]);
+}
+
+#[test]
+fn dry_runs_reject_unknown_projects_too() {
+    let entries = tracker::read_export(&fixture("clockify.csv"), &CLOCKIFY, &[], Ok).unwrap();
+
+    let result = import_entries(&mut common::database(), entries, ImportOptions {
+        dry_run: true,
+        create_projects: false,
+        allow_overlaps: false,
+    });
+    assert!(result.unwrap_err().to_string().contains("unknown projects: ABC, DOC"));
+}
+
+#[test]
+fn clockify_parses_twelve_hour_times() {
+    let entries = tracker::read_export(&fixture("clockify.csv"), &CLOCKIFY, &[], Ok).unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(
+        entries[0].1.time.time_start,
+        NaiveDate::from_ymd_opt(2026, 9, 2).unwrap().and_hms_opt(13, 0, 0).unwrap()
+    );
+    assert_eq!(entries[0].1.tickets, vec![ticket("ABC", 12)]);
+    assert_eq!(entries[1].1.tickets, vec![ticket("DOC", 7)]);
+}
+
+#[test]
+fn clockify_day_first_reads_dates_differently() {
+    let entries = tracker::read_export(&fixture("clockify.csv"), &CLOCKIFY_DAY_FIRST, &[], Ok)
+        .unwrap();
+
+    assert_eq!(
+        entries[0].1.time.time_start.date(),
+        NaiveDate::from_ymd_opt(2026, 2, 9).unwrap()
+    );
+}
+
+#[test]
+fn ical_skips_all_day_events_and_strips_exported_tickets() {
+    let events = read_calendar(&fixture("calendar.ics")).unwrap();
+
+    assert_eq!(events.len(), 3);
+
+    // The alarm's summary and duration aren't the event's.
+    let standup = &events[0];
+    assert_eq!(standup.summary, "Standup, daily");
+    assert_eq!(standup.end - standup.start, TimeDelta::minutes(15));
+    assert!(standup.tickets().is_empty());
+
+    // Folded lines are joined, and the ticket prefix from an export isn't kept in the description.
+    let review = events[1].clone();
+    assert_eq!(review.uid.as_deref(), Some("time-7@time-tracker"));
+    assert_eq!(review.tickets(), vec![ticket("ABC", 12), ticket("ABC", 14)]);
+
+    let known = review.tickets();
+    assert_eq!(review.description(&[]), review.summary);
+
+    let entry = review.into_entry(vec![], &known);
+    assert_eq!(entry.time.time_desc, "Reviewed login changes with the team");
+    assert_eq!(entry.time.time_end - entry.time.time_start, TimeDelta::minutes(90));
+
+    // Only tickets of known projects are taken for a prefix.
+    assert_eq!(events[2].description(&[]), "NOPE-1: call back");
+}
+
+#[test]
+fn ical_text_round_trips_through_escaping() {
+    let text = "Notes; with, commas\r\nand\nlines\\";
+    assert_eq!(escape(text), "Notes\\; with\\, commas\\nand\\nlines\\\\");
+    assert_eq!(unescape(&escape(text)), "Notes; with, commas\nand\nlines\\");
+    assert_eq!(escape("a\rb"), "a\\nb");
+}
+
+/// Write `contents` to a temporary file for reading back in.
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = env::temp_dir().join(format!("time-tracker-{name}-{}.csv", process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn timesheets_round_trip() {
+    let mut conn = common::seeded();
+
+    let times = select_times(&mut conn, TimeFilter::default(), None).unwrap();
+    let expected: Vec<_> = times.iter()
+        .map(|t| (*t.time_start, *t.time_end, t.time_desc.clone(), t.tickets.clone()))
+        .collect();
+
+    let csv = timesheet_csv(times, &TicketTitles::default(), false).unwrap();
+    let path = temp_file("timesheet", &csv);
+    let entries = read_timesheet(&path, &[]);
+    fs::remove_file(&path).unwrap();
+
+    let entries = entries.unwrap();
+    assert_eq!(entries[0].0, "line 2");
+
+    let imported: Vec<_> = entries.into_iter()
+        .map(|(_, e)| (e.time.time_start, e.time.time_end, e.time.time_desc, e.tickets))
+        .collect();
+    assert_eq!(imported, expected);
+}
+
+#[test]
+fn timesheet_columns_can_be_mapped() {
+    let path = temp_file("mapped", b"\
+        Date,Hours,Issue,Notes\n\
+        2026-09-07 09:00,1.5,ABC-12 XYZ-3,Fix login\n\
+        2026-09-08 10:00,,,No time\n");
+
+    let mappings: Vec<ColumnMapping> = ["start=Date", "dur=Hours", "tickets=Issue", "desc=Notes"]
+        .into_iter()
+        .map(|m| m.parse().unwrap())
+        .collect();
+    let mapped = read_timesheet(&path, &mappings);
+    let unmapped = read_timesheet(&path, &[]);
+    fs::remove_file(&path).unwrap();
+
+    // The second entry can't be read, and the error points at its line.
+    let error = mapped.unwrap_err().to_string();
+    assert!(error.starts_with("Error on line 3 of timesheet"), "{error}");
+    assert!(unmapped.unwrap_err().to_string().contains("no start column"));
+
+    let path = temp_file("mapped-valid", b"\
+        Date,Hours,Issue,Notes\n\
+        2026-09-07 09:00,1.5,ABC-12 XYZ-3,Fix login\n");
+    let entries = read_timesheet(&path, &mappings);
+    fs::remove_file(&path).unwrap();
+
+    let (_, entry) = &entries.unwrap()[0];
+    assert_eq!(entry.time.time_desc, "Fix login");
+    assert_eq!(entry.tickets, vec![ticket("ABC", 12), ticket("XYZ", 3)]);
+    assert_eq!(
+        entry.time.time_start,
+        NaiveDate::from_ymd_opt(2026, 9, 7).unwrap().and_hms_opt(9, 0, 0).unwrap()
+    );
+    assert_eq!(entry.time.time_end - entry.time.time_start, TimeDelta::minutes(90));
+}
+
+#[test]
+fn only_tickets_of_known_projects_are_found_in_text() {
+    let mut conn = common::seeded();
+    let mappings: Vec<TicketMapping> = ["urgent=WEB-1", "release=WEB-2"].into_iter()
+        .map(|m| m.parse().unwrap())
+        .collect();
+
+    let path = temp_file("lookalike", b"\
+        Project,Task,Description,Tags,Start date,Start time,End date,End time\n\
+        Website,Q3-2026,Upgrade to node-18 for ABC-12,\"v2-3, urgent\",\
+        2026-09-02,09:00,2026-09-02,10:00\n");
+    let entries = tracker::read_export(&path, &TOGGL, &mappings, |tickets| {
+        Ok(known_tickets(&mut conn, tickets)?)
+    });
+    fs::remove_file(&path).unwrap();
+
+    // Mapped tickets are kept, even though their project doesn't exist yet.
+    let (_, entry) = &entries.unwrap()[0];
+    assert_eq!(entry.tickets, vec![ticket("ABC", 12), ticket("WEB", 1)]);
+
+    let path = temp_file("lookalike-tags", br#"[{
+        "start": "20260902T090000Z",
+        "end": "20260902T100000Z",
+        "tags": ["ABC-12", "v2-3", "release"]
+    }]"#);
+    let entries = timewarrior::read_export(&path, &mappings, |tickets| {
+        Ok(known_tickets(&mut conn, tickets)?)
+    });
+    fs::remove_file(&path).unwrap();
+
+    // Tags that aren't tickets after all describe the entry like any other.
+    let (_, entry) = &entries.unwrap()[0];
+    assert_eq!(entry.tickets, vec![ticket("ABC", 12), ticket("WEB", 2)]);
+    assert_eq!(entry.time.time_desc, "v2-3 release");
+}
+
+#[test]
+fn ical_imports_only_take_tickets_of_known_projects() {
+    use time_tracker::orm::schema::project;
+
+    let mut conn = common::seeded();
+
+    let path = temp_file("calendar", b"\
+        BEGIN:VCALENDAR\r\n\
+        BEGIN:VEVENT\r\n\
+        SUMMARY:Sprint-3 planning\r\n\
+        DTSTART:20261005T090000\r\n\
+        DTEND:20261005T100000\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        SUMMARY:ABC-12: Retest login\r\n\
+        DTSTART:20261006T090000\r\n\
+        DTEND:20261006T100000\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n");
+    let args = CliArgs::try_parse_from([
+        "time-tracker", "import", "ical", path.to_str().unwrap(), "--ticket", "INT-4"
+    ]).unwrap();
+    let Action::Import(import_args) = args.action else { panic!("Parsed as {:?}", args.action) };
+    let result = import(&mut conn, import_args);
+    fs::remove_file(&path).unwrap();
+    result.unwrap();
+
+    let filter = TimeFilter { unbilled: true, ..Default::default() };
+    let imported: Vec<_> = select_times(&mut conn, filter, None).unwrap()
+        .into_iter()
+        .filter(|t| t.time_start.date() >= NaiveDate::from_ymd_opt(2026, 10, 1).unwrap())
+        .map(|t| (t.time_desc, t.tickets))
+        .collect();
+    assert_eq!(imported, [
+        ("Sprint-3 planning".to_owned(), vec![ticket("INT", 4)]),
+        ("Retest login".to_owned(), vec![ticket("ABC", 12), ticket("INT", 4)]),
+    ]);
+
+    let projects: Vec<String> = project::table.select(project::proj_key).load(&mut conn).unwrap();
+    assert_eq!(projects, ["ABC", "INT", "XYZ"]);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use time_tracker::cli::args::GroupRule;
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::invoice::{
+    create_invoice, draft_activities, new_invoice, next_invoice_num, DraftActivity
+};
+use time_tracker::service::time::log_time;
+use time_tracker::util::date::Period;
+
+use common::ticket;
+
+fn sep(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 9, day).unwrap()
+}
+
+fn oct(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+}
+
+/// The seeded database plus unbilled September time across both billable projects, the internal
+/// one and without tickets, and some time in October.
+fn unbilled() -> SqliteConnection {
+    let mut conn = common::seeded();
+
+    for entry in [
+        common::entry(sep(7), 9, 11, "Fix login timeout", vec![ticket("ABC", 12)]),
+        common::entry(sep(8), 9, 10, "Export to PDF", vec![ticket("ABC", 14)]),
+        common::entry(sep(9), 9, 12, "Chart colours", vec![ticket("XYZ", 3)]),
+        common::entry(sep(10), 14, 15, "Plan next sprint", vec![]),
+        common::entry(sep(11), 9, 10, "Team meeting", vec![ticket("INT", 1)]),
+        common::entry(oct(1), 9, 10, "Release", vec![ticket("ABC", 15)]),
+    ] {
+        log_time(&mut conn, entry).unwrap();
+    }
+
+    conn
+}
+
+fn draft(conn: &mut SqliteConnection, period: &str, rule: GroupRule) -> Vec<DraftActivity> {
+    draft_activities(conn, &period.parse::<Period>().unwrap(), "ACME", rule).unwrap()
+}
+
+fn summary(drafts: &[DraftActivity]) -> Vec<(&str, Vec<&str>)> {
+    drafts.iter()
+        .map(|d| (d.desc.as_str(), d.times.iter().map(|t| t.time_desc.as_str()).collect()))
+        .collect()
+}
+
+#[test]
+fn drafts_group_unbilled_billable_time_of_the_period() {
+    let mut conn = unbilled();
+
+    // Time that is already billed, non-billable or outside the period is left out, and entries
+    // without tickets are grouped by description after all projects.
+    assert_eq!(summary(&draft(&mut conn, "2026-09", GroupRule::Project)), [
+        ("Alpha", vec!["Fix login timeout", "Export to PDF"]),
+        ("Xylophone", vec!["Chart colours"]),
+        ("Plan next sprint", vec!["Plan next sprint", "Plan next sprint"]),
+    ]);
+
+    assert_eq!(summary(&draft(&mut conn, "2026-09", GroupRule::Ticket)), [
+        ("Fix login timeout", vec!["Fix login timeout"]),
+        ("Export to PDF", vec!["Export to PDF"]),
+        ("Chart colours", vec!["Chart colours"]),
+        ("Plan next sprint", vec!["Plan next sprint", "Plan next sprint"]),
+    ]);
+
+    assert_eq!(summary(&draft(&mut conn, "2026-09-03..2026-09-08", GroupRule::Desc)), [
+        ("Export to PDF", vec!["Export to PDF"]),
+        ("Fix login timeout", vec!["Fix login timeout"]),
+    ]);
+    assert_eq!(draft(&mut conn, "2026-09-03..2026-09-08", GroupRule::Desc)[0].dur(), 1.0);
+}
+
+#[test]
+fn drafting_needs_a_recipient_and_unbilled_time() {
+    let mut conn = unbilled();
+    let period = "2026-08".parse::<Period>().unwrap();
+
+    assert!(matches!(
+        draft_activities(&mut conn, &period, "NOPE", GroupRule::Project),
+        Err(ServiceError::NotFound(_))
+    ));
+    assert!(matches!(
+        draft_activities(&mut conn, &period, "ACME", GroupRule::Project),
+        Err(ServiceError::Invalid(_))
+    ));
+}
+
+#[test]
+fn created_invoices_bill_the_drafted_time() {
+    use time_tracker::orm::schema::{invoice_activity, time};
+
+    let mut conn = unbilled();
+    let period = "2026-09".parse::<Period>().unwrap();
+
+    let drafts = draft(&mut conn, "2026-09", GroupRule::Project);
+    let inv_num = next_invoice_num(&mut conn).unwrap();
+    assert_eq!(inv_num, 4);
+
+    let invoice = new_invoice(inv_num, &period, "ACME".to_owned());
+    assert_eq!((invoice.inv_start, invoice.inv_end), (None, None));
+    create_invoice(&mut conn, invoice, 85.0, drafts).unwrap();
+
+    let billed: Vec<(String, f64, String)> = time::table
+        .inner_join(invoice_activity::table)
+        .filter(invoice_activity::inv_num.eq(inv_num))
+        .order(time::time_start)
+        .select((invoice_activity::act_desc, invoice_activity::act_uprice, time::time_desc))
+        .load(&mut conn)
+        .unwrap();
+    let billed: Vec<(&str, f64, &str)> = billed.iter()
+        .map(|(act, uprice, desc)| (act.as_str(), *uprice, desc.as_str()))
+        .collect();
+    assert_eq!(billed, [
+        ("Plan next sprint", 85.0, "Plan next sprint"),
+        ("Alpha", 85.0, "Fix login timeout"),
+        ("Alpha", 85.0, "Export to PDF"),
+        ("Xylophone", 85.0, "Chart colours"),
+        ("Plan next sprint", 85.0, "Plan next sprint"),
+    ]);
+
+    // Nothing is left to bill for the period.
+    assert!(matches!(
+        draft_activities(&mut conn, &period, "ACME", GroupRule::Project),
+        Err(ServiceError::Invalid(_))
+    ));
+
+    // Periods other than a month are kept with the invoice.
+    let invoice = new_invoice(5, &"2026-09-03..2026-09-08".parse().unwrap(), "ACME".to_owned());
+    assert_eq!((invoice.inv_start, invoice.inv_end), (Some(sep(3)), Some(sep(8))));
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{entry, ticket};
+use time_tracker::cli::args::TimeFilter;
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::time::{delete_time, log_time, select_time, select_times, update_time};
+
+fn oct(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+}
+
+#[test]
+fn logged_time_is_stored_with_its_tickets() {
+    let mut conn = common::seeded();
+
+    // A ticket listed twice is only related once.
+    let time = log_time(&mut conn, entry(oct(5), 9, 11, "Fix login", vec![
+        ticket("ABC", 14),
+        ticket("ABC", 12),
+        ticket("ABC", 14),
+    ])).unwrap();
+
+    let logged = select_time(&mut conn, time.time_id).unwrap();
+    assert_eq!(logged.time_desc, "Fix login");
+    assert_eq!(logged.time_dur, Some(2.0));
+    assert_eq!(logged.act_num, None);
+    assert_eq!(logged.tickets, vec![ticket("ABC", 12), ticket("ABC", 14)]);
+}
+
+#[test]
+fn invalid_entries_are_rejected() {
+    let mut conn = common::seeded();
+
+    let error = log_time(&mut conn, entry(oct(5), 9, 10, "Unknown", vec![ticket("NOPE", 1)]))
+        .unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+    assert_eq!(error.to_string(), "No project exists with key 'NOPE'");
+
+    let error = log_time(&mut conn, entry(oct(5), 10, 9, "Backwards", vec![])).unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+
+    assert_eq!(select_times(&mut conn, TimeFilter::default(), None).unwrap().len(), 5);
+}
+
+#[test]
+fn updating_replaces_the_entry_and_its_tickets() {
+    let mut conn = common::seeded();
+
+    update_time(&mut conn, 2, entry(oct(6), 13, 16, "Fix login redirect", vec![ticket("XYZ", 3)]))
+        .unwrap();
+
+    let updated = select_time(&mut conn, 2).unwrap();
+    assert_eq!(updated.time_dur, Some(3.0));
+    assert_eq!(updated.tickets, vec![ticket("XYZ", 3)]);
+
+    let error = update_time(&mut conn, 99, entry(oct(6), 9, 10, "Missing", vec![])).unwrap_err();
+    assert!(matches!(error, ServiceError::NotFound(_)));
+}
+
+#[test]
+fn notes_are_kept_apart_from_the_description() {
+    let mut conn = common::seeded();
+
+    let notes = "Database ran out of disk.\nCleared old backups.";
+
+    let mut noted = entry(oct(5), 9, 10, "Outage", vec![]);
+    noted.time.time_notes = Some(notes.to_owned());
+    let time = log_time(&mut conn, noted).unwrap();
+
+    let logged = select_time(&mut conn, time.time_id).unwrap();
+    assert_eq!(logged.time_desc, "Outage");
+    assert_eq!(logged.time_notes.as_deref(), Some(notes));
+
+    // Updating with no notes removes them.
+    update_time(&mut conn, time.time_id, entry(oct(5), 9, 10, "Outage", vec![])).unwrap();
+    assert_eq!(select_time(&mut conn, time.time_id).unwrap().time_notes, None);
+}
+
+#[test]
+fn deleting_removes_the_entry_and_its_tickets() {
+    let mut conn = common::seeded();
+
+    delete_time(&mut conn, 2).unwrap();
+
+    assert!(matches!(select_time(&mut conn, 2), Err(ServiceError::NotFound(_))));
+    assert!(matches!(delete_time(&mut conn, 2), Err(ServiceError::NotFound(_))));
+
+    // Other entries on the same tickets are untouched.
+    assert_eq!(select_time(&mut conn, 1).unwrap().tickets, vec![ticket("ABC", 12)]);
+}
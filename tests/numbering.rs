@@ -0,0 +1,124 @@
+mod common;
+
+use chrono::NaiveDate;
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use time_tracker::cli::args::{DocIdentifier, GroupRule};
+use time_tracker::orm::insert::NewInvoice;
+use time_tracker::orm::model::Invoice;
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::invoice::{create_invoice, draft_activities, next_invoice_num};
+use time_tracker::service::numbering::{NumberFormat, remove_scheme, set_scheme};
+use time_tracker::service::time::log_time;
+use time_tracker::util::date::{Month, Period};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// Log an hour on a day and invoice it for that day's month.
+fn invoice_day(conn: &mut SqliteConnection, day: NaiveDate, recip_id: &str) -> Invoice {
+    let tickets = vec![common::ticket("ABC", 20)];
+    log_time(conn, common::entry(day, 9, 10, "Fix export", tickets)).unwrap();
+
+    let period = Period::from(Month::containing(day));
+    let drafts = draft_activities(conn, &period, recip_id, GroupRule::Project).unwrap();
+    let inv_num = next_invoice_num(conn).unwrap();
+
+    create_invoice(conn, NewInvoice {
+        inv_num,
+        inv_month: period.first(),
+        recip_id: recip_id.to_owned(),
+        inv_created: None,
+        inv_start: None,
+        inv_end: None,
+    }, 80.0, drafts).unwrap()
+}
+
+#[test]
+fn formats_have_one_sequence_and_known_placeholders() {
+    let format: NumberFormat = "INV-{year}-{seq:4}".parse().unwrap();
+    let month: Month = "2026-03".parse().unwrap();
+    assert_eq!(format.number(&month, "ACME", 42), "INV-2026-0042");
+    assert_eq!(format.series(&month, "ACME"), "INV-2026-{seq}");
+    assert_eq!(format.to_string(), "INV-{year}-{seq:4}");
+
+    let format: NumberFormat = "{recip}_{month}-{seq}".parse().unwrap();
+    assert_eq!(format.number(&month, "ACME", 7), "ACME_03-7");
+
+    for invalid in ["INV-{year}", "{seq}-{seq}", "INV-{day}-{seq}", "INV-{seq", "INV/{seq}"] {
+        assert!(matches!(invalid.parse::<NumberFormat>(), Err(ServiceError::Invalid(_))));
+    }
+
+    // Numbers that would be taken for an invoice number or a month can't be looked up.
+    assert!("{seq}".parse::<NumberFormat>().is_err());
+    assert!("{year}-{seq:2}".parse::<NumberFormat>().is_err());
+}
+
+#[test]
+fn invoices_are_numbered_without_gaps_in_each_series() {
+    let mut conn = common::seeded();
+    conn.batch_execute("INSERT INTO recipient VALUES ('BETA', 'Beta Inc', 'Somewhere')")
+        .unwrap();
+
+    // Invoices from before there was a scheme keep their plain number.
+    let plain = invoice_day(&mut conn, date(2026, 10, 1), "ACME");
+    assert_eq!((plain.inv_ref.as_deref(), plain.number()), (None, "4".to_owned()));
+
+    set_scheme(&mut conn, None, &"INV-{year}-{seq:4}".parse().unwrap()).unwrap();
+    set_scheme(&mut conn, Some("BETA"), &"B{seq:3}".parse().unwrap()).unwrap();
+
+    let numbers: Vec<String> = [
+        (date(2026, 11, 3), "ACME"),
+        (date(2026, 11, 4), "BETA"),
+        (date(2026, 12, 1), "ACME"),
+        (date(2027, 1, 5), "ACME"),
+        (date(2027, 1, 6), "BETA"),
+    ].into_iter()
+        .map(|(day, recip_id)| invoice_day(&mut conn, day, recip_id).number())
+        .collect();
+
+    // The year starts a new series, but a recipient's own format has no year to reset on.
+    assert_eq!(numbers, ["INV-2026-0001", "B001", "INV-2026-0002", "INV-2027-0001", "B002"]);
+
+    // Without its own scheme, a recipient falls back on the global one.
+    remove_scheme(&mut conn, Some("BETA")).unwrap();
+    assert_eq!(invoice_day(&mut conn, date(2027, 2, 1), "BETA").number(), "INV-2027-0002");
+
+    assert!(matches!(remove_scheme(&mut conn, Some("BETA")), Err(ServiceError::NotFound(_))));
+    assert!(matches!(
+        set_scheme(&mut conn, Some("NONE"), &"N{seq}".parse().unwrap()),
+        Err(ServiceError::Invalid(_))
+    ));
+}
+
+#[test]
+fn invoices_can_be_found_by_their_number() {
+    let mut conn = common::seeded();
+
+    set_scheme(&mut conn, None, &"INV-{year}-{seq:4}".parse().unwrap()).unwrap();
+    let created = invoice_day(&mut conn, date(2026, 10, 1), "ACME");
+
+    let ident: DocIdentifier = "INV-2026-0001".parse().unwrap();
+    assert_eq!(Invoice::select_by_identifier(ident, &mut conn).unwrap().inv_num, created.inv_num);
+
+    let unknown = "INV-2026-0002".parse().unwrap();
+    assert!(matches!(
+        Invoice::select_by_identifier(unknown, &mut conn),
+        Err(ServiceError::NotFound(_))
+    ));
+
+    // Values that don't fit a scheme are most likely mistyped ids or months.
+    for typo in ["2026-13", "INV-26-0001", "INV-2026-"] {
+        assert!(matches!(
+            Invoice::select_by_identifier(typo.parse().unwrap(), &mut conn),
+            Err(ServiceError::Invalid(_))
+        ));
+    }
+
+    // Unless they're explicitly formatted numbers, such as those of a previous scheme.
+    remove_scheme(&mut conn, None).unwrap();
+    let explicit = DocIdentifier::Ref("INV-2026-0001".to_owned());
+    let found = Invoice::select_by_identifier(explicit, &mut conn).unwrap();
+    assert_eq!(found.inv_num, created.inv_num);
+}
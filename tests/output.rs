@@ -0,0 +1,120 @@
+mod common;
+
+use serde_json::{json, Value};
+use time_tracker::cli::args::{DocIdentifier, OutputFormat, ReportGroup, TimeFilter};
+use time_tracker::cli::output::write_items;
+use time_tracker::cli::report::{summarize, write_structured};
+use time_tracker::csv::convert::{ListActivity, ListInvoice, ListTime};
+use time_tracker::orm::query::InvoiceWithActivities;
+use time_tracker::service::time::select_times;
+
+fn output(write: impl FnOnce(&mut Vec<u8>)) -> String {
+    let mut bytes = vec![];
+    write(&mut bytes);
+    String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn json_has_the_items_in_full() {
+    let mut conn = common::seeded();
+    let invoice = InvoiceWithActivities::select_by_identifier(DocIdentifier::Num(1), &mut conn)
+        .unwrap();
+
+    let activities = &invoice.activities;
+    let json = output(|out| {
+        write_items(out, OutputFormat::Json, activities, |a| ListActivity::from(a)).unwrap()
+    });
+
+    // Unlike the flattened rows, tickets stay a list.
+    let json: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json, json!([
+        {
+            "act_num": 1,
+            "inv_num": 1,
+            "act_desc": "Login fixes",
+            "act_uprice": 80.0,
+            "act_dur": 2.5,
+            "act_price": 200.0,
+            "tickets": ["ABC-12", "ABC-14"],
+        },
+        {
+            "act_num": 2,
+            "inv_num": 1,
+            "act_desc": "Hosting",
+            "act_uprice": 25.0,
+            "act_dur": 0.0,
+            "act_price": 25.0,
+            "tickets": [],
+        },
+    ]));
+}
+
+#[test]
+fn ndjson_has_one_item_per_line() {
+    let mut conn = common::seeded();
+    let times = select_times(&mut conn, TimeFilter::default(), Some(1)).unwrap();
+
+    let ndjson = output(|out| {
+        write_items(out, OutputFormat::Ndjson, &times, |t| ListTime::from(t)).unwrap()
+    });
+
+    let lines: Vec<Value> = ndjson.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1]["time_id"], 2);
+    assert_eq!(lines[1]["time_desc"], "Fix login redirect");
+    assert_eq!(lines[1]["tickets"], json!(["ABC-12", "ABC-14"]));
+    assert_eq!(lines[1]["act_num"], 1);
+}
+
+#[test]
+fn csv_has_a_flattened_row_per_item() {
+    let mut conn = common::seeded();
+    let invoice = InvoiceWithActivities::select_by_identifier(DocIdentifier::Num(1), &mut conn)
+        .unwrap();
+
+    let csv = output(|out| {
+        write_items(out, OutputFormat::Csv, &[invoice], |i| ListInvoice::from(i)).unwrap()
+    });
+
+    assert_eq!(csv.lines().collect::<Vec<_>>(), [
+        "inv_num,inv_ref,inv_month,inv_period,inv_created,recip_id,activities,inv_dur,inv_total",
+        "1,,2026-08,2026-08,2026-09-01,ACME,2,2.5,225.0",
+    ]);
+}
+
+#[test]
+fn report_records_are_keyed_by_group() {
+    let mut conn = common::seeded();
+    let by = [ReportGroup::Month, ReportGroup::Project];
+    let report = summarize(&mut conn, &by, TimeFilter::default()).unwrap();
+
+    let ndjson = output(|out| {
+        write_structured(out, OutputFormat::Ndjson, &by, &report).unwrap()
+    });
+    let first: Value = serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+    assert_eq!(first, json!({
+        "month": "2026-08",
+        "project": "ABC",
+        "entries": 2,
+        "hours": 2.5,
+        "billable": 2.5,
+        "billable_ratio": 1.0,
+    }));
+
+    let json = output(|out| {
+        write_structured(out, OutputFormat::Json, &by, &report).unwrap()
+    });
+    let json: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 4);
+    assert_eq!(json[0], first);
+
+    let csv = output(|out| {
+        write_structured(out, OutputFormat::Csv, &by, &report).unwrap()
+    });
+    assert_eq!(csv.lines().take(2).collect::<Vec<_>>(), [
+        "month,project,entries,hours,billable,billable_ratio",
+        "2026-08,ABC,2,2.5,2.5,1.00",
+    ]);
+}
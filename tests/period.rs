@@ -0,0 +1,132 @@
+mod common;
+
+use chrono::NaiveDate;
+use time_tracker::cli::args::GroupRule;
+use time_tracker::orm::insert::{NewEntry, NewInvoice};
+use time_tracker::orm::model::Invoice;
+use time_tracker::service::doctor::{Check, diagnose};
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::invoice::{create_invoice, draft_activities};
+use time_tracker::service::time::log_time;
+use time_tracker::util::date::Period;
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+}
+
+fn entry(month: u32, day: u32, act_num: Option<i32>) -> NewEntry {
+    let mut entry =
+        common::entry(date(month, day), 9, 10, "Fix export", vec![common::ticket("ABC", 20)]);
+    entry.time.act_num = act_num;
+    entry
+}
+
+#[test]
+fn periods_are_months_or_ranges_of_days() {
+    let month: Period = "2026-03".parse().unwrap();
+    assert_eq!((month.first(), month.last()), (date(3, 1), date(3, 31)));
+    assert_eq!(month.to_string(), "2026-03");
+
+    let fortnight: Period = "2026-03-01..2026-03-14".parse().unwrap();
+    assert_eq!(fortnight.month(), None);
+    assert_eq!(fortnight.to_string(), "2026-03-01..2026-03-14");
+
+    // Months at either end cover the whole month.
+    let quarter: Period = "2026-01..2026-03".parse().unwrap();
+    assert_eq!((quarter.first(), quarter.last()), (date(1, 1), date(3, 31)));
+    assert!(quarter.contains(date(3, 31).and_hms_opt(23, 0, 0).unwrap()));
+    assert!(!quarter.contains(date(4, 1).and_hms_opt(0, 0, 0).unwrap()));
+
+    assert_eq!("2026-02..2026-02-28".parse::<Period>().unwrap().to_string(), "2026-02");
+    assert!("2026-03-14..2026-03-01".parse::<Period>().is_err());
+    assert!("March".parse::<Period>().is_err());
+}
+
+#[test]
+fn billed_time_must_fall_within_the_invoice_month() {
+    let mut conn = common::seeded();
+
+    // Activity 4 is on invoice 3, for September.
+    log_time(&mut conn, entry(9, 30, Some(4))).unwrap();
+
+    let error = log_time(&mut conn, entry(10, 1, Some(4))).unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+    assert!(error.to_string().contains("outside the period 2026-09 of invoice 3"));
+
+    assert!(matches!(log_time(&mut conn, entry(9, 30, Some(9))), Err(ServiceError::NotFound(_))));
+}
+
+#[test]
+fn invoices_can_cover_their_own_period() {
+    use diesel::prelude::*;
+    use time_tracker::orm::schema::{invoice, invoice_activity};
+
+    let mut conn = common::seeded();
+
+    log_time(&mut conn, entry(10, 5, None)).unwrap();
+    log_time(&mut conn, entry(10, 20, None)).unwrap();
+
+    let fortnight: Period = "2026-10-01..2026-10-14".parse().unwrap();
+    let drafts = draft_activities(&mut conn, &fortnight, "ACME", GroupRule::Project).unwrap();
+    assert_eq!(drafts.iter().map(|d| d.times.len()).sum::<usize>(), 1);
+
+    create_invoice(&mut conn, NewInvoice {
+        inv_num: 4,
+        inv_month: date(10, 1),
+        recip_id: "ACME".to_owned(),
+        inv_created: None,
+        inv_start: Some(fortnight.first()),
+        inv_end: Some(fortnight.last()),
+    }, 80.0, drafts).unwrap();
+
+    let invoice = Invoice::query().filter(invoice::inv_num.eq(4)).first(&mut conn).unwrap();
+    assert_eq!(invoice.period(), Ok(fortnight));
+
+    // Later in the month is outside the fortnight.
+    let act_num: i32 = invoice_activity::table
+        .filter(invoice_activity::inv_num.eq(4))
+        .select(invoice_activity::act_num)
+        .first(&mut conn)
+        .unwrap();
+    log_time(&mut conn, entry(10, 14, Some(act_num))).unwrap();
+    assert!(matches!(
+        log_time(&mut conn, entry(10, 15, Some(act_num))),
+        Err(ServiceError::Invalid(_))
+    ));
+}
+
+#[test]
+fn damaged_periods_are_rejected_and_reported() {
+    use diesel::connection::SimpleConnection;
+
+    let mut conn = common::seeded();
+
+    // A period needs both of its ends, in order.
+    for (start, end) in [
+        ("'2026-09-01'", "NULL"),
+        ("NULL", "'2026-09-14'"),
+        ("'2026-09-14'", "'2026-09-01'"),
+    ] {
+        let update =
+            format!("UPDATE invoice SET inv_start = {start}, inv_end = {end} WHERE inv_num = 3");
+        assert!(conn.batch_execute(&update).is_err());
+    }
+
+    // Other programs can skip the check, so doctor still reports it.
+    conn.batch_execute("
+        PRAGMA ignore_check_constraints = ON;
+        UPDATE invoice SET inv_start = '2026-09-14', inv_end = '2026-09-01' WHERE inv_num = 3;
+    ").unwrap();
+
+    let problems: Vec<String> = diagnose(&mut conn).unwrap()
+        .into_iter()
+        .filter(|p| p.check == Check::InvoicePeriod)
+        .map(|p| p.description)
+        .collect();
+    assert_eq!(problems, [
+        "Invoice 3 has a period ending on 2026-09-01 before it starts on 2026-09-14",
+    ]);
+
+    // Time isn't checked against the invoice's month instead.
+    assert!(matches!(log_time(&mut conn, entry(9, 3, Some(4))), Err(ServiceError::Invalid(_))));
+}
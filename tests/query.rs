@@ -0,0 +1,112 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use diesel::prelude::*;
+use common::ticket;
+use time_tracker::cli::args::{DocIdentifier, TimeFilter};
+use time_tracker::orm::model::{Invoice, InvoiceActivity};
+use time_tracker::orm::query::{ActivityWithTickets, InvoiceWithActivities};
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::time::select_times;
+
+#[test]
+fn activities_collect_the_tickets_of_their_times() {
+    use time_tracker::orm::schema::invoice_activity;
+
+    let mut conn = common::seeded();
+
+    let activities = ActivityWithTickets::from_query(
+        InvoiceActivity::query().order(invoice_activity::act_num),
+        &mut conn
+    ).unwrap();
+
+    assert_eq!(activities.len(), 4);
+
+    // Both times on the activity count, and the ticket they share is only listed once.
+    let login = &activities[0];
+    assert_eq!(login.act_dur, 2.5);
+    assert_eq!(login.act_price, 200.0);
+    assert_eq!(login.tickets, BTreeSet::from([ticket("ABC", 12), ticket("ABC", 14)]));
+
+    // Activities without time are a flat fee.
+    let hosting = &activities[1];
+    assert_eq!(hosting.act_dur, 0.0);
+    assert_eq!(hosting.act_price, 25.0);
+    assert!(hosting.tickets.is_empty());
+
+    assert_eq!(activities[2].tickets, BTreeSet::from([ticket("XYZ", 3)]));
+}
+
+#[test]
+fn invoices_are_selected_by_number_or_unique_month() {
+    let mut conn = common::seeded();
+
+    let invoice = InvoiceWithActivities::select_by_identifier(DocIdentifier::Num(1), &mut conn)
+        .unwrap();
+    assert_eq!(invoice.activities.len(), 2);
+    assert_eq!(invoice.inv_dur, 2.5);
+    assert_eq!(invoice.inv_total, 225.0);
+
+    let month = DocIdentifier::Month("2026-09".parse().unwrap());
+    assert_eq!(Invoice::select_by_identifier(month, &mut conn).unwrap().inv_num, 3);
+}
+
+#[test]
+fn ambiguous_or_unknown_identifiers_are_errors() {
+    let mut conn = common::seeded();
+
+    let month = DocIdentifier::Month("2026-08".parse().unwrap());
+    let error = InvoiceWithActivities::select_by_identifier(month.clone(), &mut conn).unwrap_err();
+    assert!(matches!(error, ServiceError::Ambiguous(_)));
+    assert_eq!(error.to_string(), "'2026-08' matches 2 invoices, use an invoice number instead");
+    assert!(matches!(
+        Invoice::select_by_identifier(month, &mut conn),
+        Err(ServiceError::Ambiguous(_))
+    ));
+
+    let error = Invoice::select_by_identifier(DocIdentifier::Num(9), &mut conn).unwrap_err();
+    assert!(matches!(error, ServiceError::NotFound(_)));
+    assert_eq!(error.to_string(), "No invoice matches '9'");
+}
+
+#[test]
+fn time_filters_combine() {
+    let mut conn = common::seeded();
+
+    let filter = TimeFilter {
+        ticket: Some(ticket("ABC", 12)),
+        from: Some("2026-08-04".parse().unwrap()),
+        ..Default::default()
+    };
+    let times = select_times(&mut conn, filter, None).unwrap();
+    assert_eq!(times.iter().map(|t| t.time_id).collect::<Vec<_>>(), vec![2, 4]);
+
+    let filter = TimeFilter { unbilled: true, ..Default::default() };
+    let times = select_times(&mut conn, filter, None).unwrap();
+    assert_eq!(times.iter().map(|t| t.time_id).collect::<Vec<_>>(), vec![5]);
+
+    let times = select_times(&mut conn, TimeFilter::default(), Some(1)).unwrap();
+    assert_eq!(times.iter().map(|t| t.time_id).collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+fn descriptions_match_wildcards_literally() {
+    use diesel::connection::SimpleConnection;
+
+    let mut conn = common::seeded();
+    conn.batch_execute("
+        UPDATE time SET time_desc = 'Raise coverage to 100%' WHERE time_id = 4;
+        UPDATE time SET time_desc = 'Rename a_b' WHERE time_id = 5;
+    ").unwrap();
+
+    let matching = |conn: &mut SqliteConnection, desc: &str| -> Vec<i32> {
+        let filter = TimeFilter { desc: Some(desc.to_owned()), ..Default::default() };
+        select_times(conn, filter, None).unwrap().iter().map(|t| t.time_id).collect()
+    };
+
+    assert_eq!(matching(&mut conn, "100%"), vec![4]);
+    assert_eq!(matching(&mut conn, "a_b"), vec![5]);
+    assert_eq!(matching(&mut conn, "login"), vec![1, 2]);
+    assert_eq!(matching(&mut conn, "%"), vec![4]);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use chrono::NaiveDate;
+use time_tracker::cli::args::{ReportGroup, TimeFilter};
+use time_tracker::cli::report::{summarize, Report, Total};
+use time_tracker::orm::insert::NewEntry;
+use time_tracker::service::time::log_time;
+
+fn rows(report: &Report) -> Vec<(Vec<&str>, usize, f64)> {
+    report.rows.iter()
+        .map(|(keys, total)| {
+            (keys.iter().map(String::as_str).collect(), total.entries, total.hours)
+        })
+        .collect()
+}
+
+#[test]
+fn entries_count_towards_each_of_their_tickets() {
+    let mut conn = common::seeded();
+
+    let by = [ReportGroup::Project, ReportGroup::Ticket];
+    let report = summarize(&mut conn, &by, TimeFilter::default()).unwrap();
+
+    // The entry on both ABC-12 and ABC-14 is in both groups, and groups are nested in the order
+    // their keys first appear.
+    assert_eq!(rows(&report), [
+        (vec!["ABC", "ABC-12"], 3, 3.0),
+        (vec!["ABC", "ABC-14"], 1, 1.0),
+        (vec!["XYZ", "XYZ-3"], 1, 2.0),
+        (vec!["", ""], 1, 1.0),
+    ]);
+    assert_eq!(report.total, Total { entries: 5, hours: 6.0, billable: 6.0 });
+}
+
+#[test]
+fn entries_count_towards_each_of_their_tags() {
+    let mut conn = common::seeded();
+
+    let oct = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+    log_time(&mut conn, NewEntry {
+        tags: vec!["meeting".to_owned(), "review".to_owned()],
+        ..common::entry(oct(5), 9, 10, "Sync", vec![common::ticket("ABC", 12)])
+    }).unwrap();
+    log_time(&mut conn, NewEntry {
+        tags: vec!["meeting".to_owned()],
+        billable: Some(false),
+        ..common::entry(oct(6), 9, 11, "Retro", vec![common::ticket("ABC", 12)])
+    }).unwrap();
+
+    let by = [ReportGroup::Month, ReportGroup::Tag];
+    let report = summarize(&mut conn, &by, TimeFilter::default()).unwrap();
+
+    assert_eq!(rows(&report), [
+        (vec!["2026-08", ""], 3, 4.5),
+        (vec!["2026-09", ""], 2, 1.5),
+        (vec!["2026-10", "#meeting"], 2, 3.0),
+        (vec!["2026-10", "#review"], 1, 1.0),
+    ]);
+    assert_eq!(report.rows[2].1.billable, 1.0);
+
+    // The total counts the entry with both tags once.
+    assert_eq!(report.total, Total { entries: 7, hours: 9.0, billable: 7.0 });
+}
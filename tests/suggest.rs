@@ -0,0 +1,64 @@
+mod common;
+
+use chrono::{NaiveDate, TimeDelta};
+use common::{Repo, ticket};
+use time_tracker::git::history::{configured_author, read_commits, sessions};
+
+fn day() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 9, 2).unwrap()
+}
+
+#[test]
+fn commits_are_clustered_by_gap() {
+    let repo = Repo::new("suggest-gap");
+
+    repo.commit("2026-09-01T17:00:00", "ABC-1: The day before");
+    repo.commit("2026-09-02T09:30:00", "ABC-1: Start login page");
+    repo.commit("2026-09-02T10:15:00", "Style login page");
+    repo.commit("2026-09-02T13:00:00", "ABC-2: Fix typo");
+
+    let author = configured_author(repo.path()).unwrap();
+    assert_eq!(author, "dev@example.com");
+
+    let commits = read_commits(repo.path(), &author, day()).unwrap();
+    assert_eq!(commits.len(), 3);
+
+    let sessions = sessions(commits, TimeDelta::minutes(60), TimeDelta::minutes(30));
+    assert_eq!(sessions.len(), 2);
+
+    let morning = &sessions[0];
+    assert_eq!(morning.start, day().and_hms_opt(9, 0, 0).unwrap());
+    assert_eq!(morning.end, day().and_hms_opt(10, 15, 0).unwrap());
+    assert_eq!(morning.tickets(), vec![ticket("ABC", 1)]);
+    assert_eq!(morning.description(), "ABC-1: Start login page; Style login page");
+
+    let afternoon = &sessions[1];
+    assert_eq!(afternoon.end - afternoon.start, TimeDelta::minutes(30));
+    assert_eq!(afternoon.tickets(), vec![ticket("ABC", 2)]);
+}
+
+#[test]
+fn tickets_are_taken_from_branch_names() {
+    let repo = Repo::new("suggest-branch");
+
+    repo.commit("2026-09-02T09:00:00", "Initial commit");
+    repo.git(&["checkout", "-q", "-b", "feature/ABC-7-export"], None);
+    repo.commit("2026-09-02T09:20:00", "Add export button");
+
+    let commits = read_commits(repo.path(), "dev@example.com", day()).unwrap();
+    let sessions = sessions(commits, TimeDelta::minutes(60), TimeDelta::minutes(15));
+
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].commits[1].branch, "feature/ABC-7-export");
+    assert_eq!(sessions[0].tickets(), vec![ticket("ABC", 7)]);
+}
+
+#[test]
+fn other_authors_are_ignored() {
+    let repo = Repo::new("suggest-author");
+
+    repo.commit("2026-09-02T09:00:00", "ABC-1: Mine");
+
+    let commits = read_commits(repo.path(), "someone@example.com", day()).unwrap();
+    assert!(commits.is_empty());
+}
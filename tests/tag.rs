@@ -0,0 +1,45 @@
+mod common;
+
+use diesel::SqliteConnection;
+use time_tracker::cli::args::TimeFilter;
+use time_tracker::orm::insert::{LoggedTime, NewEntry};
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::time::{delete_time, log_time, select_time, select_times};
+
+fn retag(conn: &mut SqliteConnection, time_id: i32, tags: &[&str]) -> NewEntry {
+    let time = select_time(conn, time_id).unwrap();
+
+    NewEntry {
+        time: LoggedTime {
+            time_start: *time.time_start,
+            time_end: *time.time_end,
+            time_desc: time.time_desc,
+            act_num: time.act_num,
+            time_notes: time.time_notes,
+        },
+        tickets: time.tickets,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        billable: Some(time.time_billable),
+    }
+}
+
+#[test]
+fn tags_are_created_on_use_and_filtered_by() {
+    let mut conn = common::seeded();
+
+    let entry = retag(&mut conn, 5, &["meeting", "planning", "meeting"]);
+    let time = log_time(&mut conn, entry).unwrap();
+    assert_eq!(select_time(&mut conn, time.time_id).unwrap().tags, vec!["meeting", "planning"]);
+
+    let filter = TimeFilter { tag: Some("planning".to_owned()), ..Default::default() };
+    let times = select_times(&mut conn, filter, None).unwrap();
+    assert_eq!(times.iter().map(|t| t.time_id).collect::<Vec<_>>(), vec![time.time_id]);
+
+    let entry = retag(&mut conn, 5, &["two words"]);
+    let error = log_time(&mut conn, entry).unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+
+    delete_time(&mut conn, time.time_id).unwrap();
+    let filter = TimeFilter { tag: Some("planning".to_owned()), ..Default::default() };
+    assert!(select_times(&mut conn, filter, None).unwrap().is_empty());
+}
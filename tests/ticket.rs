@@ -0,0 +1,73 @@
+mod common;
+
+use time_tracker::orm::insert::TicketChanges;
+use time_tracker::orm::model::TicketDetails;
+use time_tracker::orm::ticket::Ticket;
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::ticket::{add_ticket, edit_ticket, select_tickets};
+
+fn details(proj_key: &str, tick_num: i32, title: &str) -> TicketDetails {
+    TicketDetails {
+        proj_key: proj_key.to_owned(),
+        tick_num,
+        tick_title: title.to_owned(),
+        tick_url: None,
+        tick_status: "open".to_owned(),
+        tick_estimate: Some(2.0),
+    }
+}
+
+#[test]
+fn tickets_are_listed_with_logged_hours() {
+    let mut conn = common::seeded();
+
+    add_ticket(&mut conn, details("ABC", 14, "Redirect after login")).unwrap();
+    add_ticket(&mut conn, details("XYZ", 9, "Not started")).unwrap();
+
+    let tickets = select_tickets(&mut conn, None, None).unwrap();
+    let logged: Vec<_> = tickets.iter()
+        .map(|t| (t.details.ticket().to_string(), t.tick_logged))
+        .collect();
+    assert_eq!(logged, vec![
+        ("ABC-12".to_owned(), 3.0),
+        ("ABC-14".to_owned(), 1.0),
+        ("XYZ-9".to_owned(), 0.0),
+    ]);
+
+    let open = select_tickets(&mut conn, Some("ABC".to_owned()), Some("open".to_owned())).unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].details.tick_title, "Redirect after login");
+}
+
+#[test]
+fn tickets_are_only_added_once_and_to_known_projects() {
+    let mut conn = common::seeded();
+
+    let error = add_ticket(&mut conn, details("ABC", 12, "Again")).unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+    assert_eq!(error.to_string(), "Ticket ABC-12 already exists, edit it instead");
+
+    let error = add_ticket(&mut conn, details("NOPE", 1, "Unknown")).unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+}
+
+#[test]
+fn editing_only_changes_given_fields() {
+    let mut conn = common::seeded();
+    let key = Ticket { proj_key: "ABC".to_owned(), tick_num: 12 };
+
+    let edited = edit_ticket(&mut conn, &key, TicketChanges {
+        tick_status: Some("open".to_owned()),
+        ..Default::default()
+    }).unwrap();
+    assert_eq!(edited.tick_status, "open");
+    assert_eq!(edited.tick_title, "Fix login timeout");
+    assert_eq!(edited.tick_estimate, Some(3.0));
+
+    let error = edit_ticket(&mut conn, &key, TicketChanges::default()).unwrap_err();
+    assert!(matches!(error, ServiceError::Invalid(_)));
+
+    let missing = Ticket { proj_key: "ABC".to_owned(), tick_num: 99 };
+    let changes = TicketChanges { tick_title: Some("Missing".to_owned()), ..Default::default() };
+    assert!(matches!(edit_ticket(&mut conn, &missing, changes), Err(ServiceError::NotFound(_))));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use common::ticket;
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use time_tracker::cli::args::HookCheckoutArgs;
+use time_tracker::cli::hook::hook_checkout;
+use time_tracker::cli::timer::now;
+use time_tracker::orm::model::Time;
+use time_tracker::orm::ticket::Ticket;
+use time_tracker::service::error::ServiceError;
+use time_tracker::service::timer::{
+    attach_tickets, running_timer, split_timer, start_timer, stop_timer, timer_tickets
+};
+
+fn at(hour: u32, min: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 9, 2).unwrap().and_hms_opt(hour, min, 0).unwrap()
+}
+
+/// An empty database with the projects `ABC` and `XYZ`.
+fn database() -> SqliteConnection {
+    let mut conn = common::database();
+    conn.batch_execute("INSERT INTO project VALUES ('ABC', 'Alpha', TRUE), ('XYZ', 'X', TRUE)")
+        .unwrap();
+    conn
+}
+
+fn logged_tickets(conn: &mut SqliteConnection, time: &Time) -> Vec<Ticket> {
+    use time_tracker::orm::schema::ticket_time;
+
+    let mut tickets: Vec<Ticket> = ticket_time::table
+        .filter(ticket_time::time_id.eq(time.time_id))
+        .select((ticket_time::proj_key, ticket_time::tick_num))
+        .load::<(String, i32)>(conn)
+        .unwrap()
+        .into_iter()
+        .map(|(proj_key, tick_num)| Ticket { proj_key, tick_num })
+        .collect();
+    tickets.sort();
+    tickets
+}
+
+#[test]
+fn tickets_attached_before_starting_are_logged_with_the_timer() {
+    let mut conn = database();
+
+    attach_tickets(&mut conn, vec![ticket("ABC", 12)]).unwrap();
+    // Attaching a ticket twice is fine, but unknown projects aren't.
+    attach_tickets(&mut conn, vec![ticket("ABC", 12)]).unwrap();
+    assert!(matches!(
+        attach_tickets(&mut conn, vec![ticket("NOPE", 1)]),
+        Err(ServiceError::Invalid(_))
+    ));
+
+    start_timer(&mut conn, "Fix login".to_owned(), vec![ticket("XYZ", 3)], at(9, 0)).unwrap();
+    assert_eq!(timer_tickets(&mut conn).unwrap(), [ticket("ABC", 12), ticket("XYZ", 3)]);
+
+    let time = stop_timer(&mut conn, at(10, 30)).unwrap();
+    assert_eq!((*time.time_start, *time.time_end), (at(9, 0), at(10, 30)));
+    assert_eq!(time.time_desc, "Fix login");
+    assert_eq!(logged_tickets(&mut conn, &time), [ticket("ABC", 12), ticket("XYZ", 3)]);
+
+    // Stopping clears the timer and its tickets.
+    assert!(running_timer(&mut conn).unwrap().is_none());
+    assert!(timer_tickets(&mut conn).unwrap().is_empty());
+}
+
+#[test]
+fn only_one_timer_runs_at_a_time() {
+    let mut conn = database();
+
+    assert!(matches!(stop_timer(&mut conn, at(9, 0)), Err(ServiceError::NotFound(_))));
+
+    start_timer(&mut conn, "First".to_owned(), vec![], at(9, 0)).unwrap();
+    assert!(matches!(
+        start_timer(&mut conn, "Second".to_owned(), vec![], at(9, 30)),
+        Err(ServiceError::Invalid(_))
+    ));
+
+    // The schema doesn't allow a second timer either.
+    assert!(conn.batch_execute(
+        "INSERT INTO timer (timer_start, timer_desc) VALUES ('2026-09-02 10:00:00', 'Second')"
+    ).is_err());
+
+    // A timer that hasn't run for a minute has nothing to log, and keeps running.
+    assert!(matches!(stop_timer(&mut conn, at(9, 0)), Err(ServiceError::Invalid(_))));
+    assert_eq!(running_timer(&mut conn).unwrap().unwrap().timer_desc, "First");
+}
+
+#[test]
+fn splitting_logs_the_timer_and_continues_on_other_tickets() {
+    let mut conn = database();
+
+    assert!(matches!(
+        split_timer(&mut conn, vec![], at(9, 0)),
+        Err(ServiceError::NotFound(_))
+    ));
+
+    start_timer(&mut conn, "Fix login".to_owned(), vec![ticket("ABC", 12)], at(9, 0)).unwrap();
+
+    let logged = split_timer(&mut conn, vec![ticket("XYZ", 3)], at(10, 0)).unwrap().unwrap();
+    assert_eq!(logged_tickets(&mut conn, &logged), [ticket("ABC", 12)]);
+
+    let running = running_timer(&mut conn).unwrap().unwrap();
+    assert_eq!((*running.timer_start, running.timer_desc.as_str()), (at(10, 0), "Fix login"));
+    assert_eq!(timer_tickets(&mut conn).unwrap(), [ticket("XYZ", 3)]);
+
+    // A timer that only just started is restarted on the new tickets instead.
+    assert!(split_timer(&mut conn, vec![ticket("ABC", 14)], at(10, 0)).unwrap().is_none());
+    assert_eq!(timer_tickets(&mut conn).unwrap(), [ticket("ABC", 14)]);
+}
+
+#[test]
+fn checkouts_attach_tickets_of_known_projects() {
+    let mut conn = database();
+
+    let checkout = |branch: &str, split| HookCheckoutArgs { branch: branch.to_owned(), split };
+
+    // Without a running timer, the tickets wait for the next one, even when splitting.
+    hook_checkout(&mut conn, checkout("feature/ABC-12-login", false)).unwrap();
+    hook_checkout(&mut conn, checkout("release-2", true)).unwrap();
+    assert_eq!(timer_tickets(&mut conn).unwrap(), [ticket("ABC", 12)]);
+
+    start_timer(&mut conn, "Review".to_owned(), vec![], now() - TimeDelta::hours(1)).unwrap();
+    hook_checkout(&mut conn, checkout("XYZ-3-export", true)).unwrap();
+
+    let logged = Time::query().load(&mut conn).unwrap();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged_tickets(&mut conn, &logged[0]), [ticket("ABC", 12)]);
+    assert_eq!(timer_tickets(&mut conn).unwrap(), [ticket("XYZ", 3)]);
+}
@@ -0,0 +1,144 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use ratatui::{Terminal, backend::TestBackend};
+use time_tracker::orm::query::TimeWithTickets;
+use time_tracker::orm::ticket::Ticket;
+use time_tracker::tui::app::{App, Effect, Key, Mode, Period};
+use time_tracker::tui::view::draw;
+
+fn at(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 9, day).unwrap().and_hms_opt(hour, min, 0).unwrap()
+}
+
+fn time(time_id: i32, day: u32, start: u32, end: u32, desc: &str) -> TimeWithTickets {
+    TimeWithTickets {
+        time_id,
+        time_start: at(day, start, 0).into(),
+        time_end: at(day, end, 0).into(),
+        time_desc: desc.to_owned(),
+        time_dur: Some((end - start) as f64),
+        act_num: None,
+        tickets: vec![Ticket { proj_key: "ABC".to_owned(), tick_num: time_id }],
+        tags: vec![],
+        time_billable: true,
+        time_notes: None,
+    }
+}
+
+/// An app showing the week of Wednesday 2026-09-02, with two entries loaded.
+fn app() -> App {
+    let mut app = App::new(NaiveDate::from_ymd_opt(2026, 9, 2).unwrap());
+    app.load(vec![time(1, 1, 9, 11, "Planning"), time(2, 2, 13, 14, "Review")], vec![]);
+    app
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        assert!(app.handle(Key::Char(c)).is_none());
+    }
+}
+
+fn clear_input(app: &mut App) {
+    while matches!(&app.mode, Mode::Edit { input, .. } if !input.is_empty()) {
+        app.handle(Key::Backspace);
+    }
+}
+
+#[test]
+fn navigation_selects_entries_and_changes_period() {
+    let mut app = app();
+
+    let monday = NaiveDate::from_ymd_opt(2026, 8, 31).unwrap();
+    assert_eq!(app.days(), (monday, at(6, 0, 0).date()));
+
+    app.handle(Key::Down);
+    app.handle(Key::Down);
+    assert_eq!(app.selected, 1, "Selection stops at the last entry");
+    app.handle(Key::Char('k'));
+    assert_eq!(app.selected, 0);
+
+    assert!(matches!(app.handle(Key::Right), Some(Effect::Reload)));
+    assert_eq!(app.date, at(9, 0, 0).date());
+
+    assert!(matches!(app.handle(Key::Char('v')), Some(Effect::Reload)));
+    assert_eq!(app.period, Period::Day);
+    assert_eq!(app.days(), (at(9, 0, 0).date(), at(9, 0, 0).date()));
+
+    app.handle(Key::Left);
+    assert_eq!(app.date, at(8, 0, 0).date());
+
+    assert!(matches!(app.handle(Key::Char('q')), Some(Effect::Quit)));
+}
+
+#[test]
+fn editing_produces_updated_entries() {
+    let mut app = app();
+    app.handle(Key::Down);
+
+    app.handle(Key::Char('e'));
+    clear_input(&mut app);
+    type_text(&mut app, "Code review");
+
+    let Some(Effect::Save { time_id, entry }) = app.handle(Key::Enter) else {
+        panic!("Expected the edit to be saved");
+    };
+    assert_eq!(time_id, 2);
+    assert_eq!(entry.time.time_desc, "Code review");
+    assert_eq!(entry.time.time_start, at(2, 13, 0));
+    assert_eq!(app.mode, Mode::Browse);
+
+    app.handle(Key::Char('r'));
+    clear_input(&mut app);
+    type_text(&mut app, "13:30-15:00");
+    let Some(Effect::Save { entry, .. }) = app.handle(Key::Enter) else { panic!() };
+    assert_eq!(entry.time.time_start, at(2, 13, 30));
+    assert_eq!(entry.time.time_end, at(2, 15, 0));
+
+    app.handle(Key::Char('T'));
+    type_text(&mut app, " XYZ-7");
+    let Some(Effect::Save { entry, .. }) = app.handle(Key::Enter) else { panic!() };
+    assert_eq!(entry.tickets, vec![
+        Ticket { proj_key: "ABC".to_owned(), tick_num: 2 },
+        Ticket { proj_key: "XYZ".to_owned(), tick_num: 7 },
+    ]);
+
+    app.handle(Key::Char('a'));
+    type_text(&mut app, "4");
+    let Some(Effect::Save { entry, .. }) = app.handle(Key::Enter) else { panic!() };
+    assert_eq!(entry.time.act_num, Some(4));
+}
+
+#[test]
+fn invalid_input_keeps_the_editor_open() {
+    let mut app = app();
+
+    app.handle(Key::Char('r'));
+    clear_input(&mut app);
+    type_text(&mut app, "9am");
+
+    assert!(app.handle(Key::Enter).is_none());
+    assert!(matches!(app.mode, Mode::Edit { .. }));
+    assert!(app.message.is_some());
+
+    assert!(app.handle(Key::Esc).is_none());
+    assert_eq!(app.mode, Mode::Browse);
+}
+
+#[test]
+fn week_renders_without_a_terminal() {
+    let app = app();
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+
+    terminal.draw(|frame| draw(frame, &app)).unwrap();
+
+    let screen: String = terminal.backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+
+    assert!(screen.contains("2026-W36 (3.0h)"));
+    assert!(screen.contains("Tue 01"));
+    assert!(screen.contains("13:00-14:00"));
+    assert!(screen.contains("No invoices this month"));
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use chrono::{Days, Local, NaiveDate};
+use diesel::connection::SimpleConnection;
+use time_tracker::cli::args::{GroupRule, OutputFormat, TimeFilter, UnbilledArgs};
+use time_tracker::cli::list::{list_unbilled, unbilled_groups, UnbilledGroup};
+use time_tracker::service::time::{log_time, select_times};
+
+use common::ticket;
+
+fn group(month: &str, group: &str, entries: usize, hours: f64) -> UnbilledGroup {
+    UnbilledGroup { month: month.parse().unwrap(), group: group.to_owned(), entries, hours }
+}
+
+#[test]
+fn unbilled_time_is_grouped_by_month_first() {
+    let mut conn = common::seeded();
+
+    let oct = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+    log_time(&mut conn, common::entry(oct(5), 9, 11, "Export", vec![ticket("XYZ", 4)])).unwrap();
+    log_time(&mut conn, common::entry(oct(6), 9, 12, "Login", vec![ticket("ABC", 12)])).unwrap();
+    log_time(&mut conn, common::entry(oct(7), 9, 10, "Login", vec![ticket("ABC", 14)])).unwrap();
+
+    let filter = TimeFilter { unbilled: true, ..Default::default() };
+    let times = select_times(&mut conn, filter, None).unwrap();
+
+    // The September entry has no tickets, so it has no project either.
+    assert_eq!(unbilled_groups(&times, GroupRule::Project), [
+        group("2026-09", "", 1, 1.0),
+        group("2026-10", "ABC", 2, 4.0),
+        group("2026-10", "XYZ", 1, 2.0),
+    ]);
+    assert_eq!(unbilled_groups(&times, GroupRule::Ticket), [
+        group("2026-09", "", 1, 1.0),
+        group("2026-10", "ABC-12", 1, 3.0),
+        group("2026-10", "ABC-14", 1, 1.0),
+        group("2026-10", "XYZ-4", 1, 2.0),
+    ]);
+    assert_eq!(unbilled_groups(&times, GroupRule::Desc), [
+        group("2026-09", "Plan next sprint", 1, 1.0),
+        group("2026-10", "Export", 1, 2.0),
+        group("2026-10", "Login", 2, 4.0),
+    ]);
+}
+
+#[test]
+fn old_unbilled_time_fails_the_listing() {
+    let mut conn = common::database();
+    conn.batch_execute("INSERT INTO project VALUES ('ABC', 'Alpha', TRUE)").unwrap();
+
+    let today = Local::now().date_naive();
+    let ago = |days| today.checked_sub_days(Days::new(days)).unwrap();
+    log_time(&mut conn, common::entry(ago(1), 9, 10, "Recent", vec![ticket("ABC", 1)])).unwrap();
+    log_time(&mut conn, common::entry(ago(10), 9, 10, "Old", vec![ticket("ABC", 1)])).unwrap();
+    log_time(&mut conn, common::entry(ago(20), 9, 10, "Older", vec![ticket("ABC", 1)])).unwrap();
+
+    let mut list = |older_than| list_unbilled(&mut conn, UnbilledArgs {
+        group_by: GroupRule::Project,
+        older_than,
+    }, OutputFormat::Json);
+
+    assert!(list(None).is_ok());
+    assert!(list(Some(30)).is_ok());
+    assert_eq!(
+        list(Some(5)).unwrap_err().to_string(),
+        "Found 2 unbilled entries older than 5 days"
+    );
+    assert_eq!(
+        list(Some(15)).unwrap_err().to_string(),
+        "Found 1 unbilled entries older than 15 days"
+    );
+}